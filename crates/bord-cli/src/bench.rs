@@ -0,0 +1,70 @@
+use bord_engine::graph::Chain;
+use std::time::Instant;
+
+/// Sample rate used for the CPU% estimate.
+const BENCH_SR: u32 = 48_000;
+
+pub struct BenchOpts<'a> {
+    pub effect: &'a str,
+    pub block_size: usize,
+    pub iterations: usize,
+    pub channels: u16,
+}
+
+/// Run `effect` over a noise block `iterations` times and print a results table.
+pub fn run(opts: &BenchOpts) -> Result<(), String> {
+    let fx = bord_engine::dsp::effect_by_name(opts.effect)
+        .ok_or_else(|| format!("Unknown effect: {}", opts.effect))?;
+    if opts.block_size == 0 || opts.iterations == 0 || opts.channels == 0 {
+        return Err("block size, iterations and channels must be > 0".into());
+    }
+    let mut chain = Chain::new(BENCH_SR, opts.channels);
+    chain.set_block_size(opts.block_size as u32);
+    chain.push(fx).map_err(|e| e.to_string())?;
+
+    let len = opts.block_size * opts.channels as usize;
+    let mut rng = 0x9E37_79B9u32;
+    let noise: Vec<f32> = (0..len)
+        .map(|_| {
+            // xorshift32: deterministic, no extra dependency
+            rng ^= rng << 13;
+            rng ^= rng >> 17;
+            rng ^= rng << 5;
+            (rng as f32 / u32::MAX as f32) * 2.0 - 1.0
+        })
+        .collect();
+    let mut block = noise.clone();
+
+    let started = Instant::now();
+    for _ in 0..opts.iterations {
+        block.copy_from_slice(&noise);
+        chain.process(&mut block);
+    }
+    let total = started.elapsed().as_secs_f64();
+
+    let mean_s = total / opts.iterations as f64;
+    let samples_per_sec = (len * opts.iterations) as f64 / total;
+    let budget_s = opts.block_size as f64 / BENCH_SR as f64;
+    let cpu_pct = 100.0 * mean_s / budget_s;
+
+    println!("{:<12} {:>6} {:>4} {:>12} {:>16} {:>8}", "effect", "block", "ch", "mean (us)", "samples/sec", "cpu%");
+    println!(
+        "{:<12} {:>6} {:>4} {:>12.3} {:>16.0} {:>8.3}",
+        chain.effect_name(0).unwrap_or(opts.effect),
+        opts.block_size,
+        opts.channels,
+        mean_s * 1e6,
+        samples_per_sec,
+        cpu_pct
+    );
+
+    let dry = chain.dry_run(BENCH_SR, opts.block_size, opts.iterations);
+    println!(
+        "preflight (silence): mean {:.3} us, max {:.3} us, budget {:.3} us -> {}",
+        dry.mean_us,
+        dry.max_us,
+        dry.budget_us,
+        if dry.is_realtime_safe { "realtime safe" } else { "NOT realtime safe" }
+    );
+    Ok(())
+}
@@ -1,3 +1,11 @@
+mod bench;
+mod monitor;
+mod repl;
+mod route;
+mod scope;
+mod spectrum;
+
+use bord_engine::dsp::silence::SilenceEvent;
 use clap::{Args, Parser, Subcommand};
 
 #[derive(Parser)]
@@ -10,9 +18,89 @@ struct Cli {
 #[derive(Subcommand)]
 enum Command {
     /// List audio devices
-    Devices,
+    Devices {
+        /// Host API to list instead of the default one (e.g. wasapi, asio)
+        #[arg(long)]
+        host: Option<String>,
+        /// Also show each device's default buffer latency
+        #[arg(short, long)]
+        verbose: bool,
+    },
     /// Start audio (passthrough + Gain effect for now)
-    Run(RunArgs),
+    Run(RunCmdArgs),
+    /// Start audio and control it from an interactive prompt
+    Repl(ReplArgs),
+    /// Route one device's input to another device's output through effects
+    Route(RouteArgs),
+    /// Play the input back with as little latency as the devices allow
+    Monitor(MonitorArgs),
+    /// Show the input's frequency spectrum live in the terminal
+    Spectrum(SpectrumArgs),
+    /// Show the state of a running `bord run`
+    Status {
+        /// Print the chain as Graphviz DOT instead (pipe into `dot -Tsvg`)
+        #[arg(long)]
+        dot: bool,
+        /// Also list the heap each effect holds
+        #[arg(long, conflicts_with = "dot")]
+        memory: bool,
+    },
+    /// Draw the effect chain of a running `bord run` (or of a saved session)
+    Graph {
+        /// Draw the chain this session file would build instead
+        #[arg(long)]
+        session: Option<std::path::PathBuf>,
+    },
+    /// Change the Gain effect of a running `bord run`
+    SetGain {
+        /// Gain in dB (e.g., -6.0)
+        #[arg(allow_hyphen_values = true)]
+        db: f32,
+    },
+    /// Change a parameter of an effect in a running `bord run`
+    SetParam {
+        /// Position of the effect in the chain (see `bord status`)
+        effect_index: usize,
+        /// Parameter key, e.g. cutoff_hz
+        param_id: String,
+        /// New value
+        #[arg(allow_hyphen_values = true, required_unless_present = "reset")]
+        value: Option<f32>,
+        /// Set the parameter back to its default instead
+        #[arg(long, conflicts_with = "value")]
+        reset: bool,
+    },
+    /// Manage effect presets in ~/.config/bord/presets
+    Presets {
+        #[command(subcommand)]
+        cmd: PresetsCommand,
+    },
+    /// Measure the CPU cost of an effect (no audio hardware is opened)
+    Bench(BenchArgs),
+    /// Measure the output -> input frequency response, or run with an EQ correcting it
+    Calibrate(CalibrateArgs),
+    /// Check a TOML/JSON config file against the bord config schema
+    Validate {
+        /// Path to the config file (.toml or .json)
+        #[arg(long = "config")]
+        config: std::path::PathBuf,
+    },
+    /// Print the config JSON Schema generated from the engine's types
+    #[cfg(feature = "schema-gen")]
+    #[command(hide = true)]
+    GenSchema,
+}
+
+#[derive(Subcommand)]
+enum PresetsCommand {
+    /// List saved presets
+    List,
+    /// Save the chain of a running `bord run` as a preset
+    Save { name: String },
+    /// Replace the chain of a running `bord run` with a preset
+    Load { name: String },
+    /// Delete a saved preset
+    Delete { name: String },
 }
 
 #[derive(Args, Debug)]
@@ -41,38 +129,603 @@ struct RunArgs {
     #[arg(long = "block")]
     block_size: Option<u32>,
 
+    /// Channel count, instead of the output device's default (e.g., 2 on an 8-channel interface)
+    #[arg(long = "channels")]
+    channels: Option<u16>,
+
+    /// When the devices' channel counts differ: ForceOutputChannels (default),
+    /// ForceInputChannels, InsertConverter or RequireMatch
+    #[arg(long = "channel-alignment", default_value = "ForceOutputChannels")]
+    channel_alignment: bord_engine::ChannelAlignmentPolicy,
+
     /// Simple test effect: gain in dB (e.g., -6.0, 0.0, +6.0)
     #[arg(long = "gain-db", default_value_t = 0.0)]
     gain_db: f32,
+
+    /// Normalize each output block's peak to this level in dBFS (e.g., -1.0)
+    #[arg(long = "normalize-db", allow_hyphen_values = true)]
+    normalize_db: Option<f32>,
+
+    /// Output silence without processing while the input RMS is below this level in dBFS (e.g., -60.0)
+    #[arg(long = "silence-threshold-db", allow_hyphen_values = true)]
+    silence_threshold_db: Option<f32>,
+
+    /// Record the processed output to a WAV file
+    #[arg(long = "record")]
+    record: Option<std::path::PathBuf>,
+
+    /// JSON file listing the effects to run after the gain stage
+    #[arg(long = "chain-file")]
+    chain_file: Option<std::path::PathBuf>,
+
+    /// Real-time priority for audio threads (1-99, SCHED_FIFO on Linux)
+    #[arg(long = "rt-priority", value_parser = clap::value_parser!(u8).range(1..=99))]
+    rt_priority: Option<u8>,
+
+    /// Fail instead of falling back to the default device when --in/--out/--in-idx/--out-idx match nothing
+    #[arg(long = "strict-device")]
+    strict_device: bool,
+
+    /// Open the devices in WASAPI exclusive mode, bypassing the Windows mixer (ignored elsewhere)
+    #[arg(long = "exclusive")]
+    exclusive: bool,
+
+    /// ASIO driver to open for both input and output (Windows; see `bord devices --host asio`)
+    #[arg(long = "asio-driver")]
+    asio_driver: Option<String>,
+
+    /// Audio host API to search (e.g., ASIO, WASAPI, ALSA); repeat to try several in order
+    #[arg(long = "host")]
+    hosts: Vec<String>,
+
+    /// Choose input and output devices from a list before starting
+    #[arg(short = 'i', long = "interactive")]
+    interactive: bool,
+
+    /// Choose only the input device interactively
+    #[arg(long = "interactive-in")]
+    interactive_in: bool,
+
+    /// Choose only the output device interactively
+    #[arg(long = "interactive-out")]
+    interactive_out: bool,
+
+    /// Accept OSC parameter messages on this UDP port (or host:port)
+    #[cfg(feature = "osc")]
+    #[arg(long = "osc")]
+    osc: Option<String>,
+}
+
+#[derive(Args, Debug)]
+struct RunCmdArgs {
+    #[command(flatten)]
+    run: RunArgs,
+
+    /// Restore config, chain and MIDI bindings from a saved session (other options are ignored)
+    #[arg(long = "session")]
+    session: Option<std::path::PathBuf>,
+
+    /// Save the session to this file when the engine stops cleanly
+    #[arg(long = "save-session")]
+    save_session: Option<std::path::PathBuf>,
+
+    /// Check the devices accept the stream configuration, print it and exit without starting
+    #[arg(long = "dry-validate")]
+    dry_validate: bool,
+}
+
+#[derive(Args, Debug)]
+struct ReplArgs {
+    #[command(flatten)]
+    run: RunArgs,
+
+    /// Show a live waveform of the chain output below the prompt
+    #[arg(long = "oscilloscope")]
+    oscilloscope: bool,
+}
+
+#[derive(Args, Debug)]
+struct RouteArgs {
+    /// Input device to capture from (name substring, case-insensitive)
+    #[arg(long = "from", required_unless_present = "list_routes")]
+    from: Option<String>,
+
+    /// Output device to play to (name substring, case-insensitive)
+    #[arg(long = "to", required_unless_present = "list_routes")]
+    to: Option<String>,
+
+    /// Chain file with the effects to run on the way (see `bord run --chain-file`)
+    #[arg(long = "effects")]
+    effects: Option<std::path::PathBuf>,
+
+    /// Also play the routed signal on this output device (name substring)
+    #[arg(long = "monitor")]
+    monitor: Option<String>,
+
+    /// Audio host API to search; repeat to try several in order
+    #[arg(long = "host")]
+    hosts: Vec<String>,
+
+    /// List the routes running in other `bord route` processes and exit
+    #[arg(long = "list-routes", conflicts_with_all = ["from", "to", "effects", "monitor"])]
+    list_routes: bool,
+}
+
+#[derive(Args, Debug)]
+struct MonitorArgs {
+    /// Input device substring (case-insensitive); the default input if unset
+    #[arg(long = "in")]
+    in_name: Option<String>,
+
+    /// Output device substring (case-insensitive); the default output if unset
+    #[arg(long = "out", conflicts_with = "loopback")]
+    out_name: Option<String>,
+
+    /// Chain file with effects to run after the gain (see `bord run --chain-file`); keep it short
+    #[arg(long = "effects")]
+    effects: Option<std::path::PathBuf>,
+
+    /// Play back on the input's own device, warning if it has to use a separate one
+    #[arg(long = "loopback")]
+    loopback: bool,
+
+    /// Real-time priority for audio threads (1-99, SCHED_FIFO on Linux)
+    #[arg(long = "rt-priority", default_value_t = 80, value_parser = clap::value_parser!(u8).range(1..=99))]
+    rt_priority: u8,
+
+    /// Audio host API to search; repeat to try several in order
+    #[arg(long = "host")]
+    hosts: Vec<String>,
+}
+
+#[derive(Args, Debug)]
+struct SpectrumArgs {
+    /// Input device substring (case-insensitive); the default input if unset
+    #[arg(long = "input")]
+    input: Option<String>,
+
+    /// FFT length in samples, rounded up to a power of two
+    #[arg(long = "fft-size", default_value_t = 2048)]
+    fft_size: usize,
+
+    /// Redraws per second
+    #[arg(long = "update-hz", default_value_t = 30.0)]
+    update_hz: f32,
+
+    /// Show this WAV file's spectrum frame by frame instead of an input's
+    #[arg(long = "file", conflicts_with = "input")]
+    file: Option<std::path::PathBuf>,
+}
+
+#[derive(Args, Debug)]
+struct BenchArgs {
+    /// Effect to benchmark (e.g., gain)
+    #[arg(long = "effect", default_value = "gain")]
+    effect: String,
+
+    /// Frames per block
+    #[arg(long = "block-size", default_value_t = 256)]
+    block_size: usize,
+
+    /// Number of blocks to process
+    #[arg(long = "iterations", default_value_t = 10_000)]
+    iterations: usize,
+
+    /// Interleaved channel count
+    #[arg(long = "channels", default_value_t = 2)]
+    channels: u16,
+}
+
+#[derive(Args, Debug)]
+struct CalibrateArgs {
+    #[command(flatten)]
+    run: RunArgs,
+
+    /// Play log-spaced sine tones out, measure what comes back and save the response to this TOML file
+    #[arg(long = "save", conflicts_with = "apply", required_unless_present = "apply")]
+    save: Option<std::path::PathBuf>,
+
+    /// Run with an EQ inverting the response saved in this file at the end of the chain
+    #[arg(long = "apply")]
+    apply: Option<std::path::PathBuf>,
+
+    /// Measurement points, log-spaced over 20 Hz - 20 kHz
+    #[arg(long = "steps", default_value_t = 31)]
+    steps: u32,
+}
+
+/// Call `method` on the running engine, exiting on any failure.
+fn call(method: &str, params: serde_json::Value) -> serde_json::Value {
+    let result = bord_engine::ipc::Client::connect(&bord_engine::ipc::default_socket_path())
+        .and_then(|mut client| client.call(method, params));
+    match result {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("IPC error: {e:?}");
+            std::process::exit(1);
+        }
+    }
+}
+
+fn presets(cmd: PresetsCommand) -> Result<(), String> {
+    use bord_engine::preset;
+    match cmd {
+        PresetsCommand::List => {
+            for name in preset::list().map_err(|e| format!("{e:#}"))? {
+                println!("{name}");
+            }
+        }
+        PresetsCommand::Save { name } => {
+            let p: preset::Preset = serde_json::from_value(call("getPreset", serde_json::json!({ "name": name })))
+                .map_err(|e| format!("Unexpected getPreset reply: {e}"))?;
+            let path = preset::save_named(&p).map_err(|e| format!("{e:#}"))?;
+            println!("Preset saved to {}", path.display());
+        }
+        PresetsCommand::Load { name } => {
+            let p = preset::load_named(&name).map_err(|e| format!("{e:#}"))?;
+            call("applyPreset", serde_json::to_value(&p).map_err(|e| e.to_string())?);
+        }
+        PresetsCommand::Delete { name } => preset::delete(&name).map_err(|e| format!("{e:#}"))?,
+    }
+    Ok(())
+}
+
+fn calibrate(args: CalibrateArgs) -> Result<(), String> {
+    use bord_engine::latency::{self, Calibration};
+    let mut eng = bord_engine::Engine::new(engine_config(args.run));
+    if let Some(path) = args.save {
+        println!("Measuring {} points; keep the room quiet.", args.steps);
+        let cal = latency::calibrate(&mut eng, args.steps).map_err(|e| format!("Calibration error: {e:?}"))?;
+        for p in &cal.points {
+            println!("{:>8.1} Hz  {:>+6.1} dB", p.freq_hz, p.gain_db);
+        }
+        cal.save(&path).map_err(|e| format!("{e:#}"))?;
+        println!("Calibration saved to {}", path.display());
+        return Ok(());
+    }
+    let Some(path) = args.apply else { return Ok(()) };
+    let cal = Calibration::load(&path).map_err(|e| format!("{e:#}"))?;
+    eng.push_effect_back(Box::new(cal.inverse_eq()));
+    eng.start().map_err(|e| format!("Engine start error: {e:?}"))?;
+    eng.print_chain();
+    if let Err(e) = eng.serve_ipc(&bord_engine::ipc::default_socket_path()) {
+        eprintln!("Warning: control socket unavailable: {e:?}");
+    }
+    println!("Audio running with {} correction bands. Ctrl+C to stop.", cal.points.len());
+    while !eng.stop_requested() {
+        std::thread::sleep(std::time::Duration::from_millis(500));
+    }
+    eng.stop();
+    Ok(())
+}
+
+/// Resolve hosts, run interactive device selection if asked, and build the engine config.
+/// Exits on invalid input.
+fn engine_config(mut args: RunArgs) -> bord_engine::EngineConfig {
+    let mut hosts = Vec::new();
+    for name in &args.hosts {
+        match bord_engine::devices::host_id_by_name(name) {
+            Some(id) => hosts.push(id),
+            None => {
+                eprintln!("Unknown or unavailable host: {name}");
+                std::process::exit(1);
+            }
+        }
+    }
+    if args.interactive || args.interactive_in || args.interactive_out {
+        let host = if hosts.is_empty() {
+            bord_engine::devices::Host::default_host()
+        } else {
+            match bord_engine::devices::Host::with_hosts(&hosts) {
+                Ok(h) => h,
+                Err(e) => {
+                    eprintln!("Error: {e:?}");
+                    std::process::exit(1);
+                }
+            }
+        };
+        let choose = |want_input: bool| match bord_engine::devices::prompt_device_index(&host, want_input) {
+            Ok(i) => Some(i),
+            Err(e) => {
+                eprintln!("Error: {e:?}");
+                std::process::exit(1);
+            }
+        };
+        if args.interactive || args.interactive_in {
+            args.in_idx = choose(true);
+            args.in_name = None;
+        }
+        if args.interactive || args.interactive_out {
+            args.out_idx = choose(false);
+            args.out_name = None;
+        }
+    }
+    let chain = args.chain_file.map(|path| match bord_engine::config::load_chain_file(&path) {
+        Ok(spec) => spec,
+        Err(e) => {
+            eprintln!("Error: {e:?}");
+            std::process::exit(1);
+        }
+    });
+    bord_engine::EngineConfig {
+        input_name: args.in_name,
+        output_name: args.out_name,
+        input_index: args.in_idx,
+        output_index: args.out_idx,
+        monitor_name: None,
+        sample_rate: args.sample_rate,
+        block_size: args.block_size,
+        channel_count: args.channels,
+        channel_alignment: args.channel_alignment,
+        gain_db: args.gain_db,
+        normalize_db: args.normalize_db,
+        silence_threshold_db: args.silence_threshold_db,
+        record_path: args.record,
+        rt_priority: args.rt_priority,
+        exclusive_mode: args.exclusive,
+        asio_driver: args.asio_driver,
+        pick_policy: if args.strict_device {
+            bord_engine::PickPolicy::IndexThenName
+        } else {
+            bord_engine::PickPolicy::default()
+        },
+        hosts,
+        chain,
+        max_chain_memory_bytes: None,
+    }
+}
+
+/// `bytes` in the largest binary unit that keeps it at least 1.
+fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 { format!("{bytes} B") } else { format!("{value:.1} {}", UNITS[unit]) }
 }
 
 fn main() {
     let cli = Cli::parse();
     match cli.cmd {
-        Command::Devices => {
-            if let Err(e) = bord_engine::devices::print_devices() {
+        Command::Devices { host, verbose } => {
+            let host_id = host.map(|name| {
+                bord_engine::devices::host_id_by_name(&name).unwrap_or_else(|| {
+                    if name.eq_ignore_ascii_case("asio") {
+                        eprintln!("{}", bord_engine::devices::ASIO_UNAVAILABLE);
+                        std::process::exit(1);
+                    }
+                    let names: Vec<_> = bord_engine::devices::list_all_hosts().into_iter().map(|(_, n)| n).collect();
+                    eprintln!("Unknown or unavailable host {name:?}; available: {}", names.join(", "));
+                    std::process::exit(1);
+                })
+            });
+            if let Err(e) = bord_engine::devices::print_devices(host_id, verbose) {
                 eprintln!("Error listing devices: {e:?}");
                 std::process::exit(1);
             }
         }
-        Command::Run(args) => {
-            let cfg = bord_engine::EngineConfig {
-                input_name: args.in_name,
-                output_name: args.out_name,
-                input_index: args.in_idx,
-                output_index: args.out_idx,
-                sample_rate: args.sample_rate,
-                block_size: args.block_size,
-                gain_db: args.gain_db,
+        Command::Run(RunCmdArgs { run: args, session, save_session, dry_validate }) => {
+            let rt_requested = args.rt_priority.is_some();
+            #[cfg(feature = "osc")]
+            let osc = args.osc.clone();
+            let mut eng = match session {
+                Some(path) => match bord_engine::Engine::load_session(&path) {
+                    Ok(eng) => {
+                        println!("Restored session from {}", path.display());
+                        eng
+                    }
+                    Err(e) => {
+                        eprintln!("Session error: {e:?}");
+                        std::process::exit(1);
+                    }
+                },
+                None => {
+                    let cfg = engine_config(args);
+                    println!("Starting with config: {cfg:?}");
+                    bord_engine::Engine::new(cfg)
+                }
             };
-            println!("Starting with config: {cfg:?}");
-            let mut eng = bord_engine::Engine::new(cfg);
+            if dry_validate {
+                match eng.validate() {
+                    Ok(v) => {
+                        println!("input:  {:?} {:?}", v.input_config, v.input_format);
+                        println!("output: {:?} {:?}", v.output_config, v.output_format);
+                        for w in &v.warnings {
+                            println!("warning: {w}");
+                        }
+                        return;
+                    }
+                    Err(e) => {
+                        eprintln!("Validation error: {e:?}");
+                        std::process::exit(1);
+                    }
+                }
+            }
             if let Err(e) = eng.start() {
                 eprintln!("Engine start error: {e:?}");
                 std::process::exit(1);
             }
+            println!(
+                "Input: {}, output: {} ({} Hz, {} ch)",
+                eng.input_device_name().unwrap_or_default(),
+                eng.output_device_name().unwrap_or_default(),
+                eng.sample_rate().unwrap_or(0),
+                eng.channels().unwrap_or(0)
+            );
+            eng.print_chain();
+            if let Err(e) = eng.serve_ipc(&bord_engine::ipc::default_socket_path()) {
+                eprintln!("Warning: control socket unavailable: {e:?}");
+            }
+            #[cfg(feature = "osc")]
+            if let Some(osc) = osc {
+                match bord_engine::osc::listen_addr(&osc).and_then(|addr| eng.serve_osc(&addr).map(|s| s.local_addr())) {
+                    Ok(addr) => println!("OSC listening on udp://{addr}"),
+                    Err(e) => {
+                        eprintln!("OSC error: {e:?}");
+                        std::process::exit(1);
+                    }
+                }
+            }
+            if rt_requested && !eng.rt_priority_applied() {
+                // Callbacks may not have fired yet; give them a moment before reporting.
+                std::thread::sleep(std::time::Duration::from_millis(200));
+                if !eng.rt_priority_applied() {
+                    eprintln!("Warning: real-time priority was not applied to all audio threads.");
+                }
+            }
             println!("Audio running. Ctrl+C to stop.");
-            loop { std::thread::sleep(std::time::Duration::from_millis(500)); }
+            let started = std::time::Instant::now();
+            while !eng.stop_requested() {
+                std::thread::sleep(std::time::Duration::from_millis(500));
+                for ev in eng.silence_events() {
+                    match ev {
+                        SilenceEvent::Started(at) => println!("[{:>8.2}s] silence, processing suspended", at.duration_since(started).as_secs_f32()),
+                        SilenceEvent::Ended(at) => println!("[{:>8.2}s] signal, processing resumed", at.duration_since(started).as_secs_f32()),
+                    }
+                }
+            }
+            eng.stop();
+            if let Some(path) = save_session {
+                match eng.save_session(&path) {
+                    Ok(()) => println!("Session saved to {}", path.display()),
+                    Err(e) => eprintln!("Session save error: {e:?}"),
+                }
+            }
+        }
+        Command::Repl(args) => {
+            if let Err(e) = repl::run(engine_config(args.run), args.oscilloscope) {
+                eprintln!("{e}");
+                std::process::exit(1);
+            }
+        }
+        Command::Route(RouteArgs { list_routes: true, .. }) => route::list(),
+        Command::Route(RouteArgs { from: Some(from), to: Some(to), effects, monitor, hosts, .. }) => {
+            if let Err(e) = route::run(route::RouteOpts { from, to, effects, monitor, hosts }) {
+                eprintln!("{e}");
+                std::process::exit(1);
+            }
+        }
+        // clap requires --from and --to unless --list-routes is given.
+        Command::Route(_) => unreachable!(),
+        Command::Monitor(args) => {
+            let opts = monitor::MonitorOpts {
+                input: args.in_name,
+                output: args.out_name,
+                effects: args.effects,
+                loopback: args.loopback,
+                rt_priority: args.rt_priority,
+                hosts: args.hosts,
+            };
+            if let Err(e) = monitor::run(opts) {
+                eprintln!("{e}");
+                std::process::exit(1);
+            }
+        }
+        Command::Spectrum(args) => {
+            let opts = spectrum::SpectrumOpts { input: args.input, fft_size: args.fft_size, update_hz: args.update_hz, file: args.file };
+            if let Err(e) = spectrum::run(opts) {
+                eprintln!("{e}");
+                std::process::exit(1);
+            }
+        }
+        Command::Status { dot: true, .. } => match call("getChainDot", serde_json::Value::Null) {
+            serde_json::Value::String(dot) => print!("{dot}"),
+            other => eprintln!("Unexpected getChainDot reply: {other}"),
+        },
+        Command::Status { dot: false, memory } => match serde_json::from_value::<bord_engine::EngineStats>(call("getStats", serde_json::Value::Null)) {
+            Ok(st) => {
+                println!("running:     {}", st.running);
+                println!("input:       {}", st.input_device.as_deref().unwrap_or("?"));
+                println!("output:      {}", st.output_device.as_deref().unwrap_or("?"));
+                println!("sample rate: {} Hz", st.sample_rate);
+                println!("channels:    {}", st.channels);
+                println!("rt priority: {}", if st.rt_priority_applied { "applied" } else { "not applied" });
+                println!("paused:      {}", st.paused);
+                println!("underruns:   {}", st.underruns);
+                println!("chain:");
+                for (i, name) in st.chain.iter().enumerate() {
+                    match st.memory_bytes.get(i).filter(|_| memory) {
+                        Some(bytes) => println!("{:>2}  {:<16} {}", i, name, format_bytes(*bytes)),
+                        None => println!("{:>2}  {}", i, name),
+                    }
+                }
+                if memory {
+                    let total = st.memory_bytes.iter().sum::<usize>();
+                    match st.max_chain_memory_bytes {
+                        Some(limit) => println!("memory:      {} of {}", format_bytes(total), format_bytes(limit)),
+                        None => println!("memory:      {}", format_bytes(total)),
+                    }
+                }
+            }
+            Err(e) => eprintln!("Unexpected getStats reply: {e}"),
+        },
+        Command::Graph { session: Some(path) } => match bord_engine::Engine::load_session(&path).and_then(|eng| eng.chain_ascii()) {
+            Ok(text) => print!("{text}"),
+            Err(e) => {
+                eprintln!("Session error: {e:?}");
+                std::process::exit(1);
+            }
+        },
+        Command::Graph { session: None } => match call("getChainAscii", serde_json::Value::Null) {
+            serde_json::Value::String(text) => print!("{text}"),
+            other => eprintln!("Unexpected getChainAscii reply: {other}"),
+        },
+        Command::SetGain { db } => {
+            call("setGain", serde_json::json!({ "db": db }));
+        }
+        // clap makes `value` and `--reset` exclusive, one of them required.
+        Command::SetParam { effect_index, param_id, value, .. } => {
+            match value {
+                Some(value) => call("setEffectParam", serde_json::json!({ "index": effect_index, "key": param_id, "value": value })),
+                None => call("resetEffectParam", serde_json::json!({ "index": effect_index, "key": param_id })),
+            };
+        }
+        Command::Presets { cmd } => {
+            if let Err(e) = presets(cmd) {
+                eprintln!("{e}");
+                std::process::exit(1);
+            }
+        }
+        Command::Validate { config } => {
+            let issues = bord_engine::config::load_value(&config)
+                .and_then(|v| bord_engine::config::validate(&v));
+            match issues {
+                Ok(issues) if issues.is_empty() => println!("{}: ok", config.display()),
+                Ok(issues) => {
+                    for i in &issues {
+                        eprintln!("{}: {}", i.path, i.message);
+                    }
+                    eprintln!("{}: {} problem(s)", config.display(), issues.len());
+                    std::process::exit(1);
+                }
+                Err(e) => {
+                    eprintln!("Error: {e:?}");
+                    std::process::exit(1);
+                }
+            }
+        }
+        #[cfg(feature = "schema-gen")]
+        Command::GenSchema => println!("{}", bord_engine::config::generate_schema()),
+        Command::Calibrate(args) => {
+            if let Err(e) = calibrate(args) {
+                eprintln!("{e}");
+                std::process::exit(1);
+            }
+        }
+        Command::Bench(args) => {
+            let opts = bench::BenchOpts {
+                effect: &args.effect,
+                block_size: args.block_size,
+                iterations: args.iterations,
+                channels: args.channels,
+            };
+            if let Err(e) = bench::run(&opts) {
+                eprintln!("Bench error: {e}");
+                std::process::exit(1);
+            }
         }
     }
 }
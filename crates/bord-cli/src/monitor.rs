@@ -0,0 +1,64 @@
+use bord_engine::config::{load_chain_file, ConfigFile};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// Frames per buffer `bord monitor` asks for; devices that can't go this low
+/// get their smallest size instead.
+const MONITOR_BLOCK_FRAMES: u32 = 64;
+
+pub struct MonitorOpts {
+    pub input: Option<String>,
+    pub output: Option<String>,
+    pub effects: Option<PathBuf>,
+    pub loopback: bool,
+    pub rt_priority: u8,
+    pub hosts: Vec<String>,
+}
+
+/// Input -> Gain (and any `effects`) -> output, at the smallest block size
+/// the devices take and with real-time priority, until stopped over IPC.
+pub fn run(opts: MonitorOpts) -> Result<(), String> {
+    let chain = opts.effects.as_deref().map(load_chain_file).transpose().map_err(|e| format!("{e:?}"))?;
+    let file = ConfigFile {
+        input: opts.input,
+        output: opts.output,
+        rt_priority: Some(opts.rt_priority),
+        hosts: (!opts.hosts.is_empty()).then_some(opts.hosts),
+        chain,
+        ..ConfigFile::default()
+    };
+    let mut eng = bord_engine::Engine::new(file.to_engine_config().map_err(|e| format!("{e:#}"))?);
+    if opts.loopback && !eng.loopback().map_err(|e| format!("{e:?}"))? {
+        eprintln!("Warning: the input device has no outputs; monitoring on a separate output device");
+    }
+    let frames = eng.fit_block_size(MONITOR_BLOCK_FRAMES).map_err(|e| format!("{e:?}"))?;
+    eng.start().map_err(|e| format!("Engine start error: {e:?}"))?;
+    let (input, output) = (eng.input_device_name().unwrap_or_default(), eng.output_device_name().unwrap_or_default());
+    println!("Input: {input}, output: {output} ({} Hz, {frames} frames per buffer)", eng.sample_rate().unwrap_or(0));
+    if opts.loopback && input != output {
+        eprintln!("Warning: input and output are separate devices, so their clocks may drift apart");
+    }
+    eng.print_chain();
+
+    // The streams report their latency from their first callbacks.
+    let started = Instant::now();
+    while eng.round_trip_latency().is_none() && started.elapsed() < Duration::from_secs(2) {
+        std::thread::sleep(Duration::from_millis(20));
+    }
+    match eng.round_trip_latency() {
+        Some(latency) => println!("Round-trip latency: {:.2} ms", latency.as_secs_f64() * 1000.0),
+        None => println!("Round-trip latency: not reported by this backend"),
+    }
+    if !eng.rt_priority_applied() {
+        eprintln!("Warning: real-time priority was not applied to all audio threads.");
+    }
+    if let Err(e) = eng.serve_ipc(&bord_engine::ipc::default_socket_path()) {
+        eprintln!("Warning: control socket unavailable: {e:?}");
+    }
+    println!("Monitoring. Ctrl+C to stop.");
+    while !eng.stop_requested() {
+        std::thread::sleep(Duration::from_millis(500));
+    }
+    eng.stop();
+    Ok(())
+}
@@ -0,0 +1,146 @@
+use crate::scope;
+use bord_engine::dsp::mono_mix::MonoMix;
+use bord_engine::dsp::waveform::{Waveform, WaveformReader};
+use bord_engine::params::{ParamEvent, ParamId};
+use bord_engine::{Engine, EngineConfig};
+use crossterm::{cursor, terminal, QueueableCommand};
+use std::io::{BufRead, Write};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Frames kept by the oscilloscope tap: twice the displayed window, so a
+/// trigger point can be found in the older half.
+const SCOPE_FRAMES: usize = 2048;
+const SCOPE_REFRESH: Duration = Duration::from_millis(50);
+/// Screen rows: prompt, command output, blank, then the oscilloscope panel.
+const PROMPT_ROW: u16 = 0;
+const MESSAGE_ROW: u16 = 1;
+const PANEL_ROW: u16 = 3;
+
+const HELP: &str = "commands: gain <db> | mono on|off | trigger <level> | stats | help | quit";
+
+/// Start the engine and read commands from stdin until `quit` or EOF.
+pub fn run(cfg: EngineConfig, oscilloscope: bool) -> Result<(), String> {
+    let mut eng = Engine::new(cfg);
+    let mono = MonoMix::new(false);
+    let mono_flag = mono.enabled_flag();
+    eng.push_effect_back(Box::new(mono));
+    let reader = oscilloscope.then(|| {
+        let (tap, reader) = Waveform::new(SCOPE_FRAMES);
+        eng.push_effect_back(Box::new(tap));
+        reader
+    });
+    eng.start().map_err(|e| format!("Engine start error: {e:?}"))?;
+
+    let trigger = Arc::new(AtomicU32::new(0.0f32.to_bits()));
+    let done = Arc::new(AtomicBool::new(false));
+    let scope_thread = reader.map(|reader| {
+        let (trigger, done) = (trigger.clone(), done.clone());
+        let mut out = std::io::stdout();
+        let _ = out.queue(terminal::Clear(terminal::ClearType::All)).and_then(|o| o.flush());
+        std::thread::spawn(move || scope_loop(&reader, &trigger, &done))
+    });
+    let term = Term { scope: scope_thread.is_some() };
+
+    term.say(HELP);
+    let stdin = std::io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        term.prompt();
+        let Some(Ok(line)) = lines.next() else { break };
+        let mut words = line.split_whitespace();
+        match (words.next(), words.next()) {
+            (None, _) => {}
+            (Some("quit" | "exit"), _) => break,
+            (Some("help"), _) => term.say(HELP),
+            (Some("stats"), _) => {
+                let st = eng.stats();
+                term.say(&format!(
+                    "{} Hz, {} ch, underruns {}, chain: {}",
+                    st.sample_rate, st.channels, st.underruns, st.chain.join(" -> ")
+                ));
+            }
+            (Some("gain"), Some(v)) => match v.parse::<f32>() {
+                Ok(db) => match eng.stats().chain.iter().position(|n| n == "Gain") {
+                    Some(index) => {
+                        let key = ParamId::new("db").expect("short key");
+                        eng.params().send(ParamEvent::SetParamDb { index, key, db });
+                        term.say(&format!("gain {db} dB"));
+                    }
+                    None => term.say("no Gain effect in the chain"),
+                },
+                Err(_) => term.say("usage: gain <db>"),
+            },
+            (Some("mono"), Some(v @ ("on" | "off"))) => {
+                mono_flag.store(v == "on", Ordering::Relaxed);
+                term.say(&format!("mono {v}"));
+            }
+            (Some("mono"), _) => term.say("usage: mono on|off"),
+            (Some("trigger"), Some(v)) => match v.parse::<f32>() {
+                Ok(level) if (-1.0..=1.0).contains(&level) => {
+                    trigger.store(level.to_bits(), Ordering::Relaxed);
+                    term.say(&format!("trigger {level}"));
+                }
+                _ => term.say("usage: trigger <level>  (-1.0 to 1.0)"),
+            },
+            (Some(cmd), _) => term.say(&format!("unknown command: {cmd} ({HELP})")),
+        }
+    }
+
+    done.store(true, Ordering::Relaxed);
+    if let Some(t) = scope_thread {
+        let _ = t.join();
+    }
+    eng.stop();
+    Ok(())
+}
+
+fn scope_loop(reader: &WaveformReader, trigger: &AtomicU32, done: &AtomicBool) {
+    let mut samples = Vec::with_capacity(SCOPE_FRAMES);
+    while !done.load(Ordering::Relaxed) {
+        reader.snapshot(&mut samples);
+        let lines = scope::render(&samples, f32::from_bits(trigger.load(Ordering::Relaxed)));
+        // Draw around the cursor so a half-typed command stays where it is.
+        let mut out = std::io::stdout().lock();
+        let _ = out.queue(cursor::SavePosition);
+        for (i, line) in lines.iter().enumerate() {
+            let _ = out.queue(cursor::MoveTo(0, PANEL_ROW + i as u16));
+            let _ = out.queue(terminal::Clear(terminal::ClearType::CurrentLine));
+            let _ = out.write_all(line.as_bytes());
+        }
+        let _ = out.queue(cursor::RestorePosition);
+        let _ = out.flush();
+        drop(out);
+        std::thread::sleep(SCOPE_REFRESH);
+    }
+}
+
+/// Prompt/message output: plain lines, or fixed rows above the scope panel.
+struct Term {
+    scope: bool,
+}
+
+impl Term {
+    fn say(&self, msg: &str) {
+        let mut out = std::io::stdout().lock();
+        if self.scope {
+            let _ = out.queue(cursor::MoveTo(0, MESSAGE_ROW));
+            let _ = out.queue(terminal::Clear(terminal::ClearType::CurrentLine));
+            let _ = write!(out, "{msg}");
+        } else {
+            let _ = writeln!(out, "{msg}");
+        }
+        let _ = out.flush();
+    }
+
+    fn prompt(&self) {
+        let mut out = std::io::stdout().lock();
+        if self.scope {
+            let _ = out.queue(cursor::MoveTo(0, PROMPT_ROW));
+            let _ = out.queue(terminal::Clear(terminal::ClearType::CurrentLine));
+        }
+        let _ = write!(out, "bord> ");
+        let _ = out.flush();
+    }
+}
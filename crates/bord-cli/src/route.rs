@@ -0,0 +1,70 @@
+use bord_engine::config::{load_chain_file, ConfigFile};
+use bord_engine::ipc;
+use std::path::PathBuf;
+
+pub struct RouteOpts {
+    pub from: String,
+    pub to: String,
+    pub effects: Option<PathBuf>,
+    pub monitor: Option<String>,
+    pub hosts: Vec<String>,
+}
+
+/// Capture from `from`, run the effects and play to `to` (and `monitor`)
+/// until stopped over IPC. Each route serves its own control socket, so
+/// several can run side by side and `list` finds them all.
+pub fn run(opts: RouteOpts) -> Result<(), String> {
+    let chain = opts.effects.as_deref().map(load_chain_file).transpose().map_err(|e| format!("{e:?}"))?;
+    let file = ConfigFile {
+        input: Some(opts.from),
+        output: Some(opts.to),
+        monitor: opts.monitor,
+        // A route goes between the devices named or not at all.
+        strict_device: Some(true),
+        hosts: (!opts.hosts.is_empty()).then_some(opts.hosts),
+        chain,
+        ..ConfigFile::default()
+    };
+    let cfg = file.to_engine_config().map_err(|e| format!("{e:#}"))?;
+    let mut eng = bord_engine::Engine::new(cfg);
+    eng.start().map_err(|e| format!("Engine start error: {e:?}"))?;
+    let stats = eng.stats();
+    println!("{}", describe(&stats));
+    eng.print_chain();
+    let socket = ipc::route_socket_path(std::process::id());
+    if let Err(e) = eng.serve_ipc(&socket) {
+        eprintln!("Warning: control socket unavailable: {e:?}");
+    }
+    println!("Routing. Ctrl+C to stop.");
+    while !eng.stop_requested() {
+        std::thread::sleep(std::time::Duration::from_millis(500));
+    }
+    eng.stop();
+    Ok(())
+}
+
+/// Print every running route: its process id, devices and effects.
+pub fn list() {
+    let mut found = false;
+    for path in ipc::route_socket_paths() {
+        // Stale sockets don't connect and are skipped.
+        let Ok(mut client) = ipc::Client::connect(&path) else { continue };
+        let Ok(stats) = client.call("getStats", serde_json::Value::Null) else { continue };
+        let Ok(stats) = serde_json::from_value::<bord_engine::EngineStats>(stats) else { continue };
+        let pid = path.file_stem().and_then(|s| s.to_str()).and_then(|s| s.strip_prefix("bord-route-")).unwrap_or("?");
+        println!("{pid:>7}  {}  [{}]", describe(&stats), stats.chain.join(", "));
+        found = true;
+    }
+    if !found {
+        println!("No routes running.");
+    }
+}
+
+fn describe(stats: &bord_engine::EngineStats) -> String {
+    let name = |n: &Option<String>| n.clone().unwrap_or_else(|| "?".into());
+    let mut text = format!("{} -> {}", name(&stats.input_device), name(&stats.output_device));
+    if let Some(monitor) = &stats.monitor_device {
+        text.push_str(&format!(" (monitor: {monitor})"));
+    }
+    text
+}
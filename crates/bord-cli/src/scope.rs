@@ -0,0 +1,40 @@
+/// Plot size in character cells (the dBFS label column comes on top of this).
+pub const WIDTH: usize = 80;
+pub const HEIGHT: usize = 12;
+
+/// Render `samples` (oldest first) as `HEIGHT` lines of character art.
+/// The view starts at the first rising crossing of `trigger` (linear, -1..1)
+/// and free-runs on the most recent samples when there is none.
+pub fn render(samples: &[f32], trigger: f32) -> Vec<String> {
+    let per_col = (samples.len() / 2 / WIDTH).max(1);
+    let window = (WIDTH * per_col).min(samples.len());
+    let last_start = samples.len() - window;
+    let start = (1..=last_start)
+        .find(|&i| samples[i - 1] < trigger && samples[i] >= trigger)
+        .unwrap_or(last_start);
+    let view = &samples[start..start + window];
+
+    let mut grid = vec![[' '; WIDTH]; HEIGHT];
+    for (col, chunk) in view.chunks(per_col).take(WIDTH).enumerate() {
+        let lo = chunk.iter().copied().fold(f32::INFINITY, f32::min);
+        let hi = chunk.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+        for row in &mut grid[row_of(hi)..=row_of(lo)] {
+            row[col] = '█';
+        }
+    }
+
+    grid.iter()
+        .enumerate()
+        .map(|(r, row)| {
+            // Amplitude at the middle of this row, shown as dBFS.
+            let amp = 1.0 - (r as f32 + 0.5) * 2.0 / HEIGHT as f32;
+            let db = 20.0 * amp.abs().log10();
+            format!("{:>4.0} dB│{}", db, row.iter().collect::<String>())
+        })
+        .collect()
+}
+
+fn row_of(sample: f32) -> usize {
+    let y = (1.0 - sample.clamp(-1.0, 1.0)) / 2.0 * HEIGHT as f32;
+    (y as usize).min(HEIGHT - 1)
+}
@@ -0,0 +1,142 @@
+use bord_engine::dsp::gain::Gain;
+use bord_engine::dsp::spectrum::{log_bands, wav_spectra, SpectrumAnalyzer};
+use bord_engine::{Engine, EngineConfig};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
+use crossterm::style::{Color, SetForegroundColor};
+use crossterm::{cursor, terminal, QueueableCommand};
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+const LO_HZ: f32 = 20.0;
+const HI_HZ: f32 = 20_000.0;
+const FLOOR_DB: f32 = -80.0;
+/// Width of the dBFS labels left of the bars.
+const LABEL_COLS: u16 = 7;
+const FREQ_MARKS: [(f32, &str); 9] =
+    [(50.0, "50"), (100.0, "100"), (200.0, "200"), (500.0, "500"), (1e3, "1k"), (2e3, "2k"), (5e3, "5k"), (1e4, "10k"), (2e4, "20k")];
+
+pub struct SpectrumOpts {
+    pub input: Option<String>,
+    pub fft_size: usize,
+    pub update_hz: f32,
+    pub file: Option<PathBuf>,
+}
+
+/// Draw the input's spectrum (or a WAV file's, played through at real-time
+/// speed) in the terminal until 'q'.
+pub fn run(opts: SpectrumOpts) -> Result<(), String> {
+    let interval = Duration::from_secs_f32(1.0 / opts.update_hz.clamp(1.0, 120.0));
+    if let Some(path) = &opts.file {
+        let (sr, frames) = wav_spectra(path, opts.fft_size, opts.update_hz).map_err(|e| format!("{e:#}"))?;
+        let title = format!("{} ({sr} Hz, FFT {})", path.display(), opts.fft_size.max(64).next_power_of_two());
+        let mut frames = frames.into_iter();
+        let mut last = Vec::new();
+        return with_screen(|| {
+            draw_until_quit(interval, &title, sr, || {
+                // Hold the last frame once the file has played.
+                if let Some(frame) = frames.next() {
+                    last = frame;
+                }
+                last.clone()
+            })
+        });
+    }
+
+    let mut eng = Engine::new(EngineConfig { input_name: opts.input, ..EngineConfig::default() });
+    let (tap, mut reader) = SpectrumAnalyzer::new(opts.fft_size);
+    eng.push_effect_back(Box::new(tap));
+    // Analyse only: keep the input off the speakers.
+    eng.push_effect_back(Box::new(Gain::new(f32::NEG_INFINITY)));
+    eng.start().map_err(|e| format!("Engine start error: {e:?}"))?;
+    let title = format!(
+        "{} ({} Hz, FFT {})",
+        eng.input_device_name().unwrap_or_default(),
+        reader.sample_rate(),
+        reader.fft_size()
+    );
+    let sr = reader.sample_rate();
+    let result = with_screen(|| draw_until_quit(interval, &title, sr, || reader.spectrum().to_vec()));
+    eng.stop();
+    result
+}
+
+/// Run `f` on the alternate screen in raw mode, restoring the terminal after.
+fn with_screen(f: impl FnOnce() -> std::io::Result<()>) -> Result<(), String> {
+    let mut out = std::io::stdout();
+    terminal::enable_raw_mode().map_err(|e| e.to_string())?;
+    let _ = out.queue(terminal::EnterAlternateScreen).and_then(|o| o.queue(cursor::Hide)).and_then(|o| o.flush());
+    let result = f();
+    let _ = out.queue(cursor::Show).and_then(|o| o.queue(terminal::LeaveAlternateScreen)).and_then(|o| o.flush());
+    let _ = terminal::disable_raw_mode();
+    result.map_err(|e| e.to_string())
+}
+
+fn draw_until_quit(interval: Duration, title: &str, sr: u32, mut spectrum: impl FnMut() -> Vec<f32>) -> std::io::Result<()> {
+    let mut next = Instant::now();
+    loop {
+        let (cols, rows) = terminal::size()?;
+        let bands = cols.saturating_sub(LABEL_COLS).max(1) as usize;
+        let levels = log_bands(&spectrum(), sr, bands, LO_HZ, HI_HZ);
+        draw(&mut std::io::stdout().lock(), title, &levels, rows)?;
+        next += interval;
+        while let Some(wait) = next.checked_duration_since(Instant::now()) {
+            if !event::poll(wait)? {
+                break;
+            }
+            if let Event::Key(key) = event::read()? {
+                if is_quit(key) {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+fn is_quit(key: KeyEvent) -> bool {
+    matches!(key.code, KeyCode::Char('q') | KeyCode::Esc)
+        || (key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL))
+}
+
+/// Title row, bars from `FLOOR_DB` to 0 dBFS, then the frequency axis.
+fn draw(out: &mut impl Write, title: &str, levels: &[f32], rows: u16) -> std::io::Result<()> {
+    let height = rows.saturating_sub(3).max(1);
+    out.queue(cursor::MoveTo(0, 0))?;
+    out.queue(terminal::Clear(terminal::ClearType::All))?;
+    write!(out, "{title}  (q to quit)")?;
+    for r in 0..height {
+        // Level at the bottom of this row; a bar fills it from there up.
+        let row_db = FLOOR_DB * (r + 1) as f32 / height as f32;
+        out.queue(cursor::MoveTo(0, 1 + r))?;
+        let label = if r % 4 == 0 { format!("{row_db:>5.0} │") } else { "      │".to_string() };
+        out.queue(SetForegroundColor(Color::Reset))?;
+        write!(out, "{label}")?;
+        out.queue(SetForegroundColor(level_color(row_db)))?;
+        let line: String = levels.iter().map(|&db| if db >= row_db { '█' } else { ' ' }).collect();
+        write!(out, "{line}")?;
+    }
+    out.queue(SetForegroundColor(Color::Reset))?;
+    out.queue(cursor::MoveTo(0, 1 + height))?;
+    write!(out, "      └{}", "─".repeat(levels.len()))?;
+    out.queue(cursor::MoveTo(LABEL_COLS, 2 + height))?;
+    let mut axis = vec![' '; levels.len()];
+    for (hz, text) in FREQ_MARKS {
+        let col = ((hz / LO_HZ).ln() / (HI_HZ / LO_HZ).ln() * levels.len() as f32) as usize;
+        let col = col.min(levels.len().saturating_sub(text.len()));
+        if axis[col..col + text.len()].iter().all(|&c| c == ' ') {
+            for (a, ch) in axis[col..].iter_mut().zip(text.chars()) {
+                *a = ch;
+            }
+        }
+    }
+    write!(out, "{}", axis.into_iter().collect::<String>())?;
+    out.flush()
+}
+
+fn level_color(db: f32) -> Color {
+    match db {
+        db if db > -3.0 => Color::Red,
+        db if db > -12.0 => Color::Yellow,
+        _ => Color::Green,
+    }
+}
@@ -0,0 +1,56 @@
+//! Feedback delay line recirculating subnormal state on near-zero input, with and without
+//! `with_denormals_flushed`. `cargo bench -p bord-engine --bench denormals`
+use bord_engine::dsp::utils::with_denormals_flushed;
+use std::hint::black_box;
+use std::time::Instant;
+
+const DELAY: usize = 4410;
+const BLOCK: usize = 512;
+const BLOCKS: usize = 4000;
+
+/// Recirculating delay at 0.999 feedback: state decays into the subnormal range.
+struct FeedbackDelay {
+    line: Vec<f32>,
+    pos: usize,
+}
+
+impl FeedbackDelay {
+    fn new() -> Self {
+        let mut line = vec![0.0; DELAY];
+        line.iter_mut().enumerate().for_each(|(i, s)| *s = 1.0e-39 * (i % 7 + 1) as f32);
+        Self { line, pos: 0 }
+    }
+    fn process(&mut self, block: &mut [f32]) {
+        for s in block.iter_mut() {
+            let out = self.line[self.pos];
+            self.line[self.pos] = *s + out * 0.999;
+            self.pos = (self.pos + 1) % DELAY;
+            *s = out;
+        }
+    }
+}
+
+fn run(flush: bool) -> f64 {
+    let mut fx = FeedbackDelay::new();
+    let mut block = [0.0f32; BLOCK];
+    let started = Instant::now();
+    for _ in 0..BLOCKS {
+        block.fill(1.0e-42);
+        if flush {
+            with_denormals_flushed(|| fx.process(&mut block));
+        } else {
+            fx.process(&mut block);
+        }
+        black_box(&block);
+    }
+    started.elapsed().as_secs_f64() * 1e6 / BLOCKS as f64
+}
+
+fn main() {
+    let plain = run(false);
+    let flushed = run(true);
+    println!("{:<10} {:>12}", "mode", "us/block");
+    println!("{:<10} {:>12.3}", "plain", plain);
+    println!("{:<10} {:>12.3}", "flushed", flushed);
+    println!("speedup: {:.1}x", plain / flushed);
+}
@@ -0,0 +1,47 @@
+//! A stereo chain run on interleaved blocks (`Chain::process`) versus stereo
+//! frames (`Chain::process_frames_stereo`). `cargo bench -p bord-engine --bench frames`
+use bord_engine::dsp::auto_pan::AutoPan;
+use bord_engine::dsp::balance::StereoBalance;
+use bord_engine::dsp::effect::Frame;
+use bord_engine::dsp::gain::Gain;
+use bord_engine::dsp::lfo::LfoWaveform;
+use bord_engine::graph::Chain;
+use std::hint::black_box;
+use std::time::Instant;
+
+const SR: u32 = 48_000;
+const BLOCK: usize = 512; // frames
+const BLOCKS: usize = 20_000;
+
+fn chain() -> Chain {
+    let mut chain = Chain::new(SR, 2);
+    chain.push(Box::new(Gain::new(-3.0))).expect("any block size");
+    chain.push(Box::new(StereoBalance::new(0.3))).expect("any block size");
+    chain.push(Box::new(AutoPan::new(0.5, 0.8, LfoWaveform::Sine))).expect("any block size");
+    chain
+}
+
+fn run(frames: bool) -> f64 {
+    let mut chain = chain();
+    let mut block = vec![0.0f32; BLOCK * 2];
+    let started = Instant::now();
+    for n in 0..BLOCKS {
+        block.iter_mut().enumerate().for_each(|(i, s)| *s = ((n * BLOCK + i) as f32 * 0.01).sin() * 0.5);
+        if frames {
+            chain.process_frames_stereo(Frame::from_interleaved_mut(&mut block));
+        } else {
+            chain.process(&mut block);
+        }
+        black_box(&block);
+    }
+    started.elapsed().as_secs_f64() * 1e6 / BLOCKS as f64
+}
+
+fn main() {
+    let interleaved = run(false);
+    let frames = run(true);
+    println!("{:<12} {:>12}", "path", "us/block");
+    println!("{:<12} {:>12.3}", "interleaved", interleaved);
+    println!("{:<12} {:>12.3}", "frames", frames);
+    println!("speedup: {:.2}x", interleaved / frames);
+}
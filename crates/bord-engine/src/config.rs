@@ -0,0 +1,390 @@
+//! On-disk configuration (TOML or JSON) and its JSON Schema.
+//!
+//! `SCHEMA` is generated from `ConfigFile` with the `schema-gen` feature:
+//! `cargo run -p bord-cli --features schema-gen -- gen-schema > crates/bord-engine/schema/bord-config.schema.json`
+//!
+//! A `[chain]` section lists effects by registered type name, with their
+//! parameters inline, and parallel sections of serial branches:
+//!
+//! ```toml
+//! [[chain.effect]]
+//! type = "Gain"
+//! gain_db = -6.0
+//!
+//! [[chain.parallel]]
+//! position = 0   # before effect 0; omitted, parallel sections follow the effects
+//! branches = [
+//!     [{ type = "LowPassFilter", cutoff_hz = 800.0 }],
+//!     [{ type = "HighPassFilter", cutoff_hz = 3000.0 }, { type = "Gain", db = -3.0 }],
+//! ]
+//! ```
+//!
+//! A `ParametricEq` takes its bands as a list instead of parameters:
+//! `{ type = "ParametricEq", bands = [{ freq_hz = 100.0, gain_db = 3.0, q = 0.7 }] }`.
+//!
+//! A chain file (`--chain-file`) is the effect list alone, as JSON:
+//!
+//! ```json
+//! [
+//!     { "type": "Gain", "params": { "db": -6 } },
+//!     { "type": "ParametricEq", "bands": [{ "freq_hz": 3000, "gain_db": -4, "q": 1.0 }] }
+//! ]
+//! ```
+
+use crate::dsp::effect::Effect;
+use crate::dsp::parallel::Parallel;
+use crate::dsp::parametric_eq::{EqBand, ParametricEq};
+use crate::dsp::registry::{builtin_registry, EffectRegistry};
+use crate::graph::Chain;
+use crate::{ChannelAlignmentPolicy, EngineConfig, PickPolicy};
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+pub const SCHEMA: &str = include_str!("../schema/bord-config.schema.json");
+
+/// File form of `EngineConfig`. Every field is optional; unknown keys are rejected.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema-gen", derive(schemars::JsonSchema))]
+#[serde(deny_unknown_fields)]
+pub struct ConfigFile {
+    /// Input device substring (case-insensitive)
+    pub input: Option<String>,
+    /// Output device substring (case-insensitive)
+    pub output: Option<String>,
+    /// Input device index (see `bord devices`)
+    pub input_index: Option<usize>,
+    /// Output device index (see `bord devices`)
+    pub output_index: Option<usize>,
+    /// Second output device (name substring) that plays the same signal, for monitoring
+    pub monitor: Option<String>,
+    /// Sample rate in Hz
+    #[cfg_attr(feature = "schema-gen", schemars(range(min = 8000, max = 384000)))]
+    pub sample_rate: Option<u32>,
+    /// Frames per buffer
+    #[cfg_attr(feature = "schema-gen", schemars(range(min = 16, max = 8192)))]
+    pub block_size: Option<u32>,
+    /// Channel count, instead of the output device's default
+    #[cfg_attr(feature = "schema-gen", schemars(range(min = 1, max = 64)))]
+    pub channels: Option<u16>,
+    /// What to do when the input and output devices' channel counts differ
+    pub channel_alignment: Option<ChannelAlignmentPolicy>,
+    /// Gain effect level in dB
+    #[cfg_attr(feature = "schema-gen", schemars(range(min = -96.0, max = 24.0)))]
+    pub gain_db: Option<f32>,
+    /// Per-block peak normalization target in dBFS
+    #[cfg_attr(feature = "schema-gen", schemars(range(min = -60.0, max = 0.0)))]
+    pub normalize_db: Option<f32>,
+    /// Skip processing while the input RMS stays below this level in dBFS
+    #[cfg_attr(feature = "schema-gen", schemars(range(min = -120.0, max = 0.0)))]
+    pub silence_threshold_db: Option<f32>,
+    /// WAV file to record the processed output to
+    pub record: Option<PathBuf>,
+    /// Real-time priority for audio threads
+    #[cfg_attr(feature = "schema-gen", schemars(range(min = 1, max = 99)))]
+    pub rt_priority: Option<u8>,
+    /// Fail instead of falling back to the default device
+    pub strict_device: Option<bool>,
+    /// Open the devices in WASAPI exclusive mode (Windows only)
+    pub exclusive: Option<bool>,
+    /// ASIO driver to open (Windows, in a build with ASIO support)
+    pub asio_driver: Option<String>,
+    /// Host APIs to search, in order (e.g. "ASIO", "WASAPI")
+    pub hosts: Option<Vec<String>>,
+    /// Effects to run after the gain stage
+    pub chain: Option<ChainSpec>,
+    /// Refuse effects that would take the chain's heap past this many bytes
+    pub max_chain_memory_bytes: Option<usize>,
+}
+
+/// The `[chain]` section.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema-gen", derive(schemars::JsonSchema))]
+#[serde(deny_unknown_fields)]
+pub struct ChainSpec {
+    /// Effects in chain order
+    #[serde(default)]
+    pub effect: Vec<EffectSpec>,
+    /// Parallel sections, placed by `position`
+    #[serde(default)]
+    pub parallel: Vec<ParallelSpec>,
+}
+
+/// One effect: its registered type name, an optional dry/wet mix and its
+/// parameters as passed to `set_param_db`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema-gen", derive(schemars::JsonSchema))]
+pub struct EffectSpec {
+    /// Effect type, e.g. "Gain" (case-insensitive)
+    #[serde(rename = "type")]
+    pub type_name: String,
+    /// Wet share, 0 (dry) to 1 (wet)
+    #[cfg_attr(feature = "schema-gen", schemars(range(min = 0.0, max = 1.0)))]
+    pub mix: Option<f32>,
+    /// Peaking bands, for a ParametricEq only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bands: Option<Vec<EqBand>>,
+    /// Parameter key -> value
+    #[serde(flatten)]
+    pub params: BTreeMap<String, f32>,
+}
+
+/// One entry of a chain file: like `EffectSpec`, with the parameters in
+/// their own `params` object.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ChainFileEffect {
+    #[serde(rename = "type")]
+    pub type_name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mix: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bands: Option<Vec<EqBand>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub params: BTreeMap<String, f32>,
+}
+
+impl From<ChainFileEffect> for EffectSpec {
+    fn from(fx: ChainFileEffect) -> Self {
+        Self { type_name: fx.type_name, mix: fx.mix, bands: fx.bands, params: fx.params }
+    }
+}
+
+impl From<EffectSpec> for ChainFileEffect {
+    fn from(fx: EffectSpec) -> Self {
+        Self { type_name: fx.type_name, mix: fx.mix, bands: fx.bands, params: fx.params }
+    }
+}
+
+/// Branches that each process a copy of the input; their outputs are summed.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema-gen", derive(schemars::JsonSchema))]
+#[serde(deny_unknown_fields)]
+pub struct ParallelSpec {
+    /// Index in `effect` to insert before; after all of them if omitted
+    pub position: Option<usize>,
+    /// Serial effect lists
+    pub branches: Vec<Vec<EffectSpec>>,
+    /// Wet share of the whole section, 0 (dry) to 1 (wet)
+    #[cfg_attr(feature = "schema-gen", schemars(range(min = 0.0, max = 1.0)))]
+    pub mix: Option<f32>,
+}
+
+impl EffectSpec {
+    fn is_parametric_eq(&self) -> bool {
+        self.type_name.eq_ignore_ascii_case("ParametricEq")
+    }
+
+    fn build(&self, registry: &EffectRegistry) -> Result<Box<dyn Effect>> {
+        let mut fx: Box<dyn Effect> = if self.is_parametric_eq() {
+            Box::new(ParametricEq::new(self.bands.clone().unwrap_or_default()))
+        } else if self.bands.is_some() {
+            return Err(anyhow!("{:?} takes no bands; only ParametricEq does", self.type_name));
+        } else {
+            registry.create(&self.type_name).ok_or_else(|| {
+                let mut known = registry.names();
+                known.push("ParametricEq");
+                known.sort_unstable();
+                anyhow!("Unknown effect type {:?}; known types: {}", self.type_name, known.join(", "))
+            })?
+        };
+        for (key, &value) in &self.params {
+            fx.set_param_db(key, value);
+        }
+        Ok(fx)
+    }
+}
+
+impl ChainSpec {
+    /// Top-level entries in chain order: `Ok(effect)` or `Err(parallel)`.
+    fn entries(&self) -> Vec<std::result::Result<&EffectSpec, &ParallelSpec>> {
+        let n = self.effect.len();
+        let mut entries = Vec::with_capacity(n + self.parallel.len());
+        for i in 0..=n {
+            let here = |p: &&ParallelSpec| p.position.map_or(i == n, |at| at.min(n) == i);
+            entries.extend(self.parallel.iter().filter(here).map(Err));
+            entries.extend(self.effect.get(i).map(Ok));
+        }
+        entries
+    }
+
+    /// Build the effects with their mix, in chain order.
+    pub fn build(&self, registry: &EffectRegistry) -> Result<Vec<(Box<dyn Effect>, f32)>> {
+        self.entries()
+            .into_iter()
+            .map(|entry| match entry {
+                Ok(spec) => Ok((spec.build(registry)?, spec.mix.unwrap_or(1.0))),
+                Err(p) => {
+                    let branches = p.branches.iter()
+                        .map(|branch| branch.iter().map(|spec| spec.build(registry)).collect::<Result<Vec<_>>>())
+                        .collect::<Result<Vec<_>>>()?;
+                    Ok((Box::new(Parallel::new(branches)) as Box<dyn Effect>, p.mix.unwrap_or(1.0)))
+                }
+            })
+            .collect()
+    }
+
+    /// Canonical names of the effects `build` makes, in chain order; unknown
+    /// types are skipped.
+    pub fn names(&self, registry: &EffectRegistry) -> Vec<&'static str> {
+        self.entries()
+            .into_iter()
+            .filter_map(|entry| match entry {
+                Ok(spec) if spec.is_parametric_eq() => Some("ParametricEq"),
+                Ok(spec) => registry.canonical_name(&spec.type_name),
+                Err(_) => Some("Parallel"),
+            })
+            .collect()
+    }
+}
+
+/// Build a chain from the `[chain]` section of a TOML config (empty if there
+/// is none), with effects from the built-in registry.
+pub fn parse_chain(config: &str, sample_rate: u32, channels: u16) -> Result<Chain> {
+    let file: ConfigFile = toml::from_str(config).context("Invalid config")?;
+    build_chain(&file.chain.unwrap_or_default(), sample_rate, channels)
+}
+
+/// Build a chain from chain file JSON, with effects from the built-in registry.
+pub fn parse_chain_json(json: &str, sample_rate: u32, channels: u16) -> Result<Chain> {
+    build_chain(&chain_spec_from_json(json)?, sample_rate, channels)
+}
+
+fn build_chain(spec: &ChainSpec, sample_rate: u32, channels: u16) -> Result<Chain> {
+    let mut chain = Chain::new(sample_rate, channels);
+    for (fx, mix) in spec.build(builtin_registry())? {
+        chain.push_with_mix(fx, mix)?;
+    }
+    Ok(chain)
+}
+
+/// The effects of chain file JSON, as a `[chain]` section.
+pub fn chain_spec_from_json(json: &str) -> Result<ChainSpec> {
+    let effects: Vec<ChainFileEffect> = serde_json::from_str(json).context("Invalid chain file")?;
+    Ok(ChainSpec { effect: effects.into_iter().map(EffectSpec::from).collect(), parallel: Vec::new() })
+}
+
+/// Chain file JSON for `spec`. Parallel sections have no chain file form.
+pub fn chain_spec_to_json(spec: &ChainSpec) -> Result<String> {
+    if !spec.parallel.is_empty() {
+        return Err(anyhow!("Parallel sections can't be written to a chain file"));
+    }
+    let effects: Vec<ChainFileEffect> = spec.effect.iter().cloned().map(ChainFileEffect::from).collect();
+    Ok(serde_json::to_string_pretty(&effects)?)
+}
+
+/// Read a chain file; see the module docs for the format. Unknown effect
+/// types are reported here rather than when the engine starts.
+pub fn load_chain_file(path: &Path) -> Result<ChainSpec> {
+    let text = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let spec = chain_spec_from_json(&text).with_context(|| format!("{} is not a bord chain file", path.display()))?;
+    spec.build(builtin_registry()).with_context(|| format!("Invalid chain in {}", path.display()))?;
+    Ok(spec)
+}
+
+/// Write `spec` as a chain file.
+pub fn save_chain_file(spec: &ChainSpec, path: &Path) -> Result<()> {
+    std::fs::write(path, chain_spec_to_json(spec)?).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+impl ConfigFile {
+    /// The `EngineConfig` this file describes; unknown host names are an error.
+    pub fn to_engine_config(&self) -> Result<EngineConfig> {
+        let hosts = self
+            .hosts
+            .iter()
+            .flatten()
+            .map(|name| crate::devices::host_id_by_name(name).ok_or_else(|| anyhow!("Unknown or unavailable host: {name}")))
+            .collect::<Result<_>>()?;
+        Ok(EngineConfig {
+            input_name: self.input.clone(),
+            output_name: self.output.clone(),
+            input_index: self.input_index,
+            output_index: self.output_index,
+            monitor_name: self.monitor.clone(),
+            sample_rate: self.sample_rate,
+            block_size: self.block_size,
+            channel_count: self.channels,
+            channel_alignment: self.channel_alignment.unwrap_or_default(),
+            gain_db: self.gain_db.unwrap_or(0.0),
+            normalize_db: self.normalize_db,
+            silence_threshold_db: self.silence_threshold_db,
+            record_path: self.record.clone(),
+            rt_priority: self.rt_priority,
+            exclusive_mode: self.exclusive == Some(true),
+            asio_driver: self.asio_driver.clone(),
+            pick_policy: if self.strict_device == Some(true) { PickPolicy::IndexThenName } else { PickPolicy::default() },
+            hosts,
+            chain: self.chain.clone(),
+            max_chain_memory_bytes: self.max_chain_memory_bytes,
+        })
+    }
+}
+
+impl From<&EngineConfig> for ConfigFile {
+    fn from(cfg: &EngineConfig) -> Self {
+        Self {
+            input: cfg.input_name.clone(),
+            output: cfg.output_name.clone(),
+            input_index: cfg.input_index,
+            output_index: cfg.output_index,
+            monitor: cfg.monitor_name.clone(),
+            sample_rate: cfg.sample_rate,
+            block_size: cfg.block_size,
+            channels: cfg.channel_count,
+            channel_alignment: (cfg.channel_alignment != ChannelAlignmentPolicy::default()).then_some(cfg.channel_alignment),
+            gain_db: Some(cfg.gain_db),
+            normalize_db: cfg.normalize_db,
+            silence_threshold_db: cfg.silence_threshold_db,
+            record: cfg.record_path.clone(),
+            rt_priority: cfg.rt_priority,
+            strict_device: (cfg.pick_policy == PickPolicy::IndexThenName).then_some(true),
+            exclusive: cfg.exclusive_mode.then_some(true),
+            asio_driver: cfg.asio_driver.clone(),
+            hosts: (!cfg.hosts.is_empty()).then(|| cfg.hosts.iter().map(|h| h.name().to_string()).collect()),
+            chain: cfg.chain.clone(),
+            max_chain_memory_bytes: cfg.max_chain_memory_bytes,
+        }
+    }
+}
+
+/// One schema violation, addressed by JSON pointer into the config.
+#[derive(Debug, Clone)]
+pub struct Issue {
+    pub path: String,
+    pub message: String,
+}
+
+/// Read `path` as TOML (`.toml`) or JSON (anything else) into a JSON value.
+pub fn load_value(path: &Path) -> Result<serde_json::Value> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let is_toml = path.extension().is_some_and(|e| e.eq_ignore_ascii_case("toml"));
+    if is_toml {
+        let v: toml::Value = toml::from_str(&text)
+            .with_context(|| format!("{} is not valid TOML", path.display()))?;
+        Ok(serde_json::to_value(v)?)
+    } else {
+        serde_json::from_str(&text).with_context(|| format!("{} is not valid JSON", path.display()))
+    }
+}
+
+/// Check `value` against `SCHEMA`; an empty list means it is valid.
+pub fn validate(value: &serde_json::Value) -> Result<Vec<Issue>> {
+    let schema: serde_json::Value = serde_json::from_str(SCHEMA)?;
+    let validator = jsonschema::validator_for(&schema).map_err(|e| anyhow!("Invalid embedded schema: {e}"))?;
+    Ok(validator
+        .iter_errors(value)
+        .map(|e| {
+            let path = e.instance_path().to_string();
+            Issue { path: if path.is_empty() { "/".into() } else { path }, message: e.to_string() }
+        })
+        .collect())
+}
+
+#[cfg(feature = "schema-gen")]
+pub fn generate_schema() -> String {
+    let schema = schemars::schema_for!(ConfigFile);
+    serde_json::to_string_pretty(&schema).expect("schema serializes")
+}
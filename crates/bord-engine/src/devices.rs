@@ -1,16 +1,109 @@
-use anyhow::Result;
+use anyhow::{anyhow, Context, Result};
 use cpal::traits::{DeviceTrait, HostTrait};
+use std::io::{BufRead, Write};
+
+/// One or more cpal hosts searched in order (e.g. ASIO first, then WASAPI).
+pub struct Host {
+    hosts: Vec<cpal::Host>,
+}
+
+impl Host {
+    /// Just the platform's default host.
+    pub fn default_host() -> Self {
+        Self { hosts: vec![cpal::default_host()] }
+    }
+
+    /// Every host available on this platform, in cpal's order.
+    pub fn all() -> Self {
+        let hosts = cpal::available_hosts().into_iter().filter_map(|id| cpal::host_from_id(id).ok()).collect();
+        Self { hosts }
+    }
+
+    /// The listed hosts in the given order; unavailable ones are skipped.
+    /// Errors only if none of them could be opened.
+    pub fn with_hosts(include: &[cpal::HostId]) -> Result<Host> {
+        let hosts: Vec<_> = include.iter().filter_map(|&id| cpal::host_from_id(id).ok()).collect();
+        if hosts.is_empty() {
+            return Err(anyhow!("None of the requested hosts are available: {include:?}"));
+        }
+        Ok(Self { hosts })
+    }
+
+    pub fn ids(&self) -> Vec<cpal::HostId> {
+        self.hosts.iter().map(|h| h.id()).collect()
+    }
+
+    /// Devices of every host, host by host. Hosts that fail to enumerate are skipped.
+    pub fn all_devices(&self) -> impl Iterator<Item = cpal::Device> + '_ {
+        self.hosts.iter().filter_map(|h| h.devices().ok()).flatten()
+    }
+
+    /// Default input of the first host that has one.
+    pub fn default_input_device(&self) -> Option<cpal::Device> {
+        self.hosts.iter().find_map(|h| h.default_input_device())
+    }
+
+    /// Default output of the first host that has one.
+    pub fn default_output_device(&self) -> Option<cpal::Device> {
+        self.hosts.iter().find_map(|h| h.default_output_device())
+    }
+}
+
+/// Look up a host by (case-insensitive) name, e.g. "ALSA", "ASIO", "WASAPI".
+pub fn host_id_by_name(name: &str) -> Option<cpal::HostId> {
+    cpal::available_hosts().into_iter().find(|id| id.name().eq_ignore_ascii_case(name))
+}
+
+/// Why there is no ASIO host. ASIO is Windows-only and cpal leaves it out
+/// unless built with its `asio` feature, which needs the Steinberg ASIO SDK
+/// (see the note on `cpal` in bord-engine's Cargo.toml).
+pub const ASIO_UNAVAILABLE: &str =
+    "This build has no ASIO host: ASIO needs Windows and cpal built with its `asio` feature (and the ASIO SDK, via CPAL_ASIO_DIR)";
+
+/// The ASIO host, if this build has one (see `ASIO_UNAVAILABLE`).
+pub fn asio_host_id() -> Option<cpal::HostId> {
+    host_id_by_name("ASIO")
+}
+
+/// Installed ASIO drivers by name. Each is one device of the ASIO host, with
+/// both inputs and outputs; `EngineConfig::asio_driver` takes these names.
+pub fn list_asio_drivers() -> Result<Vec<String>> {
+    let id = asio_host_id().ok_or_else(|| anyhow!(ASIO_UNAVAILABLE))?;
+    let host = cpal::host_from_id(id).context("The ASIO host is unavailable")?;
+    let mut names: Vec<String> = host.devices()?.filter_map(|d| d.name().ok()).collect();
+    names.dedup();
+    Ok(names)
+}
 
 #[derive(Debug, Clone)]
 pub struct DeviceInfo {
     pub name: String,
     pub is_default_input: bool,
     pub is_default_output: bool,
+    /// Smallest buffer the default input config allows, in ms at its sample
+    /// rate; `None` without input or where the backend doesn't say.
+    pub input_latency_ms: Option<f32>,
+    /// As `input_latency_ms`, for the default output config.
+    pub output_latency_ms: Option<f32>,
 }
 
+/// Devices of the platform's default host.
 pub fn list_devices() -> Result<Vec<DeviceInfo>> {
-    let host = cpal::default_host();
+    Ok(devices_of(&cpal::default_host()))
+}
+
+/// Devices of one host, e.g. ASIO on Windows where the default is WASAPI.
+pub fn list_devices_for_host(host_id: cpal::HostId) -> Result<Vec<DeviceInfo>> {
+    let host = cpal::host_from_id(host_id).with_context(|| format!("Host {} is unavailable", host_id.name()))?;
+    Ok(devices_of(&host))
+}
 
+/// Hosts available on this platform with their names, in cpal's order.
+pub fn list_all_hosts() -> Vec<(cpal::HostId, &'static str)> {
+    cpal::available_hosts().into_iter().map(|id| (id, id.name())).collect()
+}
+
+fn devices_of(host: &cpal::Host) -> Vec<DeviceInfo> {
     let default_in  = host.default_input_device().map(|d| d.name().unwrap_or_default());
     let default_out = host.default_output_device().map(|d| d.name().unwrap_or_default());
 
@@ -21,15 +114,46 @@ pub fn list_devices() -> Result<Vec<DeviceInfo>> {
             let name = dev.name().unwrap_or_else(|_| "<unknown>".to_string());
             let is_def_in  = default_in.as_ref().map(|n| n == &name).unwrap_or(false);
             let is_def_out = default_out.as_ref().map(|n| n == &name).unwrap_or(false);
-            out.push(DeviceInfo { name, is_default_input: is_def_in, is_default_output: is_def_out });
+            out.push(DeviceInfo {
+                name,
+                is_default_input: is_def_in,
+                is_default_output: is_def_out,
+                input_latency_ms: default_latency_ms(&dev, true),
+                output_latency_ms: default_latency_ms(&dev, false),
+            });
         }
     }
-    Ok(out)
+    out
 }
 
-/// Pretty-print for CLI
-pub fn print_devices() -> Result<()> {
-    let list = list_devices()?;
+/// Buffer latency of the device's default config: its smallest buffer size at
+/// its sample rate. `None` if there is no such config or the size is unknown.
+fn default_latency_ms(dev: &cpal::Device, want_input: bool) -> Option<f32> {
+    let config = if want_input { dev.default_input_config() } else { dev.default_output_config() }.ok()?;
+    let (min, _) = buffer_size_range(dev, want_input)?;
+    Some(min as f32 * 1000.0 / config.sample_rate().0 as f32)
+}
+
+/// Frames per buffer the device's default config accepts, as (min, max).
+/// `None` if there is no such config or the backend doesn't say.
+pub fn buffer_size_range(dev: &cpal::Device, want_input: bool) -> Option<(u32, u32)> {
+    let config = if want_input { dev.default_input_config() } else { dev.default_output_config() }.ok()?;
+    match *config.buffer_size() {
+        cpal::SupportedBufferSize::Range { min, max } => Some((min, max)),
+        cpal::SupportedBufferSize::Unknown => None,
+    }
+}
+
+/// Pretty-print for CLI; `host` defaults to the platform's default host.
+/// `verbose` adds each device's buffer latency.
+pub fn print_devices(host: Option<cpal::HostId>, verbose: bool) -> Result<()> {
+    if host.is_some() && host == asio_host_id() {
+        return print_asio_drivers(verbose);
+    }
+    let list = match host {
+        Some(id) => list_devices_for_host(id)?,
+        None => list_devices()?,
+    };
     if list.is_empty() {
         println!("(no devices found)");
         return Ok(());
@@ -40,7 +164,106 @@ pub fn print_devices() -> Result<()> {
         if d.is_default_output { if !marks.is_empty() { marks.push(' ');} marks.push_str("*O"); }
         if !marks.is_empty() { print!("[{marks}] "); }
         println!("{:>2}  {}", i, d.name);
+        if verbose {
+            let ms = |l: Option<f32>| l.map_or_else(|| "unknown".to_string(), |ms| format!("{ms:.1} ms"));
+            println!("      latency: in {}, out {}", ms(d.input_latency_ms), ms(d.output_latency_ms));
+        }
     }
     Ok(())
 }
 
+/// ASIO drivers are listed on their own: each is a single device with both
+/// directions, so the default input/output marks don't apply.
+fn print_asio_drivers(verbose: bool) -> Result<()> {
+    let drivers = list_asio_drivers()?;
+    if drivers.is_empty() {
+        println!("(no ASIO drivers installed)");
+        return Ok(());
+    }
+    println!("ASIO drivers:");
+    let host = Host::with_hosts(&[asio_host_id().context(ASIO_UNAVAILABLE)?])?;
+    for (i, name) in drivers.iter().enumerate() {
+        println!("{i:>2}  {name}");
+        if verbose {
+            if let Some(dev) = host.all_devices().find(|d| d.name().is_ok_and(|n| &n == name)) {
+                let ch = |want_input| max_channels(&dev, want_input).map_or_else(|| "?".to_string(), |n| n.to_string());
+                println!("      channels: in {}, out {}", ch(true), ch(false));
+            }
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn supports_direction(dev: &cpal::Device, want_input: bool) -> bool {
+    if want_input {
+        dev.supported_input_configs().ok().is_some()
+    } else {
+        dev.supported_output_configs().ok().is_some()
+    }
+}
+
+/// Most channels any supported configuration offers, if the device reports any.
+pub(crate) fn max_channels(dev: &cpal::Device, want_input: bool) -> Option<u16> {
+    if want_input {
+        dev.supported_input_configs().ok()?.map(|r| r.channels()).max()
+    } else {
+        dev.supported_output_configs().ok()?.map(|r| r.channels()).max()
+    }
+}
+
+/// Supported configurations as `"2ch 44100-48000 Hz F32"` lines.
+pub(crate) fn config_summaries(dev: &cpal::Device, want_input: bool) -> Vec<String> {
+    let ranges: Vec<cpal::SupportedStreamConfigRange> = if want_input {
+        dev.supported_input_configs().map(|c| c.collect()).unwrap_or_default()
+    } else {
+        dev.supported_output_configs().map(|c| c.collect()).unwrap_or_default()
+    };
+    ranges
+        .iter()
+        .map(|r| {
+            let (lo, hi) = (r.min_sample_rate().0, r.max_sample_rate().0);
+            let rate = if lo == hi { format!("{lo}") } else { format!("{lo}-{hi}") };
+            format!("{}ch {rate} Hz {:?}", r.channels(), r.sample_format())
+        })
+        .collect()
+}
+
+/// List `host`'s input (or output) devices with their configurations and ask
+/// on stdin. The returned index is what `EngineConfig::input_index` /
+/// `output_index` expect.
+pub fn prompt_device_index(host: &Host, want_input: bool) -> Result<usize> {
+    let side = if want_input { "input" } else { "output" };
+    let devices: Vec<_> = host.all_devices().filter(|d| supports_direction(d, want_input)).collect();
+    if devices.is_empty() {
+        return Err(anyhow!("No {side} devices found"));
+    }
+    println!("Available {side} devices:");
+    for (i, d) in devices.iter().enumerate() {
+        println!("{:>2}  {}", i, d.name().unwrap_or_else(|_| "<unknown>".to_string()));
+        for cfg in config_summaries(d, want_input) {
+            println!("      {cfg}");
+        }
+    }
+    let stdin = std::io::stdin();
+    let mut line = String::new();
+    loop {
+        print!("Select {side} device [0-{}]: ", devices.len() - 1);
+        std::io::stdout().flush()?;
+        line.clear();
+        if stdin.lock().read_line(&mut line).context("Failed to read selection")? == 0 {
+            return Err(anyhow!("No {side} device selected (end of input)"));
+        }
+        match line.trim().parse::<usize>() {
+            Ok(i) if i < devices.len() => return Ok(i),
+            _ => println!("Please enter a number between 0 and {}.", devices.len() - 1),
+        }
+    }
+}
+
+/// Interactively choose a device from the default host.
+pub fn pick_device_interactive(want_input: bool) -> Result<cpal::Device> {
+    let host = Host::default_host();
+    let idx = prompt_device_index(&host, want_input)?;
+    let dev = host.all_devices().filter(|d| supports_direction(d, want_input)).nth(idx);
+    dev.context("Selected device disappeared")
+}
@@ -0,0 +1,106 @@
+use alloc::alloc::{alloc, dealloc, handle_alloc_error, Layout};
+use core::fmt;
+use core::ops::{Deref, DerefMut};
+use core::ptr::NonNull;
+
+/// Alignment of every `AlignedVec` allocation: one cache line, and enough
+/// for aligned AVX/AVX-512 loads.
+pub const ALIGN: usize = 64;
+
+/// Fixed-length heap buffer whose first element sits on a 64-byte boundary
+/// (a plain `Vec<f32>` only promises 4). Sized once, in `prepare` or a
+/// constructor; it derefs to a slice for everything else.
+pub struct AlignedVec<T> {
+    ptr: NonNull<T>,
+    len: usize,
+}
+
+// Safety: owns its elements like `Box<[T]>`.
+unsafe impl<T: Send> Send for AlignedVec<T> {}
+unsafe impl<T: Sync> Sync for AlignedVec<T> {}
+
+impl<T> AlignedVec<T> {
+    /// Empty, without allocating.
+    pub const fn new() -> Self {
+        Self { ptr: NonNull::dangling(), len: 0 }
+    }
+
+    /// `len` copies of `value`.
+    pub fn from_elem(value: T, len: usize) -> Self
+    where
+        T: Clone,
+    {
+        let ptr = match Self::layout(len) {
+            // Safety: `layout` has a non-zero size.
+            Some(layout) => NonNull::new(unsafe { alloc(layout) }.cast::<T>()).unwrap_or_else(|| handle_alloc_error(layout)),
+            // Zero-sized elements still get `len` clones, which `Drop` drops.
+            None => NonNull::dangling(),
+        };
+        for i in 0..len {
+            // Safety: in bounds of the allocation (or a zero-sized write); each slot written once.
+            unsafe { ptr.as_ptr().add(i).write(value.clone()) };
+        }
+        Self { ptr, len }
+    }
+
+    /// Heap bytes held, for `Effect::memory_usage_bytes`.
+    pub fn memory_bytes(&self) -> usize {
+        Self::layout(self.len).map_or(0, |l| l.size())
+    }
+
+    /// `None` when nothing needs allocating (no elements, or zero-sized ones).
+    fn layout(len: usize) -> Option<Layout> {
+        let layout = Layout::array::<T>(len).and_then(|l| l.align_to(ALIGN)).expect("AlignedVec too large");
+        (layout.size() > 0).then_some(layout)
+    }
+}
+
+impl<T> Drop for AlignedVec<T> {
+    fn drop(&mut self) {
+        // Safety: the first `len` elements were initialised in `from_elem`,
+        // and the allocation was made with this layout.
+        unsafe {
+            core::ptr::drop_in_place(core::ptr::slice_from_raw_parts_mut(self.ptr.as_ptr(), self.len));
+            if let Some(layout) = Self::layout(self.len) {
+                dealloc(self.ptr.as_ptr().cast(), layout);
+            }
+        }
+    }
+}
+
+impl<T> Deref for AlignedVec<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        // Safety: `len` initialised elements (or a dangling, aligned pointer for none).
+        unsafe { core::slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl<T> DerefMut for AlignedVec<T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        // Safety: as in `deref`, and `&mut self` makes this the only borrow.
+        unsafe { core::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl<T> Default for AlignedVec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone> Clone for AlignedVec<T> {
+    fn clone(&self) -> Self {
+        let Some(first) = self.first() else { return Self::new() };
+        let mut out = Self::from_elem(first.clone(), self.len);
+        out.clone_from_slice(self);
+        out
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for AlignedVec<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
@@ -0,0 +1,76 @@
+use super::delay_line::DelayLine;
+use super::effect::{Effect, ParamDescriptor};
+
+const PARAMS: &[ParamDescriptor] = &[ParamDescriptor::new("feedback", 0.5), ParamDescriptor::new("delay_samples", 556.0)];
+
+/// Schroeder all-pass, `H(z) = (g + z^-D) / (1 + g z^-D)` with `g = feedback`
+/// in (-1, 1) and `D = delay_samples`: flat magnitude, frequency-dependent
+/// phase. Chain a few with mutually prime delays for a diffuse reverb tail.
+pub struct AllPassFilter {
+    pub feedback: f32,
+    delay_samples: usize,
+    line: DelayLine,
+}
+
+impl AllPassFilter {
+    pub fn new(delay_samples: usize, feedback: f32) -> Self {
+        Self { feedback: clamp_feedback(feedback), delay_samples: delay_samples.max(1), line: DelayLine::new() }
+    }
+    pub fn delay_samples(&self) -> usize {
+        self.delay_samples
+    }
+    /// Real-time safe up to the delay the filter was prepared with; a longer
+    /// delay reallocates (and clears) the buffer.
+    pub fn set_delay_samples(&mut self, n: usize) {
+        self.delay_samples = n.max(1);
+        if !self.line.is_empty() {
+            self.line.set_delay(self.delay_samples);
+        }
+    }
+}
+
+fn clamp_feedback(g: f32) -> f32 {
+    g.clamp(-0.999, 0.999)
+}
+
+impl Effect for AllPassFilter {
+    fn name(&self) -> &str {
+        "AllPassFilter"
+    }
+
+    fn prepare(&mut self, _sr: u32, channels: u16) {
+        self.line.prepare(channels, self.delay_samples);
+    }
+    fn set_param_db(&mut self, key: &str, value: f32) {
+        match key {
+            "feedback" => self.feedback = clamp_feedback(value),
+            "delay_samples" => self.set_delay_samples(value.max(1.0) as usize),
+            _ => {}
+        }
+    }
+    fn param_descriptors(&self) -> &[ParamDescriptor] {
+        PARAMS
+    }
+    fn process(&mut self, block: &mut [f32]) {
+        if self.line.is_empty() {
+            return;
+        }
+        let g = self.feedback;
+        for frame in block.chunks_exact_mut(self.line.channels()) {
+            for (c, x) in frame.iter_mut().enumerate() {
+                // w[n] = x[n] - g w[n-D];  y[n] = g w[n] + w[n-D]
+                let delayed = self.line.read(c);
+                let w = *x - g * delayed;
+                self.line.write(c, w);
+                *x = g * w + delayed;
+            }
+            self.line.advance();
+        }
+    }
+    fn clone_fresh(&self) -> Option<Box<dyn Effect>> {
+        Some(Box::new(AllPassFilter::new(self.delay_samples, self.feedback)))
+    }
+    fn memory_usage_bytes(&self) -> usize {
+        self.line.memory_bytes()
+    }
+}
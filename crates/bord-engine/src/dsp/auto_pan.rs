@@ -0,0 +1,68 @@
+use super::effect::{Effect, Frame, ParamDescriptor};
+use super::lfo::{Lfo, LfoWaveform};
+use std::f32::consts::{FRAC_PI_4, SQRT_2};
+
+const PARAMS: &[ParamDescriptor] = &[ParamDescriptor::new("rate", 0.5), ParamDescriptor::new("depth", 1.0)];
+
+/// Sweeps a stereo signal between the speakers with an LFO. `depth` in [0, 1]
+/// scales the swing (1 reaches hard left/right). Equal-power gains, normalized
+/// to unity at the centre, so `depth == 0` is a passthrough and hard-panned
+/// peaks are lifted by up to 3 dB. Stereo only; other channel counts pass
+/// through untouched.
+pub struct AutoPan {
+    pub depth: f32,
+    lfo: Lfo,
+    stereo: bool,
+}
+
+impl AutoPan {
+    pub fn new(rate: f32, depth: f32, waveform: LfoWaveform) -> Self {
+        Self { depth: depth.clamp(0.0, 1.0), lfo: Lfo::new(waveform, rate), stereo: false }
+    }
+    /// Sweep rate in Hz; real-time safe.
+    pub fn set_rate(&mut self, hz: f32) {
+        self.lfo.set_rate(hz);
+    }
+    pub fn rate(&self) -> f32 {
+        self.lfo.rate()
+    }
+}
+
+impl Effect for AutoPan {
+    fn name(&self) -> &str {
+        "AutoPan"
+    }
+
+    fn prepare(&mut self, sr: u32, channels: u16) {
+        self.stereo = channels == 2;
+        self.lfo.prepare(sr);
+    }
+    fn set_param_db(&mut self, key: &str, value: f32) {
+        match key {
+            "rate" => self.set_rate(value),
+            "depth" => self.depth = value.clamp(0.0, 1.0),
+            _ => {}
+        }
+    }
+    fn param_descriptors(&self) -> &[ParamDescriptor] {
+        PARAMS
+    }
+    fn process(&mut self, block: &mut [f32]) {
+        self.process_frames(Frame::from_interleaved_mut(block));
+    }
+    fn process_frames(&mut self, frames: &mut [Frame<2>]) {
+        if !self.stereo {
+            return;
+        }
+        for Frame([l, r]) in frames.iter_mut() {
+            // pan -1 (left) .. 1 (right) -> angle 0 .. pi/2
+            let angle = (self.depth * self.lfo.tick() + 1.0) * FRAC_PI_4;
+            let (sin, cos) = angle.sin_cos();
+            *l *= cos * SQRT_2;
+            *r *= sin * SQRT_2;
+        }
+    }
+    fn clone_fresh(&self) -> Option<Box<dyn Effect>> {
+        Some(Box::new(AutoPan::new(self.lfo.rate(), self.depth, self.lfo.waveform)))
+    }
+}
@@ -0,0 +1,73 @@
+use super::effect::{Effect, Frame, ParamDescriptor};
+use super::smoothed::{SmoothedParam, DEFAULT_RAMP_S};
+
+const PARAMS: &[ParamDescriptor] = &[ParamDescriptor::new("balance", 0.0)];
+
+/// Linear L/R balance of a stereo signal: -1 silences the right channel,
+/// 0 is unity on both, 1 silences the left. Unlike panning there is no
+/// equal-power law; the louder side always stays at unity. Gain changes ramp
+/// over 20 ms. Stereo only; other channel counts pass through untouched.
+pub struct StereoBalance {
+    pub balance: f32,
+    stereo: bool,
+    left: SmoothedParam,
+    right: SmoothedParam,
+}
+
+impl StereoBalance {
+    pub fn new(balance: f32) -> Self {
+        let balance = balance.clamp(-1.0, 1.0);
+        let (l, r) = Self::gains(balance);
+        Self { balance, stereo: false, left: SmoothedParam::new(l), right: SmoothedParam::new(r) }
+    }
+
+    pub fn set_balance(&mut self, balance: f32) {
+        self.balance = balance.clamp(-1.0, 1.0);
+        let (l, r) = Self::gains(self.balance);
+        self.left.set_target(l);
+        self.right.set_target(r);
+    }
+
+    fn gains(balance: f32) -> (f32, f32) {
+        ((1.0 - balance).clamp(0.0, 1.0), (1.0 + balance).clamp(0.0, 1.0))
+    }
+}
+
+impl Effect for StereoBalance {
+    fn name(&self) -> &str {
+        "StereoBalance"
+    }
+
+    fn prepare(&mut self, sr: u32, channels: u16) {
+        self.stereo = channels == 2;
+        if !self.stereo {
+            crate::bord_log!("StereoBalance: needs 2 channels, got {channels}; passing through");
+        }
+        self.left.prepare(sr, DEFAULT_RAMP_S);
+        self.right.prepare(sr, DEFAULT_RAMP_S);
+    }
+    fn set_param_db(&mut self, key: &str, value: f32) {
+        // Linear -1..1, despite the method name.
+        if key == "balance" {
+            self.set_balance(value);
+        }
+    }
+    fn param_descriptors(&self) -> &[ParamDescriptor] {
+        PARAMS
+    }
+    fn process(&mut self, block: &mut [f32]) {
+        self.process_frames(Frame::from_interleaved_mut(block));
+    }
+    fn process_frames(&mut self, frames: &mut [Frame<2>]) {
+        if !self.stereo {
+            return;
+        }
+        for Frame([l, r]) in frames.iter_mut() {
+            *l *= self.left.tick();
+            *r *= self.right.tick();
+        }
+    }
+    fn clone_fresh(&self) -> Option<Box<dyn Effect>> {
+        Some(Box::new(StereoBalance::new(self.balance)))
+    }
+}
@@ -0,0 +1,61 @@
+use super::biquad::{Biquad, Coeffs};
+use super::effect::{Effect, ParamDescriptor};
+
+const PARAMS: &[ParamDescriptor] = &[ParamDescriptor::new("center", 1000.0), ParamDescriptor::new("bandwidth", 500.0)];
+
+/// Second-order band-pass with 0 dB gain at `center_hz`.
+pub struct BandPass {
+    pub center_hz: f32,
+    pub bandwidth_hz: f32,
+    sr: u32,
+    channels: u16, // 0 until prepared
+    filter: Biquad,
+}
+
+impl BandPass {
+    pub fn new(center_hz: f32, bandwidth_hz: f32) -> Self {
+        Self { center_hz, bandwidth_hz, sr: 48_000, channels: 0, filter: Biquad::new() }
+    }
+    fn recompute(&mut self) {
+        let q = self.center_hz / self.bandwidth_hz.max(1e-3);
+        self.filter.coeffs = Coeffs::band_pass(self.center_hz, q, self.sr);
+    }
+}
+
+impl Effect for BandPass {
+    fn name(&self) -> &str {
+        "BandPass"
+    }
+
+    fn prepare(&mut self, sr: u32, channels: u16) {
+        self.sr = sr;
+        self.channels = channels;
+        self.filter.prepare(channels);
+        self.recompute();
+    }
+    fn prepare_hot(&mut self, sr: u32, channels: u16) -> bool {
+        if (sr, channels) != (self.sr, self.channels) {
+            return false;
+        }
+        self.recompute();
+        true
+    }
+    fn set_param_db(&mut self, key: &str, value: f32) {
+        // Hz, despite the method name.
+        match key {
+            "center" => self.center_hz = value,
+            "bandwidth" => self.bandwidth_hz = value,
+            _ => return,
+        }
+        self.recompute();
+    }
+    fn param_descriptors(&self) -> &[ParamDescriptor] {
+        PARAMS
+    }
+    fn process(&mut self, block: &mut [f32]) {
+        self.filter.process(block);
+    }
+    fn clone_fresh(&self) -> Option<Box<dyn Effect>> {
+        Some(Box::new(BandPass::new(self.center_hz, self.bandwidth_hz)))
+    }
+}
@@ -0,0 +1,97 @@
+//! Single second-order section (Audio EQ Cookbook designs), Direct Form II
+//! transposed with one state pair per channel.
+
+/// Normalized coefficients (a0 = 1).
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Coeffs {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+}
+
+impl Coeffs {
+    pub(crate) fn low_pass(cutoff_hz: f32, q: f32, sr: u32) -> Self {
+        let (cos, alpha) = prewarp(cutoff_hz, q, sr);
+        let b = (1.0 - cos) / 2.0;
+        Self::normalize([b, 1.0 - cos, b], [1.0 + alpha, -2.0 * cos, 1.0 - alpha])
+    }
+
+    pub(crate) fn high_pass(cutoff_hz: f32, q: f32, sr: u32) -> Self {
+        let (cos, alpha) = prewarp(cutoff_hz, q, sr);
+        let b = (1.0 + cos) / 2.0;
+        Self::normalize([b, -(1.0 + cos), b], [1.0 + alpha, -2.0 * cos, 1.0 - alpha])
+    }
+
+    /// Constant 0 dB peak gain band-pass.
+    pub(crate) fn band_pass(center_hz: f32, q: f32, sr: u32) -> Self {
+        let (cos, alpha) = prewarp(center_hz, q, sr);
+        Self::normalize([alpha, 0.0, -alpha], [1.0 + alpha, -2.0 * cos, 1.0 - alpha])
+    }
+
+    pub(crate) fn notch(center_hz: f32, q: f32, sr: u32) -> Self {
+        let (cos, alpha) = prewarp(center_hz, q, sr);
+        Self::normalize([1.0, -2.0 * cos, 1.0], [1.0 + alpha, -2.0 * cos, 1.0 - alpha])
+    }
+
+    /// Boost or cut of `gain_db` around `center_hz`, 0 dB elsewhere.
+    pub(crate) fn peaking(center_hz: f32, q: f32, gain_db: f32, sr: u32) -> Self {
+        let (cos, alpha) = prewarp(center_hz, q, sr);
+        let a = 10f64.powf(gain_db as f64 / 40.0);
+        Self::normalize([1.0 + alpha * a, -2.0 * cos, 1.0 - alpha * a], [1.0 + alpha / a, -2.0 * cos, 1.0 - alpha / a])
+    }
+
+    /// From raw `b`/`a` coefficients, dividing through by `a[0]`.
+    pub(crate) fn normalize(b: [f64; 3], a: [f64; 3]) -> Self {
+        Self {
+            b0: (b[0] / a[0]) as f32,
+            b1: (b[1] / a[0]) as f32,
+            b2: (b[2] / a[0]) as f32,
+            a1: (a[1] / a[0]) as f32,
+            a2: (a[2] / a[0]) as f32,
+        }
+    }
+}
+
+/// `(cos w0, alpha)` with the frequency kept below Nyquist and Q above zero.
+fn prewarp(hz: f32, q: f32, sr: u32) -> (f64, f64) {
+    let sr = sr.max(1) as f64;
+    let w0 = std::f64::consts::TAU * (hz as f64).clamp(1.0, 0.49 * sr) / sr;
+    let (sin, cos) = w0.sin_cos();
+    (cos, sin / (2.0 * (q as f64).max(1e-3)))
+}
+
+pub(crate) struct Biquad {
+    pub(crate) coeffs: Coeffs,
+    state: Vec<[f32; 2]>,
+}
+
+impl Biquad {
+    pub(crate) fn new() -> Self {
+        Self { coeffs: Coeffs::default(), state: Vec::new() }
+    }
+
+    pub(crate) fn prepare(&mut self, channels: u16) {
+        self.state = vec![[0.0; 2]; channels.max(1) as usize];
+    }
+
+    pub(crate) fn process(&mut self, block: &mut [f32]) {
+        let channels = self.state.len().max(1);
+        for frame in block.chunks_mut(channels) {
+            for (ch, x) in frame.iter_mut().enumerate() {
+                *x = self.tick(ch, *x);
+            }
+        }
+    }
+
+    /// Filter one sample of channel `ch`.
+    pub(crate) fn tick(&mut self, ch: usize, x: f32) -> f32 {
+        let c = self.coeffs;
+        let z = &mut self.state[ch];
+        let y = c.b0 * x + z[0];
+        z[0] = c.b1 * x - c.a1 * y + z[1];
+        z[1] = c.b2 * x - c.a2 * y;
+        y
+    }
+}
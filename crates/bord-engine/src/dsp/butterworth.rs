@@ -0,0 +1,115 @@
+//! Butterworth cascades shared by `HighPassFilter` and `LowPassFilter`:
+//! order N is N/2 biquads plus one first-order section when N is odd,
+//! each run in Direct Form II transposed.
+
+#[cfg(not(feature = "std"))]
+use super::math::FloatExt;
+use alloc::vec;
+use alloc::vec::Vec;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Response {
+    LowPass,
+    HighPass,
+}
+
+/// Normalized coefficients (a0 = 1); first-order sections have b2 = a2 = 0.
+#[derive(Debug, Clone, Copy, Default)]
+struct Section {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+}
+
+pub(crate) const MAX_ORDER: u8 = 8;
+
+pub(crate) struct Cascade {
+    response: Response,
+    sections: Vec<Section>,
+    state: Vec<[f32; 2]>, // [channel * sections + section]
+    channels: usize,
+}
+
+impl Cascade {
+    pub(crate) fn new(response: Response) -> Self {
+        Self { response, sections: Vec::new(), state: Vec::new(), channels: 1 }
+    }
+
+    /// Size coefficient and state storage for `order` and compute coefficients.
+    pub(crate) fn prepare(&mut self, order: u8, cutoff_hz: f32, sr: u32, channels: u16) {
+        let order = order.clamp(1, MAX_ORDER) as usize;
+        self.channels = channels.max(1) as usize;
+        self.sections = vec![Section::default(); order.div_ceil(2)];
+        self.state = vec![[0.0; 2]; self.sections.len() * self.channels];
+        self.design(order, cutoff_hz, sr);
+    }
+
+    /// Recompute coefficients in place; state is kept so the change is click-free-ish.
+    pub(crate) fn set_cutoff(&mut self, order: u8, cutoff_hz: f32, sr: u32) {
+        let order = order.clamp(1, MAX_ORDER) as usize;
+        if order.div_ceil(2) == self.sections.len() {
+            self.design(order, cutoff_hz, sr);
+        }
+    }
+
+    fn design(&mut self, order: usize, cutoff_hz: f32, sr: u32) {
+        let sr = sr.max(1) as f64;
+        let fc = (cutoff_hz as f64).clamp(1.0, 0.49 * sr);
+        let pairs = order / 2;
+        for (k, s) in self.sections.iter_mut().enumerate() {
+            *s = if k < pairs {
+                let q = 1.0 / (2.0 * ((2 * k + 1) as f64 * core::f64::consts::PI / (2 * order) as f64).sin());
+                biquad(self.response, fc, q, sr)
+            } else {
+                first_order(self.response, fc, sr)
+            };
+        }
+    }
+
+    pub(crate) fn process(&mut self, block: &mut [f32]) {
+        let n = self.sections.len();
+        for frame in block.chunks_mut(self.channels) {
+            for (ch, x) in frame.iter_mut().enumerate() {
+                let state = &mut self.state[ch * n..(ch + 1) * n];
+                let mut v = *x;
+                for (s, z) in self.sections.iter().zip(state.iter_mut()) {
+                    let y = s.b0 * v + z[0];
+                    z[0] = s.b1 * v - s.a1 * y + z[1];
+                    z[1] = s.b2 * v - s.a2 * y;
+                    v = y;
+                }
+                *x = v;
+            }
+        }
+    }
+}
+
+fn biquad(response: Response, fc: f64, q: f64, sr: f64) -> Section {
+    let w0 = core::f64::consts::TAU * fc / sr;
+    let (sin, cos) = w0.sin_cos();
+    let alpha = sin / (2.0 * q);
+    let a0 = 1.0 + alpha;
+    let (b0, b1) = match response {
+        Response::LowPass => ((1.0 - cos) / 2.0, 1.0 - cos),
+        Response::HighPass => ((1.0 + cos) / 2.0, -(1.0 + cos)),
+    };
+    Section {
+        b0: (b0 / a0) as f32,
+        b1: (b1 / a0) as f32,
+        b2: (b0 / a0) as f32,
+        a1: (-2.0 * cos / a0) as f32,
+        a2: ((1.0 - alpha) / a0) as f32,
+    }
+}
+
+fn first_order(response: Response, fc: f64, sr: f64) -> Section {
+    let k = (core::f64::consts::PI * fc / sr).tan();
+    let a1 = ((k - 1.0) / (k + 1.0)) as f32;
+    let (b0, b1) = match response {
+        Response::LowPass => (k / (1.0 + k), k / (1.0 + k)),
+        Response::HighPass => (1.0 / (1.0 + k), -1.0 / (1.0 + k)),
+    };
+    Section { b0: b0 as f32, b1: b1 as f32, b2: 0.0, a1, a2: 0.0 }
+}
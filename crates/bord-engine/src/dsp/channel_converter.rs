@@ -0,0 +1,64 @@
+use super::effect::Effect;
+use super::utils::vec_bytes;
+
+/// Maps `from` interleaved channels onto `to`, for an input device with a
+/// different channel count than the output. Widening repeats the input
+/// channels in order (mono -> all, stereo -> L R L R ...); narrowing averages
+/// the channels that land on each output (stereo -> mono is (L + R) / 2, and
+/// 4 -> 2 folds channels 2 and 3 onto 0 and 1).
+pub struct ChannelConverter {
+    pub to: u16,
+    from: usize,
+    input: Vec<f32>,
+}
+
+impl ChannelConverter {
+    pub fn new(to: u16) -> Self {
+        Self { to: to.max(1), from: 1, input: Vec::new() }
+    }
+}
+
+impl Effect for ChannelConverter {
+    fn name(&self) -> &str {
+        "ChannelConverter"
+    }
+
+    fn prepare(&mut self, _sr: u32, channels: u16) {
+        self.from = channels.max(1) as usize;
+        self.input = Vec::with_capacity(4096 * self.from);
+    }
+    fn output_channel_count(&self, _input_channels: u16) -> u16 {
+        self.to
+    }
+    fn process(&mut self, block: &mut [f32]) {
+        let (from, to) = (self.from, self.to as usize);
+        if from == to {
+            return;
+        }
+        let frames = block.len() / from.max(to);
+        self.input.clear();
+        self.input.extend_from_slice(&block[..frames * from]);
+        for (inp, out) in self.input.chunks_exact(from).zip(block.chunks_exact_mut(to)) {
+            if to > from {
+                for (c, s) in out.iter_mut().enumerate() {
+                    *s = inp[c % from];
+                }
+            } else {
+                out.fill(0.0);
+                for (c, &x) in inp.iter().enumerate() {
+                    out[c % to] += x;
+                }
+                // Channels `c` with `c % to == o`: from / to, rounded up for the first from % to.
+                for (o, s) in out.iter_mut().enumerate() {
+                    *s /= (from / to + usize::from(o < from % to)) as f32;
+                }
+            }
+        }
+    }
+    fn clone_fresh(&self) -> Option<Box<dyn Effect>> {
+        Some(Box::new(ChannelConverter::new(self.to)))
+    }
+    fn memory_usage_bytes(&self) -> usize {
+        vec_bytes(&self.input)
+    }
+}
@@ -0,0 +1,64 @@
+use super::effect::Effect;
+use super::utils::vec_bytes;
+
+/// Frames the mono buffer holds before it has to grow.
+const RESERVED_FRAMES: usize = 4096;
+
+/// Runs a mono `inner` effect on one channel only, e.g. a high-pass on the
+/// left: that channel is copied out to a mono buffer, processed and copied
+/// back; the others pass through. A `channel` the block doesn't have leaves
+/// it untouched.
+pub struct ChannelInsert {
+    pub channel: usize,
+    pub inner: Box<dyn Effect>,
+    channels: usize,
+    mono: Vec<f32>,
+}
+
+impl ChannelInsert {
+    pub fn new(channel: usize, inner: Box<dyn Effect>) -> Self {
+        Self { channel, inner, channels: 0, mono: Vec::new() }
+    }
+}
+
+impl Effect for ChannelInsert {
+    fn name(&self) -> &str {
+        "ChannelInsert"
+    }
+
+    fn prepare(&mut self, sr: u32, channels: u16) {
+        self.channels = channels as usize;
+        self.inner.prepare(sr, 1);
+        self.mono = Vec::with_capacity(RESERVED_FRAMES);
+    }
+    fn set_param_db(&mut self, key: &str, value: f32) {
+        self.inner.set_param_db(key, value);
+    }
+    fn process(&mut self, block: &mut [f32]) {
+        let (ch, c) = (self.channels, self.channel);
+        if c >= ch {
+            return;
+        }
+        self.mono.clear();
+        self.mono.extend(block.chunks_exact(ch).map(|frame| frame[c]));
+        self.inner.process(&mut self.mono);
+        for (frame, &s) in block.chunks_exact_mut(ch).zip(&self.mono) {
+            frame[c] = s;
+        }
+    }
+    fn latency_samples(&self) -> u32 {
+        self.inner.latency_samples()
+    }
+    fn tail_samples(&self) -> u32 {
+        self.inner.tail_samples()
+    }
+    fn supported_block_sizes(&self) -> Option<(u32, u32)> {
+        self.inner.supported_block_sizes()
+    }
+    fn clone_fresh(&self) -> Option<Box<dyn Effect>> {
+        Some(Box::new(ChannelInsert::new(self.channel, self.inner.clone_fresh()?)))
+    }
+    fn memory_usage_bytes(&self) -> usize {
+        self.inner.memory_usage_bytes() + vec_bytes(&self.mono)
+    }
+}
@@ -0,0 +1,110 @@
+use super::delay_line::DelayLine;
+use super::effect::{Effect, ParamDescriptor};
+
+const PARAMS: &[ParamDescriptor] = &[ParamDescriptor::new("gain", 0.84), ParamDescriptor::new("delay_samples", 1557.0)];
+
+/// Largest IIR gain magnitude; at 1.0 the feedback loop never decays.
+const MAX_IIR_GAIN: f32 = 0.999;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CombMode {
+    /// Feedforward: `y[n] = x[n] + gain * x[n - D]`.
+    Fir,
+    /// Feedback: `y[n] = x[n] + gain * y[n - D]`; `|gain| < 1` keeps it stable.
+    Iir,
+}
+
+/// Comb filter over a `delay_samples` delay. Short delays give flanger-like
+/// notches (`Fir`) or resonant peaks (`Iir`); long IIR delays ring out as the
+/// echoes of a reverb comb bank.
+pub struct CombFilter {
+    pub mode: CombMode,
+    gain: f32,
+    delay_samples: usize,
+    line: DelayLine,
+}
+
+impl CombFilter {
+    pub fn new(delay_samples: usize, gain: f32, mode: CombMode) -> Self {
+        let mut comb = Self { mode, gain: 0.0, delay_samples: delay_samples.max(1), line: DelayLine::new() };
+        comb.set_gain(gain);
+        comb
+    }
+    pub fn gain(&self) -> f32 {
+        self.gain
+    }
+    /// In IIR mode a gain of magnitude 1 or more is clamped, with a warning.
+    pub fn set_gain(&mut self, gain: f32) {
+        if self.mode == CombMode::Iir && gain.abs() > MAX_IIR_GAIN {
+            crate::bord_log!("CombFilter: IIR gain {gain} is unstable, clamped to ±{MAX_IIR_GAIN}");
+        }
+        self.gain = self.clamp_gain(gain);
+    }
+    fn clamp_gain(&self, gain: f32) -> f32 {
+        match self.mode {
+            CombMode::Fir => gain,
+            CombMode::Iir => gain.clamp(-MAX_IIR_GAIN, MAX_IIR_GAIN),
+        }
+    }
+    pub fn delay_samples(&self) -> usize {
+        self.delay_samples
+    }
+    /// Real-time safe up to the delay the filter was prepared with; a longer
+    /// delay reallocates (and clears) the buffer.
+    pub fn set_delay_samples(&mut self, n: usize) {
+        self.delay_samples = n.max(1);
+        if !self.line.is_empty() {
+            self.line.set_delay(self.delay_samples);
+        }
+    }
+}
+
+impl Effect for CombFilter {
+    fn name(&self) -> &str {
+        "CombFilter"
+    }
+
+    fn prepare(&mut self, _sr: u32, channels: u16) {
+        self.line.prepare(channels, self.delay_samples);
+    }
+    fn set_param_db(&mut self, key: &str, value: f32) {
+        // Clamped quietly: this may run on the audio thread.
+        match key {
+            "gain" => self.gain = self.clamp_gain(value),
+            "delay_samples" => self.set_delay_samples(value.max(1.0) as usize),
+            _ => {}
+        }
+    }
+    fn param_descriptors(&self) -> &[ParamDescriptor] {
+        PARAMS
+    }
+    fn process(&mut self, block: &mut [f32]) {
+        if self.line.is_empty() {
+            return;
+        }
+        let g = self.gain;
+        for frame in block.chunks_exact_mut(self.line.channels()) {
+            for (c, x) in frame.iter_mut().enumerate() {
+                let delayed = self.line.read(c);
+                let y = *x + g * delayed;
+                self.line.write(c, if self.mode == CombMode::Fir { *x } else { y });
+                *x = y;
+            }
+            self.line.advance();
+        }
+    }
+    /// Until the echoes have decayed by 60 dB.
+    fn tail_samples(&self) -> u32 {
+        let g = self.gain.abs();
+        if self.mode == CombMode::Fir || g < 1e-3 {
+            return self.delay_samples as u32;
+        }
+        (self.delay_samples as f32 * (1e-3f32.ln() / g.ln())).ceil() as u32
+    }
+    fn clone_fresh(&self) -> Option<Box<dyn Effect>> {
+        Some(Box::new(CombFilter::new(self.delay_samples, self.gain, self.mode)))
+    }
+    fn memory_usage_bytes(&self) -> usize {
+        self.line.memory_bytes()
+    }
+}
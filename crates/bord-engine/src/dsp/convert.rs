@@ -0,0 +1,96 @@
+//! Sample format conversions between device formats and the engine's f32.
+//! Integer formats map full scale to [-1, 1); unsigned formats are offset so
+//! that the mid-code (e.g. 32768) is exact silence. Float -> int rounds and
+//! clamps, so int -> f32 -> int is lossless.
+
+use crate::ring::SpscRingF32;
+
+#[inline]
+pub fn i16_to_f32(s: i16) -> f32 {
+    s as f32 / 32768.0
+}
+
+#[inline]
+pub fn f32_to_i16(s: f32) -> i16 {
+    (s * 32768.0).round().clamp(-32768.0, 32767.0) as i16
+}
+
+#[inline]
+pub fn u16_to_f32(s: u16) -> f32 {
+    (s as f32 - 32768.0) / 32768.0
+}
+
+#[inline]
+pub fn f32_to_u16(s: f32) -> u16 {
+    (f32_to_i16(s) as i32 + 32768) as u16
+}
+
+#[inline]
+pub fn i32_to_f32(s: i32) -> f32 {
+    (s as f64 / 2_147_483_648.0) as f32
+}
+
+#[inline]
+pub fn f32_to_i32(s: f32) -> i32 {
+    (s as f64 * 2_147_483_648.0).round().clamp(i32::MIN as f64, i32::MAX as f64) as i32
+}
+
+/// A device sample type the engine can read and write.
+pub trait SampleConvert: Copy {
+    /// The value that encodes silence (0 for signed, mid-code for unsigned).
+    const SILENCE: Self;
+    fn to_f32(self) -> f32;
+    fn from_f32(v: f32) -> Self;
+}
+
+impl SampleConvert for f32 {
+    const SILENCE: Self = 0.0;
+    #[inline] fn to_f32(self) -> f32 { self }
+    #[inline] fn from_f32(v: f32) -> Self { v }
+}
+
+impl SampleConvert for i16 {
+    const SILENCE: Self = 0;
+    #[inline] fn to_f32(self) -> f32 { i16_to_f32(self) }
+    #[inline] fn from_f32(v: f32) -> Self { f32_to_i16(v) }
+}
+
+impl SampleConvert for u16 {
+    const SILENCE: Self = 32768;
+    #[inline] fn to_f32(self) -> f32 { u16_to_f32(self) }
+    #[inline] fn from_f32(v: f32) -> Self { f32_to_u16(v) }
+}
+
+impl SampleConvert for i32 {
+    const SILENCE: Self = 0;
+    #[inline] fn to_f32(self) -> f32 { i32_to_f32(self) }
+    #[inline] fn from_f32(v: f32) -> Self { f32_to_i32(v) }
+}
+
+/// Replace `dst` with `src` converted to f32. Does not allocate if `dst` has capacity.
+pub fn convert_slice_to_f32<T: SampleConvert>(src: &[T], dst: &mut Vec<f32>) {
+    dst.clear();
+    dst.extend(src.iter().map(|&s| s.to_f32()));
+}
+
+/// Convert `src` into `dst` element-wise; extra `dst` samples are left untouched.
+pub fn convert_slice_from_f32<T: SampleConvert>(src: &[f32], dst: &mut [T]) {
+    for (d, &s) in dst.iter_mut().zip(src) {
+        *d = T::from_f32(s);
+    }
+}
+
+/// Output-callback read: pop `out.len()` samples from `ring` through `scratch`
+/// and convert them into `out`, or write silence and return false if the ring
+/// is short. Does not allocate once `scratch` has `out.len()` capacity.
+pub fn pop_converted<T: SampleConvert>(ring: &SpscRingF32, scratch: &mut Vec<f32>, out: &mut [T]) -> bool {
+    scratch.clear();
+    scratch.resize(out.len(), 0.0);
+    if ring.pop_into(scratch) {
+        convert_slice_from_f32(scratch, out);
+        true
+    } else {
+        out.fill(T::SILENCE);
+        false
+    }
+}
@@ -0,0 +1,88 @@
+use super::gain_ramp::GainRamp;
+use crate::graph::Chain;
+use alloc::vec::Vec;
+
+/// Two chains fed the same input, with a linear crossfade between their
+/// outputs so switching presets doesn't cut. `crossfade_progress` is 0 for A
+/// and 1 for B; once a fade ends the silent chain is bypassed (it still runs,
+/// so events queued to it are applied, but its effects don't).
+pub struct CrossfadeChain {
+    pub chain_a: Chain,
+    pub chain_b: Chain,
+    active: usize, // 0 = A, 1 = B
+    crossfade_progress: GainRamp,
+    sample_rate: u32,
+    channels: usize,
+    scratch: Vec<f32>, // the other chain's block
+}
+
+impl CrossfadeChain {
+    /// Starts on A with B bypassed. `sample_rate` and `channels` are the
+    /// chains' input format.
+    pub fn new(chain_a: Chain, mut chain_b: Chain, sample_rate: u32, channels: u16) -> Self {
+        chain_b.set_bypass_all(true);
+        let channels = channels.max(1) as usize;
+        Self {
+            chain_a,
+            chain_b,
+            active: 0,
+            crossfade_progress: GainRamp::new(0.0),
+            sample_rate,
+            channels,
+            scratch: Vec::with_capacity(4096 * channels),
+        }
+    }
+
+    /// 0 for A, 1 for B: the chain faded to (or towards) last.
+    pub fn active(&self) -> usize {
+        self.active
+    }
+
+    pub fn is_fading(&self) -> bool {
+        !self.crossfade_progress.is_done()
+    }
+
+    /// Fade from A to B over `duration_ms`; 0 switches at the next block.
+    pub fn switch_to_b(&mut self, duration_ms: f32) {
+        self.chain_b.set_bypass_all(false);
+        self.fade_to(1, duration_ms);
+    }
+
+    /// Fade from B back to A over `duration_ms`.
+    pub fn switch_to_a(&mut self, duration_ms: f32) {
+        self.chain_a.set_bypass_all(false);
+        self.fade_to(0, duration_ms);
+    }
+
+    fn fade_to(&mut self, active: usize, duration_ms: f32) {
+        self.active = active;
+        let frames = (duration_ms.max(0.0) / 1000.0 * self.sample_rate as f32) as u32;
+        self.crossfade_progress.ramp_to(active as f32, frames);
+    }
+
+    /// Process one interleaved block in-place through both chains.
+    pub fn process(&mut self, block: &mut [f32]) {
+        self.scratch.clear();
+        self.scratch.extend_from_slice(block);
+        let (live, other) = match self.active {
+            0 => (&mut self.chain_a, &mut self.chain_b),
+            _ => (&mut self.chain_b, &mut self.chain_a),
+        };
+        live.process(block);
+        other.process(&mut self.scratch);
+        if !self.crossfade_progress.is_done() {
+            // `block` holds the chain faded towards, `scratch` the one faded from.
+            let (ch, towards_b) = (self.channels, self.active == 1);
+            for (frame, from) in block.chunks_exact_mut(ch).zip(self.scratch.chunks_exact(ch)) {
+                let p = self.crossfade_progress.next();
+                let t = if towards_b { p } else { 1.0 - p };
+                for (s, &f) in frame.iter_mut().zip(from) {
+                    *s = f + (*s - f) * t;
+                }
+            }
+        }
+        if self.crossfade_progress.is_done() {
+            other.set_bypass_all(true);
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use super::biquad::{Biquad, Coeffs};
+use super::effect::{Effect, ParamDescriptor};
+use super::utils::vec_bytes;
+
+const PARAMS: &[ParamDescriptor] = &[ParamDescriptor::new("strength", 0.5)];
+
+/// Knee of the crossfed low band.
+const KNEE_HZ: f32 = 700.0;
+/// Extra path length to the far ear.
+const DELAY_S: f32 = 0.000_35;
+/// Crossfeed level at `strength == 1.0` (about -6 dB).
+const MAX_LEVEL: f32 = 0.5;
+
+/// Bauer-style headphone crossfeed: each channel's lows, delayed ~0.35 ms,
+/// bleed into the other one, while a matching shelf on the direct path keeps
+/// the overall response flat. `strength` in [0, 1]; 0 is a passthrough.
+/// Stereo only; other channel counts pass through untouched. Meant to go last
+/// in the chain when monitoring on headphones.
+pub struct HeadphoneCrossfeed {
+    pub strength: f32,
+    stereo: bool,
+    low: Biquad,
+    delay: Vec<[f32; 2]>, // lowpassed L/R
+    pos: usize,
+}
+
+impl HeadphoneCrossfeed {
+    pub fn new(strength: f32) -> Self {
+        Self { strength: strength.clamp(0.0, 1.0), stereo: false, low: Biquad::new(), delay: Vec::new(), pos: 0 }
+    }
+}
+
+impl Effect for HeadphoneCrossfeed {
+    fn name(&self) -> &str {
+        "HeadphoneCrossfeed"
+    }
+
+    fn prepare(&mut self, sr: u32, channels: u16) {
+        self.stereo = channels == 2;
+        self.low.prepare(2);
+        self.low.coeffs = Coeffs::low_pass(KNEE_HZ, 0.5, sr);
+        let len = ((DELAY_S * sr as f32).round() as usize).max(1);
+        self.delay = vec![[0.0; 2]; len];
+        self.pos = 0;
+    }
+    fn set_param_db(&mut self, key: &str, value: f32) {
+        // Linear 0..1, despite the method name.
+        if key == "strength" {
+            self.strength = value.clamp(0.0, 1.0);
+        }
+    }
+    fn param_descriptors(&self) -> &[ParamDescriptor] {
+        PARAMS
+    }
+    fn process(&mut self, block: &mut [f32]) {
+        if !self.stereo || self.delay.is_empty() {
+            return;
+        }
+        let g = self.strength * MAX_LEVEL;
+        let norm = 1.0 / (1.0 + g);
+        for frame in block.chunks_exact_mut(2) {
+            let (l, r) = (frame[0], frame[1]);
+            let low = [self.low.tick(0, l), self.low.tick(1, r)];
+            let far = std::mem::replace(&mut self.delay[self.pos], low);
+            self.pos = (self.pos + 1) % self.delay.len();
+            // Direct: lows as-is, highs lifted by `g`; far: the other side's delayed lows.
+            frame[0] = (l + g * (l - low[0]) + g * far[1]) * norm;
+            frame[1] = (r + g * (r - low[1]) + g * far[0]) * norm;
+        }
+    }
+    fn clone_fresh(&self) -> Option<Box<dyn Effect>> {
+        Some(Box::new(HeadphoneCrossfeed::new(self.strength)))
+    }
+    fn memory_usage_bytes(&self) -> usize {
+        vec_bytes(&self.delay)
+    }
+}
@@ -0,0 +1,97 @@
+use super::biquad::{Biquad, Coeffs};
+use super::effect::{Effect, ParamDescriptor};
+use super::utils::vec_bytes;
+
+const PARAMS: &[ParamDescriptor] = &[ParamDescriptor::new("crossover_hz", 2000.0)];
+
+/// Butterworth Q; two cascaded sections make the Linkwitz-Riley 4th order slope.
+const BUTTERWORTH_Q: f32 = std::f32::consts::FRAC_1_SQRT_2;
+
+/// Linkwitz-Riley 4th-order two-way crossover for bi-amping. Doubles the
+/// channel count: each output frame is all low-band channels, then all
+/// high-band ones (stereo in -> L-low, R-low, L-high, R-high). The bands sum
+/// back to a flat (all-pass) response.
+pub struct CrossoverLR4 {
+    pub crossover_hz: f32,
+    sr: u32,
+    channels: usize,
+    low: [Biquad; 2],
+    high: [Biquad; 2],
+    input: Vec<f32>,
+}
+
+impl CrossoverLR4 {
+    pub fn new(crossover_hz: f32) -> Self {
+        Self {
+            crossover_hz,
+            sr: 48_000,
+            channels: 1,
+            low: [Biquad::new(), Biquad::new()],
+            high: [Biquad::new(), Biquad::new()],
+            input: Vec::new(),
+        }
+    }
+
+    pub fn set_crossover_hz(&mut self, f: f32) {
+        self.crossover_hz = f;
+        self.recompute();
+    }
+
+    fn recompute(&mut self) {
+        for b in &mut self.low {
+            b.coeffs = Coeffs::low_pass(self.crossover_hz, BUTTERWORTH_Q, self.sr);
+        }
+        for b in &mut self.high {
+            b.coeffs = Coeffs::high_pass(self.crossover_hz, BUTTERWORTH_Q, self.sr);
+        }
+    }
+}
+
+impl Effect for CrossoverLR4 {
+    fn name(&self) -> &str {
+        "CrossoverLR4"
+    }
+
+    fn prepare(&mut self, sr: u32, channels: u16) {
+        self.sr = sr;
+        self.channels = channels.max(1) as usize;
+        for b in self.low.iter_mut().chain(self.high.iter_mut()) {
+            b.prepare(channels);
+        }
+        self.input = Vec::with_capacity(4096 * self.channels);
+        self.recompute();
+    }
+    fn set_param_db(&mut self, key: &str, value: f32) {
+        // Hz, despite the method name.
+        if key == "crossover_hz" {
+            self.set_crossover_hz(value);
+        }
+    }
+    fn param_descriptors(&self) -> &[ParamDescriptor] {
+        PARAMS
+    }
+    fn output_channel_count(&self, input_channels: u16) -> u16 {
+        input_channels * 2
+    }
+    fn process(&mut self, block: &mut [f32]) {
+        let ch = self.channels;
+        let frames = block.len() / (2 * ch);
+        // The input occupies the front of `block` and is overwritten by the output.
+        self.input.clear();
+        self.input.extend_from_slice(&block[..frames * ch]);
+        for (inp, out) in self.input.chunks_exact(ch).zip(block.chunks_exact_mut(2 * ch)) {
+            for (c, &x) in inp.iter().enumerate() {
+                let [l1, l2] = &mut self.low;
+                let [h1, h2] = &mut self.high;
+                out[c] = l2.tick(c, l1.tick(c, x));
+                out[ch + c] = h2.tick(c, h1.tick(c, x));
+            }
+        }
+    }
+    fn clone_fresh(&self) -> Option<Box<dyn Effect>> {
+        Some(Box::new(CrossoverLR4::new(self.crossover_hz)))
+    }
+    fn memory_usage_bytes(&self) -> usize {
+        vec_bytes(&self.input)
+    }
+}
@@ -0,0 +1,75 @@
+use super::aligned_vec::AlignedVec;
+
+/// Interleaved multichannel delay with a fixed capacity and an adjustable
+/// delay up to it. Per frame: `read` each channel, `write` each channel, then
+/// `advance`.
+pub(crate) struct DelayLine {
+    buf: AlignedVec<f32>,
+    channels: usize,
+    frames: usize, // capacity
+    delay: usize,  // 1..=frames
+    pos: usize,
+}
+
+impl DelayLine {
+    pub(crate) fn new() -> Self {
+        Self { buf: AlignedVec::new(), channels: 0, frames: 0, delay: 1, pos: 0 }
+    }
+
+    /// Allocate and clear room for `delay` frames of `channels`.
+    pub(crate) fn prepare(&mut self, channels: u16, delay: usize) {
+        self.channels = channels as usize;
+        self.frames = delay.max(1);
+        self.delay = self.frames;
+        self.buf = AlignedVec::from_elem(0.0, self.frames * self.channels);
+        self.pos = 0;
+    }
+
+    pub(crate) fn memory_bytes(&self) -> usize {
+        self.buf.memory_bytes()
+    }
+
+    pub(crate) fn channels(&self) -> usize {
+        self.channels
+    }
+
+    /// Moves the read point; real-time safe while `delay <= capacity()`.
+    /// Beyond that it reallocates and clears the line.
+    pub(crate) fn set_delay(&mut self, delay: usize) {
+        let delay = delay.max(1);
+        if delay > self.frames {
+            self.prepare(self.channels as u16, delay);
+        } else {
+            self.delay = delay;
+        }
+    }
+
+    #[inline]
+    pub(crate) fn read(&self, ch: usize) -> f32 {
+        let frame = (self.pos + self.frames - self.delay) % self.frames;
+        self.buf[frame * self.channels + ch]
+    }
+
+    /// Channel `ch` as written `delay` frames ago, for extra taps;
+    /// `delay` in 1..=capacity.
+    #[inline]
+    pub(crate) fn read_at(&self, ch: usize, delay: usize) -> f32 {
+        let frame = (self.pos + self.frames - delay.clamp(1, self.frames)) % self.frames;
+        self.buf[frame * self.channels + ch]
+    }
+
+    #[inline]
+    pub(crate) fn write(&mut self, ch: usize, v: f32) {
+        self.buf[self.pos * self.channels + ch] = v;
+    }
+
+    #[inline]
+    pub(crate) fn advance(&mut self) {
+        self.pos = (self.pos + 1) % self.frames;
+    }
+
+    /// True until prepared (or when prepared for zero channels).
+    pub(crate) fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+}
@@ -0,0 +1,205 @@
+use rustfft::num_complex::Complex;
+use rustfft::{Fft, FftPlanner};
+use std::sync::Arc;
+
+use super::effect::Effect;
+use super::utils::vec_bytes;
+
+/// STFT frame length.
+const FFT_SIZE: usize = 1024;
+/// 75% overlap.
+const HOP: usize = FFT_SIZE / 4;
+/// How long `calibrate` listens to the noise.
+const CALIBRATION_S: f32 = 0.5;
+/// Lowest gain a bin is left with, so subtraction doesn't leave holes that
+/// ring as "musical noise" (-26 dB).
+const SPECTRAL_FLOOR: f32 = 0.05;
+
+/// Spectral subtraction. Each channel is analysed in Hann-windowed 1024-point
+/// frames every 256 samples; every bin's magnitude is reduced by
+/// `reduction_strength` times the noise estimate for that bin, keeping the
+/// original phase, and the frames are overlap-added back.
+///
+/// The noise estimate is a flat spectrum at `noise_floor_estimate_db` (dBFS
+/// RMS, as white noise) until a calibration has run: with `calibrate` set,
+/// the first 500 ms after `prepare` are taken as noise only (and passed
+/// through untouched); `calibrate()` listens again.
+pub struct Denoise {
+    pub noise_floor_estimate_db: f32,
+    /// Over-subtraction factor: 1 removes the estimated noise, more is harsher.
+    pub reduction_strength: f32,
+    /// Learn the noise from the first 500 ms after `prepare`.
+    pub calibrate: bool,
+    fft: Option<Arc<dyn Fft<f32>>>,
+    ifft: Option<Arc<dyn Fft<f32>>>,
+    window: Vec<f32>,
+    channels: Vec<ChannelState>,
+    spectrum: Vec<Complex<f32>>,
+    scratch: Vec<Complex<f32>>,
+    pos: usize,                // samples into the current hop
+    calibration_frames: usize, // frames a calibration averages over
+    calibrating: usize,        // frames of calibration left
+    learned: bool,             // the estimate came from a calibration
+}
+
+struct ChannelState {
+    input: Vec<f32>,  // last FFT_SIZE input samples
+    output: Vec<f32>, // overlap-add accumulator
+    noise: Vec<f32>,  // magnitude per bin
+}
+
+impl Denoise {
+    pub fn new(noise_floor_estimate_db: f32, reduction_strength: f32) -> Self {
+        Self {
+            noise_floor_estimate_db,
+            reduction_strength,
+            calibrate: false,
+            fft: None,
+            ifft: None,
+            window: Vec::new(),
+            channels: Vec::new(),
+            spectrum: Vec::new(),
+            scratch: Vec::new(),
+            pos: 0,
+            calibration_frames: 1,
+            calibrating: 0,
+            learned: false,
+        }
+    }
+
+    /// Take the next 500 ms as noise only and re-estimate from it.
+    pub fn calibrate(&mut self) {
+        self.calibrating = self.calibration_frames;
+        self.learned = true;
+        for ch in &mut self.channels {
+            ch.noise.fill(0.0);
+        }
+    }
+
+    pub fn is_calibrating(&self) -> bool {
+        self.calibrating > 0
+    }
+
+    /// Flat noise magnitude per bin for white noise at `noise_floor_estimate_db`
+    /// through the analysis window.
+    fn flat_noise(&self) -> f32 {
+        let energy: f32 = self.window.iter().map(|w| w * w).sum();
+        10f32.powf(self.noise_floor_estimate_db / 20.0) * energy.sqrt()
+    }
+
+    /// Analyse, subtract and resynthesise one frame of channel `c`.
+    fn process_frame(&mut self, c: usize) {
+        let (Some(fft), Some(ifft)) = (&self.fft, &self.ifft) else { return };
+        let ch = &mut self.channels[c];
+        for ((bin, &x), &w) in self.spectrum.iter_mut().zip(&ch.input).zip(&self.window) {
+            *bin = Complex::new(x * w, 0.0);
+        }
+        fft.process_with_scratch(&mut self.spectrum, &mut self.scratch);
+
+        if self.calibrating > 0 {
+            let scale = 1.0 / self.calibration_frames as f32;
+            for (n, bin) in ch.noise.iter_mut().zip(&self.spectrum) {
+                *n += bin.norm() * scale;
+            }
+        } else {
+            for (bin, &n) in self.spectrum.iter_mut().zip(&ch.noise) {
+                let mag = bin.norm();
+                if mag > 0.0 {
+                    let gain = ((mag - self.reduction_strength * n) / mag).max(SPECTRAL_FLOOR);
+                    *bin *= gain;
+                }
+            }
+        }
+
+        ifft.process_with_scratch(&mut self.spectrum, &mut self.scratch);
+        // Hann squared sums to 1.5 at 75% overlap; the inverse FFT is unnormalised.
+        let norm = 1.0 / (1.5 * FFT_SIZE as f32);
+        ch.output.copy_within(HOP.., 0);
+        ch.output[FFT_SIZE - HOP..].fill(0.0);
+        for ((o, bin), &w) in ch.output.iter_mut().zip(&self.spectrum).zip(&self.window) {
+            *o += bin.re * w * norm;
+        }
+        ch.input.copy_within(HOP.., 0);
+    }
+}
+
+impl Effect for Denoise {
+    fn name(&self) -> &str {
+        "Denoise"
+    }
+
+    fn prepare(&mut self, sr: u32, channels: u16) {
+        let mut planner = FftPlanner::new();
+        let fft = planner.plan_fft_forward(FFT_SIZE);
+        let ifft = planner.plan_fft_inverse(FFT_SIZE);
+        let scratch = fft.get_inplace_scratch_len().max(ifft.get_inplace_scratch_len());
+        self.scratch = vec![Complex::default(); scratch];
+        self.fft = Some(fft);
+        self.ifft = Some(ifft);
+        self.spectrum = vec![Complex::default(); FFT_SIZE];
+        // Periodic Hann, which overlap-adds to a constant.
+        self.window = (0..FFT_SIZE)
+            .map(|i| 0.5 - 0.5 * (std::f32::consts::TAU * i as f32 / FFT_SIZE as f32).cos())
+            .collect();
+        let noise = self.flat_noise();
+        self.channels = (0..channels.max(1))
+            .map(|_| ChannelState { input: vec![0.0; FFT_SIZE], output: vec![0.0; FFT_SIZE], noise: vec![noise; FFT_SIZE] })
+            .collect();
+        self.pos = 0;
+        self.calibration_frames = ((CALIBRATION_S * sr as f32) as usize / HOP).max(1);
+        self.calibrating = 0;
+        self.learned = false;
+        if self.calibrate {
+            self.calibrate();
+        }
+    }
+    fn set_param_db(&mut self, key: &str, value: f32) {
+        match key {
+            "reduction_strength" => self.reduction_strength = value,
+            // Only replaces the estimate if no calibration has run since `prepare`.
+            "noise_floor_db" => {
+                self.noise_floor_estimate_db = value;
+                if !self.learned {
+                    let noise = self.flat_noise();
+                    for ch in &mut self.channels {
+                        ch.noise.fill(noise);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    fn process(&mut self, block: &mut [f32]) {
+        let n = self.channels.len();
+        if self.fft.is_none() || n == 0 {
+            return;
+        }
+        for frame in block.chunks_exact_mut(n) {
+            for (x, ch) in frame.iter_mut().zip(&mut self.channels) {
+                ch.input[FFT_SIZE - HOP + self.pos] = *x;
+                *x = ch.output[self.pos];
+            }
+            self.pos += 1;
+            if self.pos == HOP {
+                self.pos = 0;
+                for c in 0..n {
+                    self.process_frame(c);
+                }
+                self.calibrating = self.calibrating.saturating_sub(1);
+            }
+        }
+    }
+    fn latency_samples(&self) -> u32 {
+        FFT_SIZE as u32
+    }
+    fn memory_usage_bytes(&self) -> usize {
+        let channels: usize =
+            self.channels.iter().map(|ch| vec_bytes(&ch.input) + vec_bytes(&ch.output) + vec_bytes(&ch.noise)).sum();
+        channels + vec_bytes(&self.window) + vec_bytes(&self.spectrum) + vec_bytes(&self.scratch)
+    }
+    fn clone_fresh(&self) -> Option<Box<dyn Effect>> {
+        let mut fx = Denoise::new(self.noise_floor_estimate_db, self.reduction_strength);
+        fx.calibrate = self.calibrate;
+        Some(Box::new(fx))
+    }
+}
@@ -1,9 +1,282 @@
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// `#[derive(EffectParams)]`, with the trait of the same name below.
+#[cfg(feature = "derive")]
+pub use bord_macros::EffectParams;
+
+/// One frame of `N` channels. A `[Frame<N>]` has the same layout as the
+/// interleaved block, so it converts both ways without copying.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(transparent)]
+pub struct Frame<const N: usize>(pub [f32; N]);
+
+impl<const N: usize> Default for Frame<N> {
+    fn default() -> Self {
+        Self([0.0; N])
+    }
+}
+
+impl<const N: usize> Frame<N> {
+    /// The frames as one interleaved block.
+    pub fn as_interleaved_mut(frames: &mut [Frame<N>]) -> &mut [f32] {
+        // Safety: `Frame<N>` is `repr(transparent)` over `[f32; N]`, so the
+        // frames are `len * N` contiguous, aligned f32s.
+        unsafe { core::slice::from_raw_parts_mut(frames.as_mut_ptr().cast::<f32>(), frames.len() * N) }
+    }
+
+    /// An interleaved block as frames; a trailing partial frame is left out.
+    pub fn from_interleaved_mut(block: &mut [f32]) -> &mut [Frame<N>] {
+        // Safety: as above, in reverse; the length is rounded down to whole frames.
+        unsafe { core::slice::from_raw_parts_mut(block.as_mut_ptr().cast::<Frame<N>>(), block.len() / N.max(1)) }
+    }
+}
+
+/// A `set_param_db` key and the value a default-configured effect has for it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParamDescriptor {
+    pub key: &'static str,
+    pub default_value: f32,
+}
+
+impl ParamDescriptor {
+    pub const fn new(key: &'static str, default_value: f32) -> Self {
+        Self { key, default_value }
+    }
+}
+
+/// What a parameter's value means, for display and host automation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParamUnit {
+    #[default]
+    Linear,
+    Decibels,
+    Hertz,
+    Milliseconds,
+    Percent,
+}
+
+/// A `ParamDescriptor` with the numeric id, range and unit `EffectParams` uses.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParamInfo {
+    pub id: u32,
+    pub key: &'static str,
+    pub min: f32,
+    pub max: f32,
+    pub default_value: f32,
+    pub unit: ParamUnit,
+}
+
+/// Parameters kept in an effect's own fields, addressed by numeric id.
+/// Usually derived (`derive` feature); an `Effect` impl then picks up
+/// `param_descriptors` and `set_param_db` with `effect_params_methods!()`.
+pub trait EffectParams {
+    const PARAMS: &'static [ParamDescriptor];
+    const PARAM_INFO: &'static [ParamInfo];
+
+    /// Set parameter `id`, clamped to its range; false if there is no such id.
+    fn set_param(&mut self, id: u32, value: f32) -> bool;
+    fn get_param(&self, id: u32) -> Option<f32>;
+
+    /// `set_param` by key, as `Effect::set_param_db` takes it.
+    fn set_param_by_key(&mut self, key: &str, value: f32) -> bool {
+        match Self::PARAM_INFO.iter().find(|p| p.key == key) {
+            Some(p) => self.set_param(p.id, value),
+            None => false,
+        }
+    }
+
+    /// Every parameter's current value by key.
+    fn save_state(&self) -> BTreeMap<String, f32> {
+        Self::PARAM_INFO.iter().filter_map(|p| Some((String::from(p.key), self.get_param(p.id)?))).collect()
+    }
+
+    /// Restore a `save_state` map; unknown keys are ignored and missing ones left as they are.
+    fn load_state(&mut self, state: &BTreeMap<String, f32>) {
+        for p in Self::PARAM_INFO {
+            if let Some(&value) = state.get(p.key) {
+                self.set_param(p.id, value);
+            }
+        }
+    }
+}
+
+/// `Effect::param_descriptors` and `set_param_db` for an `EffectParams` type,
+/// for use inside its `impl Effect` block.
+#[macro_export]
+macro_rules! effect_params_methods {
+    () => {
+        fn param_descriptors(&self) -> &[$crate::dsp::effect::ParamDescriptor] {
+            <Self as $crate::dsp::effect::EffectParams>::PARAMS
+        }
+        fn set_param_db(&mut self, key: &str, value: f32) {
+            <Self as $crate::dsp::effect::EffectParams>::set_param_by_key(self, key, value);
+        }
+    };
+}
+
+/// Where a block sits in the stream, for effects that follow a timeline
+/// (automation, tempo sync) rather than count samples themselves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProcessContext {
+    /// Frames processed before this block.
+    pub sample_pos: u64,
+    pub sample_rate: u32,
+    /// True on the audio callback, false when rendering offline.
+    pub is_realtime: bool,
+}
+
 /// Real-time safe effect interface.
 /// - process() must not allocate or lock on the hot path.
 /// - `block` is interleaved f32 samples in [-1, 1].
 pub trait Effect: Send {
+    /// Short display name, e.g. `"Gain"`.
+    fn name(&self) -> &str;
+    fn version(&self) -> (u32, u32, u32) { (0, 1, 0) }
     fn prepare(&mut self, _sr: u32, _channels: u16) {}
+    /// Recompute coefficients only, keeping buffers and state. Returns `false`
+    /// if (`sr`, `channels`) differ from the last `prepare()` or the effect
+    /// can't do this, in which case a full `prepare()` is needed.
+    fn prepare_hot(&mut self, _sr: u32, _channels: u16) -> bool { false }
+    /// Set the parameter `key`; unknown keys are ignored. Despite the name,
+    /// only gain-like keys take decibels: others take the value in their own
+    /// unit, e.g. a linear feedback amount, a rate in Hz or a sample count.
     fn set_param_db(&mut self, _key: &str, _db: f32) {}
+    /// The parameters `set_param_db` takes, for resetting and listing them.
+    /// Keys built from an index (a tap or band number) aren't listed.
+    fn param_descriptors(&self) -> &[ParamDescriptor] { &[] }
+    /// Set `key` back to its `param_descriptors` default; unknown keys are ignored.
+    fn reset_param_to_default(&mut self, key: &str) {
+        if let Some(d) = self.param_descriptors().iter().find(|d| d.key == key) {
+            let value = d.default_value;
+            self.set_param_db(key, value);
+        }
+    }
     fn process(&mut self, block: &mut [f32]);
+    /// `process` with the block's position; `Chain` calls this for in-place
+    /// effects on its interleaved path. Effects that use the context also
+    /// override the `_with_context` variants of `process_stereo` and
+    /// `process_frames`, which otherwise drop it.
+    fn process_with_context(&mut self, block: &mut [f32], _ctx: &ProcessContext) { self.process(block) }
+    /// False for effects that read samples they have already overwritten and
+    /// so need the input kept apart from the output; the chain then calls
+    /// `process_into` with a copy of the block instead of `process`.
+    fn in_place_safe(&self) -> bool { true }
+    /// Process `input` into `output`, which is at least `input.len()` long (it
+    /// is the block, sized for `output_channel_count`). The default copies and
+    /// runs `process()`; effects that aren't `in_place_safe` override it.
+    fn process_into(&mut self, input: &[f32], output: &mut [f32]) {
+        output[..input.len()].copy_from_slice(input);
+        self.process(output);
+    }
+
+    /// Planar stereo path. The default interleaves through a stack buffer and
+    /// calls `process()`; override it (and `supports_stereo_split`) to avoid that.
+    fn process_stereo(&mut self, left: &mut [f32], right: &mut [f32]) {
+        const FRAMES: usize = 256;
+        let mut buf = [0.0f32; FRAMES * 2];
+        for (l, r) in left.chunks_mut(FRAMES).zip(right.chunks_mut(FRAMES)) {
+            let n = l.len().min(r.len());
+            for i in 0..n {
+                buf[2 * i] = l[i];
+                buf[2 * i + 1] = r[i];
+            }
+            self.process(&mut buf[..2 * n]);
+            for i in 0..n {
+                l[i] = buf[2 * i];
+                r[i] = buf[2 * i + 1];
+            }
+        }
+    }
+    /// Stereo frames, for effects that want `frame.0[0]`/`frame.0[1]` instead
+    /// of striding; `Chain::process_frames_stereo` calls it. The default runs
+    /// `process()` on the same memory viewed as an interleaved block.
+    fn process_frames(&mut self, frames: &mut [Frame<2>]) {
+        self.process(Frame::as_interleaved_mut(frames));
+    }
+    /// `process_stereo` with the block's position, for the chain's split path.
+    fn process_stereo_with_context(&mut self, left: &mut [f32], right: &mut [f32], _ctx: &ProcessContext) {
+        self.process_stereo(left, right)
+    }
+    /// `process_frames` with the block's position, for `Chain::process_frames_stereo`.
+    fn process_frames_with_context(&mut self, frames: &mut [Frame<2>], _ctx: &ProcessContext) { self.process_frames(frames) }
+
+    /// True if `process_stereo` is a native override, not the default shim.
+    fn supports_stereo_split(&self) -> bool { false }
+
+    /// Channels out for `input_channels` in. An effect that changes the count
+    /// gets a block sized for its output, with the input interleaved in the
+    /// first `frames * input_channels` samples.
+    fn output_channel_count(&self, input_channels: u16) -> u16 { input_channels }
+
+    /// Delay this effect adds to the signal, in frames.
+    fn latency_samples(&self) -> u32 { 0 }
+
+    /// Heap the effect holds (delay lines, FFT buffers, ...), in bytes, as
+    /// allocated by the last `prepare()`; approximate.
+    fn memory_usage_bytes(&self) -> usize { 0 }
+
+    /// Frames of output (reverb, echoes) still to come after the input goes
+    /// silent. A running chain keeps a removed effect ringing this long.
+    fn tail_samples(&self) -> u32 { 0 }
+
+    /// Inclusive `(min, max)` frames per block this effect handles, e.g. an FFT
+    /// effect that needs a whole frame per call; `None` means any size.
+    fn supported_block_sizes(&self) -> Option<(u32, u32)> { None }
+
+    /// Serial effect lists run side by side, for effects that contain others
+    /// (see `Parallel`); used to draw the chain. Empty for plain effects.
+    fn branches(&self) -> &[Vec<Box<dyn Effect>>] { &[] }
+
+    /// A new, unprepared instance with the same settings and none of this one's
+    /// state, for `Chain::clone_for_parallel`. `None` for effects that can't be
+    /// duplicated (they own a file or device, or are controlled through a
+    /// shared handle).
+    fn clone_fresh(&self) -> Option<Box<dyn Effect>> { None }
+
+    /// Effects with slow setup return themselves here so `Chain::prepare_all_async`
+    /// can await them; `None` means plain `prepare()` is used.
+    #[cfg(feature = "async")]
+    fn as_async(&mut self) -> Option<&mut dyn AsyncEffect> { None }
+}
+
+/// Non-realtime, possibly slow preparation (plugin load, IR loading, ...).
+/// Runs instead of `Effect::prepare()` when driven by `Chain::prepare_all_async`.
+#[cfg(feature = "async")]
+pub trait AsyncEffect: Send {
+    fn prepare_async(&mut self, sr: u32, channels: u16) -> futures::future::BoxFuture<'_, anyhow::Result<()>>;
 }
 
+impl<E: Effect + ?Sized> Effect for Box<E> {
+    fn name(&self) -> &str { (**self).name() }
+    fn version(&self) -> (u32, u32, u32) { (**self).version() }
+    fn prepare(&mut self, sr: u32, channels: u16) { (**self).prepare(sr, channels) }
+    fn prepare_hot(&mut self, sr: u32, channels: u16) -> bool { (**self).prepare_hot(sr, channels) }
+    fn set_param_db(&mut self, key: &str, db: f32) { (**self).set_param_db(key, db) }
+    fn param_descriptors(&self) -> &[ParamDescriptor] { (**self).param_descriptors() }
+    fn reset_param_to_default(&mut self, key: &str) { (**self).reset_param_to_default(key) }
+    fn process(&mut self, block: &mut [f32]) { (**self).process(block) }
+    fn process_with_context(&mut self, block: &mut [f32], ctx: &ProcessContext) { (**self).process_with_context(block, ctx) }
+    fn in_place_safe(&self) -> bool { (**self).in_place_safe() }
+    fn process_into(&mut self, input: &[f32], output: &mut [f32]) { (**self).process_into(input, output) }
+    fn process_stereo(&mut self, left: &mut [f32], right: &mut [f32]) { (**self).process_stereo(left, right) }
+    fn process_frames(&mut self, frames: &mut [Frame<2>]) { (**self).process_frames(frames) }
+    fn process_stereo_with_context(&mut self, left: &mut [f32], right: &mut [f32], ctx: &ProcessContext) {
+        (**self).process_stereo_with_context(left, right, ctx)
+    }
+    fn process_frames_with_context(&mut self, frames: &mut [Frame<2>], ctx: &ProcessContext) {
+        (**self).process_frames_with_context(frames, ctx)
+    }
+    fn supports_stereo_split(&self) -> bool { (**self).supports_stereo_split() }
+    fn output_channel_count(&self, input_channels: u16) -> u16 { (**self).output_channel_count(input_channels) }
+    fn latency_samples(&self) -> u32 { (**self).latency_samples() }
+    fn memory_usage_bytes(&self) -> usize { (**self).memory_usage_bytes() }
+    fn tail_samples(&self) -> u32 { (**self).tail_samples() }
+    fn supported_block_sizes(&self) -> Option<(u32, u32)> { (**self).supported_block_sizes() }
+    fn branches(&self) -> &[Vec<Box<dyn Effect>>] { (**self).branches() }
+    fn clone_fresh(&self) -> Option<Box<dyn Effect>> { (**self).clone_fresh() }
+    #[cfg(feature = "async")]
+    fn as_async(&mut self) -> Option<&mut dyn AsyncEffect> { (**self).as_async() }
+}
@@ -0,0 +1,116 @@
+use super::effect::Effect;
+use super::utils::vec_bytes;
+use anyhow::{anyhow, Context, Result};
+use std::path::{Path, PathBuf};
+
+/// Source node that overwrites each block with frames read from a WAV file.
+/// Push it first in a `Chain` to run effects offline without hardware.
+/// The file is decoded up front in `prepare()` so `process()` never touches disk.
+pub struct AudioFileSource {
+    path: PathBuf,
+    pub loop_mode: bool,
+    samples: Vec<f32>, // interleaved, file channel layout
+    file_channels: usize,
+    channels: usize,
+    frame: u64,
+    error: Option<anyhow::Error>,
+}
+
+impl AudioFileSource {
+    pub fn new(path: impl Into<PathBuf>, loop_mode: bool) -> Self {
+        Self {
+            path: path.into(),
+            loop_mode,
+            samples: Vec::new(),
+            file_channels: 1,
+            channels: 1,
+            frame: 0,
+            error: None,
+        }
+    }
+
+    /// Reposition the read pointer to `sample` (frame index in the file).
+    pub fn seek(&mut self, sample: u64) {
+        self.frame = sample.min(self.total_frames());
+    }
+
+    /// True once the file is exhausted and looping is off.
+    pub fn is_finished(&self) -> bool {
+        !self.loop_mode && self.frame >= self.total_frames()
+    }
+
+    /// Error recorded by the last `prepare()`, if the file could not be used.
+    pub fn error(&self) -> Option<&anyhow::Error> {
+        self.error.as_ref()
+    }
+
+    fn total_frames(&self) -> u64 {
+        (self.samples.len() / self.file_channels) as u64
+    }
+
+    fn load(&mut self, sr: u32) -> Result<()> {
+        let (samples, spec) = read_wav(&self.path)?;
+        if spec.sample_rate != sr {
+            return Err(anyhow!(
+                "{}: sample rate {} does not match engine rate {sr}",
+                self.path.display(),
+                spec.sample_rate
+            ));
+        }
+        self.samples = samples;
+        self.file_channels = spec.channels.max(1) as usize;
+        Ok(())
+    }
+}
+
+/// Decode a whole WAV file to interleaved f32, integer formats scaled to [-1, 1).
+pub(crate) fn read_wav(path: &Path) -> Result<(Vec<f32>, hound::WavSpec)> {
+    let mut reader = hound::WavReader::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let spec = reader.spec();
+    let samples = match spec.sample_format {
+        hound::SampleFormat::Float => reader.samples::<f32>().collect::<Result<_, _>>()?,
+        hound::SampleFormat::Int => {
+            let scale = 1.0 / (1u64 << (spec.bits_per_sample - 1)) as f32;
+            reader.samples::<i32>().map(|s| s.map(|v| v as f32 * scale)).collect::<Result<_, _>>()?
+        }
+    };
+    Ok((samples, spec))
+}
+
+impl Effect for AudioFileSource {
+    fn name(&self) -> &str {
+        "AudioFileSource"
+    }
+
+    fn prepare(&mut self, sr: u32, channels: u16) {
+        self.channels = channels.max(1) as usize;
+        self.frame = 0;
+        self.error = self.load(sr).err();
+        if self.error.is_some() {
+            self.samples.clear();
+        }
+    }
+
+    fn process(&mut self, block: &mut [f32]) {
+        let total = self.total_frames();
+        for out in block.chunks_mut(self.channels) {
+            if self.frame >= total {
+                if self.loop_mode && total > 0 {
+                    self.frame = 0;
+                } else {
+                    out.fill(0.0);
+                    continue;
+                }
+            }
+            // Map output channels onto file channels (mono files fan out to all).
+            let base = self.frame as usize * self.file_channels;
+            for (c, s) in out.iter_mut().enumerate() {
+                *s = self.samples[base + c % self.file_channels];
+            }
+            self.frame += 1;
+        }
+    }
+    fn memory_usage_bytes(&self) -> usize {
+        vec_bytes(&self.samples)
+    }
+}
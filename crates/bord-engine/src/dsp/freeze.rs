@@ -0,0 +1,124 @@
+use super::effect::Effect;
+use super::smoothed::SmoothedParam;
+use super::utils::vec_bytes;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Crossfade between live and frozen audio.
+const FADE_S: f32 = 0.05;
+
+/// Locks the audio at a moment in time: the last `window` frames of input are
+/// kept, and while frozen that window loops in place of the input. Freezing
+/// and unfreezing crossfade over 50 ms. The input keeps being recorded while
+/// frozen, so freezing again captures what came in meanwhile.
+///
+/// `set_freeze` only flips an atomic flag, so any thread may call it through
+/// `freeze_flag()`; the audio thread picks the change up at the next block.
+pub struct Freeze {
+    freeze: Arc<AtomicBool>,
+    window: usize, // frames
+    channels: usize,
+    frozen: bool, // as last seen by `process`
+    blend: SmoothedParam, // 0 live .. 1 frozen
+    history: Vec<f32>,
+    history_pos: usize, // frame
+    looped: Vec<f32>,
+    loop_pos: usize, // frame
+}
+
+impl Freeze {
+    pub fn new(window: usize) -> Self {
+        Self {
+            freeze: Arc::new(AtomicBool::new(false)),
+            window: window.max(1),
+            channels: 0,
+            frozen: false,
+            blend: SmoothedParam::new(0.0),
+            history: Vec::new(),
+            history_pos: 0,
+            looped: Vec::new(),
+            loop_pos: 0,
+        }
+    }
+
+    pub fn set_freeze(&self, on: bool) {
+        self.freeze.store(on, Ordering::Relaxed);
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        self.freeze.load(Ordering::Relaxed)
+    }
+
+    /// Handle on the freeze state: storing true crossfades into a loop of the
+    /// last `window` frames at the next block, false back to the live input.
+    pub fn freeze_flag(&self) -> Arc<AtomicBool> {
+        self.freeze.clone()
+    }
+
+    /// Copy the history, oldest frame first, into the loop buffer.
+    fn capture(&mut self) {
+        let split = self.history_pos * self.channels;
+        let (newer, older) = self.history.split_at(split);
+        self.looped[..older.len()].copy_from_slice(older);
+        self.looped[older.len()..].copy_from_slice(newer);
+        self.loop_pos = 0;
+    }
+}
+
+impl Effect for Freeze {
+    fn name(&self) -> &str {
+        "Freeze"
+    }
+
+    fn prepare(&mut self, sr: u32, channels: u16) {
+        self.channels = channels as usize;
+        self.history = vec![0.0; self.window * self.channels];
+        self.looped = vec![0.0; self.window * self.channels];
+        self.history_pos = 0;
+        self.loop_pos = 0;
+        self.frozen = false;
+        self.blend = SmoothedParam::new(0.0);
+        self.blend.prepare(sr, FADE_S);
+    }
+    fn set_param_db(&mut self, key: &str, value: f32) {
+        // 0 or 1, despite the method name.
+        if key == "freeze" {
+            self.set_freeze(value >= 0.5);
+        }
+    }
+    fn process(&mut self, block: &mut [f32]) {
+        let ch = self.channels;
+        if ch == 0 {
+            return;
+        }
+        let want = self.is_frozen();
+        if want != self.frozen {
+            if want {
+                self.capture();
+            }
+            self.blend.set_target(if want { 1.0 } else { 0.0 });
+            self.frozen = want;
+        }
+        if !self.frozen && self.blend.is_settled() {
+            // Live: just keep the history current.
+            for frame in block.chunks_exact(ch) {
+                self.history[self.history_pos * ch..][..ch].copy_from_slice(frame);
+                self.history_pos = (self.history_pos + 1) % self.window;
+            }
+            return;
+        }
+        for frame in block.chunks_exact_mut(ch) {
+            self.history[self.history_pos * ch..][..ch].copy_from_slice(frame);
+            self.history_pos = (self.history_pos + 1) % self.window;
+            let b = self.blend.tick();
+            let looped = &self.looped[self.loop_pos * ch..][..ch];
+            for (x, &l) in frame.iter_mut().zip(looped) {
+                *x = *x * (1.0 - b) + l * b;
+            }
+            self.loop_pos = (self.loop_pos + 1) % self.window;
+        }
+    }
+    fn memory_usage_bytes(&self) -> usize {
+        vec_bytes(&self.history) + vec_bytes(&self.looped)
+    }
+}
@@ -1,4 +1,9 @@
-use super::effect::Effect;
+use super::effect::{Effect, ParamDescriptor};
+use alloc::boxed::Box;
+#[cfg(not(feature = "std"))]
+use super::math::FloatExt;
+
+const PARAMS: &[ParamDescriptor] = &[ParamDescriptor::new("db", 0.0)];
 
 /// Linear gain with dB control.
 pub struct Gain {
@@ -18,18 +23,35 @@ impl Gain {
 }
 
 impl Effect for Gain {
+    fn name(&self) -> &str {
+        "Gain"
+    }
+
     fn set_param_db(&mut self, key: &str, db: f32) {
-        if key == "db" {
+        // `gain_db` matches the config file's spelling.
+        if key == "db" || key == "gain_db" {
             self.db = db;
             self.recompute();
         }
     }
+    fn param_descriptors(&self) -> &[ParamDescriptor] {
+        PARAMS
+    }
     fn process(&mut self, block: &mut [f32]) {
         let g = self.lin;
         for s in block.iter_mut() {
-            let x = *s * g;
-            *s = if x > 1.0 { 1.0 } else if x < -1.0 { -1.0 } else { x };
+            *s = (*s * g).clamp(-1.0, 1.0);
         }
     }
+    fn process_stereo(&mut self, left: &mut [f32], right: &mut [f32]) {
+        self.process(left);
+        self.process(right);
+    }
+    fn supports_stereo_split(&self) -> bool {
+        true
+    }
+    fn clone_fresh(&self) -> Option<Box<dyn Effect>> {
+        Some(Box::new(Gain::new(self.db)))
+    }
 }
 
@@ -0,0 +1,69 @@
+use super::effect::{Effect, Frame, ProcessContext};
+
+/// A point on a `GainAutomation` envelope.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AutomationPoint {
+    /// Stream position, in frames.
+    pub frame: u64,
+    pub gain_db: f32,
+}
+
+/// Gain following an envelope on the stream's timeline: linear (in gain)
+/// between points, holding the first and last before and after them. The
+/// position comes from `ProcessContext::sample_pos`, so a chain that seeks
+/// (`Chain::set_sample_pos`) or restarts lands on the matching gain; called
+/// through plain `process` it counts frames from where it last was.
+pub struct GainAutomation {
+    points: Vec<(u64, f32)>, // frame, linear gain; sorted by frame
+    pos: u64,
+    channels: usize,
+}
+
+impl GainAutomation {
+    pub fn new(mut points: Vec<AutomationPoint>) -> Self {
+        points.sort_by_key(|p| p.frame);
+        let points = points.into_iter().map(|p| (p.frame, 10f32.powf(p.gain_db / 20.0))).collect();
+        Self { points, pos: 0, channels: 1 }
+    }
+
+    /// Linear gain at `frame`; unity with no points.
+    pub fn gain_at(&self, frame: u64) -> f32 {
+        let next = self.points.partition_point(|&(f, _)| f <= frame);
+        match (next.checked_sub(1).map(|i| self.points[i]), self.points.get(next)) {
+            (Some((f0, g0)), Some(&(f1, g1))) => g0 + (g1 - g0) * ((frame - f0) as f64 / (f1 - f0) as f64) as f32,
+            (Some((_, g)), None) | (None, Some(&(_, g))) => g,
+            (None, None) => 1.0,
+        }
+    }
+}
+
+impl Effect for GainAutomation {
+    fn name(&self) -> &str {
+        "GainAutomation"
+    }
+
+    fn prepare(&mut self, _sr: u32, channels: u16) {
+        self.channels = channels.max(1) as usize;
+        self.pos = 0;
+    }
+    fn process(&mut self, block: &mut [f32]) {
+        for frame in block.chunks_exact_mut(self.channels) {
+            let g = self.gain_at(self.pos);
+            frame.iter_mut().for_each(|s| *s *= g);
+            self.pos += 1;
+        }
+    }
+    fn process_with_context(&mut self, block: &mut [f32], ctx: &ProcessContext) {
+        self.pos = ctx.sample_pos;
+        self.process(block);
+    }
+    fn process_frames_with_context(&mut self, frames: &mut [Frame<2>], ctx: &ProcessContext) {
+        self.process_with_context(Frame::as_interleaved_mut(frames), ctx);
+    }
+    fn clone_fresh(&self) -> Option<Box<dyn Effect>> {
+        Some(Box::new(GainAutomation { points: self.points.clone(), pos: 0, channels: self.channels }))
+    }
+    fn memory_usage_bytes(&self) -> usize {
+        super::utils::vec_bytes(&self.points)
+    }
+}
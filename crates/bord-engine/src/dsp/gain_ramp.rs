@@ -0,0 +1,102 @@
+//! Sample-accurate gain ramps, for automation that has to land on a given
+//! sample rather than settle "about" 20 ms later.
+
+#[cfg(not(feature = "std"))]
+use super::math::FloatExt;
+
+/// How a `GainRamp` moves from `start` to `end`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RampShape {
+    #[default]
+    Linear,
+    /// Constant ratio per sample, i.e. linear in dB. Falls back to linear when
+    /// either end is 0 or the two differ in sign.
+    Exponential,
+}
+
+/// A gain that goes from `start` to `end` in exactly `duration_samples` calls
+/// to `next()`, then holds `end`. Each value is computed from the sample
+/// index, so long ramps don't drift the way an accumulated step does.
+#[derive(Debug, Clone)]
+pub struct GainRamp {
+    pub shape: RampShape,
+    start: f32,
+    end: f32,
+    duration_samples: u32,
+    current_sample: u32,
+}
+
+impl GainRamp {
+    /// Holding `value`, with a linear shape.
+    pub fn new(value: f32) -> Self {
+        Self { shape: RampShape::Linear, start: value, end: value, duration_samples: 0, current_sample: 0 }
+    }
+
+    /// Ramp from the current value to `target` over `duration_samples`; 0
+    /// jumps there.
+    pub fn ramp_to(&mut self, target: f32, duration_samples: u32) {
+        self.start = self.value();
+        self.end = target;
+        self.duration_samples = duration_samples;
+        self.current_sample = 0;
+    }
+
+    /// Jump to `value` and hold it.
+    pub fn set(&mut self, value: f32) {
+        *self = Self { shape: self.shape, ..Self::new(value) };
+    }
+
+    /// The gain last returned by `next()` (the start of the ramp before any).
+    pub fn value(&self) -> f32 {
+        self.at(self.current_sample)
+    }
+
+    /// Where the ramp ends.
+    pub fn target(&self) -> f32 {
+        self.end
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.current_sample >= self.duration_samples
+    }
+
+    /// Samples left until `end` is reached.
+    pub fn remaining(&self) -> u32 {
+        self.duration_samples.saturating_sub(self.current_sample)
+    }
+
+    /// Advance one sample and return its gain; `end` once the ramp is done.
+    #[inline]
+    #[allow(clippy::should_implement_trait)] // never ends, so not an `Iterator`
+    pub fn next(&mut self) -> f32 {
+        if self.current_sample < self.duration_samples {
+            self.current_sample += 1;
+        }
+        self.value()
+    }
+
+    /// Multiply each sample of `block` by successive `next()` values. For
+    /// interleaved audio, call `next()` once per frame instead.
+    pub fn apply_to_block(&mut self, block: &mut [f32]) {
+        let ramped = (self.remaining() as usize).min(block.len());
+        let (ramp, hold) = block.split_at_mut(ramped);
+        for s in ramp {
+            *s *= self.next();
+        }
+        let g = self.end;
+        for s in hold {
+            *s *= g;
+        }
+    }
+
+    fn at(&self, sample: u32) -> f32 {
+        if sample >= self.duration_samples {
+            return self.end;
+        }
+        let t = sample as f32 / self.duration_samples as f32;
+        match self.shape {
+            RampShape::Exponential if self.start * self.end > 0.0 => self.start * (self.end / self.start).powf(t),
+            _ => self.start + (self.end - self.start) * t,
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use super::effect::{Effect, ParamDescriptor};
+use super::smoothed::SmoothedParam;
+
+const PARAMS: &[ParamDescriptor] = &[
+    ParamDescriptor::new("target_rms_db", -20.0),
+    ParamDescriptor::new("max_boost_db", 12.0),
+    ParamDescriptor::new("max_cut_db", -12.0),
+    ParamDescriptor::new("speed_ms", 500.0),
+];
+
+/// How often the level is measured and the gain retargeted.
+const HOP_S: f32 = 0.01;
+/// Hops in the 100 ms RMS window.
+const WINDOW_HOPS: usize = 10;
+/// Below this RMS (pauses, room tone) the gain is held rather than boosted.
+const GATE_DB: f32 = -60.0;
+
+/// Broadcast-style gain rider: moves the level towards `target_rms_db` by
+/// the short-term (100 ms) RMS of all channels, so it follows how dense the
+/// signal is rather than its peaks. The correction is limited to
+/// `max_cut_db..=max_boost_db` (`max_cut_db` is negative) and glides there
+/// over about `speed_ms`. Gain is held while the input is near silence.
+pub struct GainRider {
+    pub target_rms_db: f32,
+    pub max_boost_db: f32,
+    pub max_cut_db: f32,
+    pub speed_ms: f32,
+    sr: u32,
+    channels: usize,
+    gain: SmoothedParam, // linear
+    hop_frames: usize,
+    frames: usize,                 // into the current hop
+    energy: f64,                   // sum of squares in the current hop
+    hops: [f64; WINDOW_HOPS],      // mean square per hop, a ring
+    hop_pos: usize,
+    hops_seen: usize,
+}
+
+impl GainRider {
+    pub fn new(target_rms_db: f32, max_boost_db: f32, max_cut_db: f32, speed_ms: f32) -> Self {
+        Self {
+            target_rms_db,
+            max_boost_db,
+            max_cut_db,
+            speed_ms,
+            sr: 48_000,
+            channels: 0,
+            gain: SmoothedParam::new(1.0),
+            hop_frames: 1,
+            frames: 0,
+            energy: 0.0,
+            hops: [0.0; WINDOW_HOPS],
+            hop_pos: 0,
+            hops_seen: 0,
+        }
+    }
+
+    /// Gain currently applied, in dB: where the smoothed gain stood at the
+    /// last frame processed, which trails the level it is moving towards.
+    pub fn gain_db(&self) -> f32 {
+        20.0 * self.gain.current().max(1e-10).log10()
+    }
+
+    fn end_hop(&mut self) {
+        self.hops[self.hop_pos] = self.energy / (self.frames * self.channels) as f64;
+        self.hop_pos = (self.hop_pos + 1) % WINDOW_HOPS;
+        self.hops_seen = (self.hops_seen + 1).min(WINDOW_HOPS);
+        self.frames = 0;
+        self.energy = 0.0;
+
+        let mean_square = self.hops[..self.hops_seen].iter().sum::<f64>() / self.hops_seen as f64;
+        let rms_db = (10.0 * mean_square.max(1e-20).log10()) as f32;
+        if rms_db < GATE_DB {
+            return;
+        }
+        let (lo, hi) = (self.max_cut_db.min(0.0), self.max_boost_db.max(0.0));
+        let db = (self.target_rms_db - rms_db).clamp(lo, hi);
+        self.gain.set_target(10f32.powf(db / 20.0));
+    }
+}
+
+impl Effect for GainRider {
+    fn name(&self) -> &str {
+        "GainRider"
+    }
+
+    fn prepare(&mut self, sr: u32, channels: u16) {
+        self.sr = sr;
+        self.channels = channels as usize;
+        self.hop_frames = ((HOP_S * sr as f32) as usize).max(1);
+        self.frames = 0;
+        self.energy = 0.0;
+        self.hops = [0.0; WINDOW_HOPS];
+        self.hop_pos = 0;
+        self.hops_seen = 0;
+        self.gain = SmoothedParam::new(1.0);
+        self.gain.prepare(sr, self.speed_ms.max(0.0) / 1000.0);
+    }
+    fn set_param_db(&mut self, key: &str, value: f32) {
+        match key {
+            "target_rms_db" => self.target_rms_db = value,
+            "max_boost_db" => self.max_boost_db = value,
+            "max_cut_db" => self.max_cut_db = value,
+            "speed_ms" => {
+                self.speed_ms = value;
+                self.gain.set_ramp(self.sr, value.max(0.0) / 1000.0);
+            }
+            _ => {}
+        }
+    }
+    fn param_descriptors(&self) -> &[ParamDescriptor] {
+        PARAMS
+    }
+    fn process(&mut self, block: &mut [f32]) {
+        if self.channels == 0 {
+            return;
+        }
+        for frame in block.chunks_exact_mut(self.channels) {
+            let g = self.gain.tick();
+            for x in frame.iter_mut() {
+                self.energy += (*x as f64) * (*x as f64);
+                *x *= g;
+            }
+            self.frames += 1;
+            if self.frames >= self.hop_frames {
+                self.end_hop();
+            }
+        }
+    }
+    fn clone_fresh(&self) -> Option<Box<dyn Effect>> {
+        Some(Box::new(GainRider::new(self.target_rms_db, self.max_boost_db, self.max_cut_db, self.speed_ms)))
+    }
+}
@@ -0,0 +1,157 @@
+use super::effect::{Effect, ParamDescriptor};
+use super::utils::vec_bytes;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+const PARAMS: &[ParamDescriptor] = &[ParamDescriptor::new("probability", 0.1), ParamDescriptor::new("repeat_count", 4.0)];
+
+/// Frames reserved for the repeat buffer so typical blocks never grow it.
+const RESERVED_FRAMES: usize = 4096;
+/// Grains per captured block in `GlitchMode::Granular`.
+const GRAINS: usize = 8;
+
+/// How a captured block is replayed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GlitchMode {
+    /// Loop it as captured.
+    Stutter,
+    /// Loop it backwards.
+    Reverse,
+    /// Replay short grains of it from random positions.
+    Granular,
+}
+
+/// Start of the glitch rolls, so a prepared effect always glitches the same way.
+const RNG_SEED: u32 = 0x9e37_79b9;
+
+/// Buffer-repeat mangler: at each block, with chance `probability`, the block
+/// is captured and replayed (per `mode`) over the next `repeat_count` blocks
+/// instead of the input, then live input resumes. `trigger()` forces a capture
+/// at the next block; like `set_freeze` on `Freeze` it only sets an atomic
+/// flag, shared through `trigger_flag()`.
+pub struct GlitchEffect {
+    pub probability: f32,
+    pub repeat_count: u32,
+    pub mode: GlitchMode,
+    trigger: Arc<AtomicBool>,
+    channels: usize,
+    buf: Vec<f32>,
+    remaining: u32, // blocks still to replace
+    pos: usize,     // replay position in frames
+    grain_start: usize,
+    grain_left: usize,
+    rng: u32,
+}
+
+impl GlitchEffect {
+    pub fn new(probability: f32, repeat_count: u32, mode: GlitchMode) -> Self {
+        Self {
+            probability: probability.clamp(0.0, 1.0),
+            repeat_count,
+            mode,
+            trigger: Arc::new(AtomicBool::new(false)),
+            channels: 0,
+            buf: Vec::new(),
+            remaining: 0,
+            pos: 0,
+            grain_start: 0,
+            grain_left: 0,
+            rng: RNG_SEED,
+        }
+    }
+
+    pub fn trigger(&self) {
+        self.trigger.store(true, Ordering::Relaxed);
+    }
+
+    /// Handle on the pending trigger: storing true forces a capture at the next
+    /// block regardless of `probability`, and the effect clears it again.
+    pub fn trigger_flag(&self) -> Arc<AtomicBool> {
+        self.trigger.clone()
+    }
+
+    /// Xorshift; good enough for dice rolls and grain positions.
+    fn next_random(&mut self) -> u32 {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 17;
+        self.rng ^= self.rng << 5;
+        self.rng
+    }
+
+    fn roll(&mut self) -> bool {
+        (self.next_random() as f32 / u32::MAX as f32) < self.probability
+    }
+
+    /// Replayed frame for output position `pos` (in frames).
+    fn source_frame(&mut self, frames: usize) -> usize {
+        let p = self.pos % frames;
+        match self.mode {
+            GlitchMode::Stutter => p,
+            GlitchMode::Reverse => frames - 1 - p,
+            GlitchMode::Granular => {
+                let grain = (frames / GRAINS).max(1);
+                if self.grain_left == 0 {
+                    self.grain_start = self.next_random() as usize % frames;
+                    self.grain_left = grain;
+                }
+                let src = (self.grain_start + grain - self.grain_left) % frames;
+                self.grain_left -= 1;
+                src
+            }
+        }
+    }
+}
+
+impl Effect for GlitchEffect {
+    fn name(&self) -> &str {
+        "GlitchEffect"
+    }
+
+    fn prepare(&mut self, _sr: u32, channels: u16) {
+        self.channels = channels as usize;
+        self.buf = Vec::with_capacity(RESERVED_FRAMES * self.channels);
+        self.remaining = 0;
+        self.pos = 0;
+        self.grain_start = 0;
+        self.grain_left = 0;
+        self.rng = RNG_SEED;
+    }
+    fn set_param_db(&mut self, key: &str, value: f32) {
+        match key {
+            "probability" => self.probability = value.clamp(0.0, 1.0),
+            "repeat_count" => self.repeat_count = value.max(0.0) as u32,
+            "trigger" if value >= 0.5 => self.trigger(),
+            _ => {}
+        }
+    }
+    fn param_descriptors(&self) -> &[ParamDescriptor] {
+        PARAMS
+    }
+    fn process(&mut self, block: &mut [f32]) {
+        let ch = self.channels;
+        if ch == 0 {
+            return;
+        }
+        if self.remaining == 0 {
+            let forced = self.trigger.swap(false, Ordering::Relaxed);
+            if (forced || self.roll()) && self.repeat_count > 0 && !block.is_empty() {
+                self.buf.clear();
+                self.buf.extend_from_slice(block);
+                self.remaining = self.repeat_count;
+                self.pos = 0;
+                self.grain_left = 0;
+            }
+            return;
+        }
+        let frames = self.buf.len() / ch;
+        for frame in block.chunks_exact_mut(ch) {
+            let src = self.source_frame(frames);
+            frame.copy_from_slice(&self.buf[src * ch..][..ch]);
+            self.pos += 1;
+        }
+        self.remaining -= 1;
+    }
+    fn memory_usage_bytes(&self) -> usize {
+        vec_bytes(&self.buf)
+    }
+}
@@ -0,0 +1,75 @@
+use super::file_source::read_wav;
+use anyhow::{anyhow, Result};
+use std::path::Path;
+
+/// Samples at or below this magnitude (-80 dBFS) count as leading silence.
+const SILENCE: f32 = 1e-4;
+
+/// Reads impulse responses from WAV files for convolution effects: resampled
+/// to the engine rate, with leading silence trimmed so the IR adds no delay.
+/// Resampling needs the `resample` feature; without it a file at another rate
+/// is an error.
+pub struct ImpulseResponseLoader;
+
+impl ImpulseResponseLoader {
+    /// Mono IR; multichannel files are averaged down.
+    pub fn load(path: &Path, target_sr: u32) -> Result<Vec<f32>> {
+        let (samples, channels) = read_ir(path, target_sr)?;
+        let mut ir: Vec<f32> = samples.chunks_exact(channels).map(|f| f.iter().sum::<f32>() / channels as f32).collect();
+        let start = first_signal(&ir).ok_or_else(|| anyhow!("{}: impulse response is silent", path.display()))?;
+        ir.drain(..start);
+        Ok(ir)
+    }
+
+    /// Left and right IRs for a stereo convolver. A mono file gives the same IR
+    /// twice; beyond two channels the rest are ignored. Both sides are trimmed
+    /// by the same amount so the delay between them is kept.
+    pub fn load_stereo(path: &Path, target_sr: u32) -> Result<(Vec<f32>, Vec<f32>)> {
+        let (samples, channels) = read_ir(path, target_sr)?;
+        let right = usize::from(channels > 1);
+        let mut left: Vec<f32> = samples.chunks_exact(channels).map(|f| f[0]).collect();
+        let mut right: Vec<f32> = samples.chunks_exact(channels).map(|f| f[right]).collect();
+        let start = match (first_signal(&left), first_signal(&right)) {
+            (Some(l), Some(r)) => l.min(r),
+            (Some(s), None) | (None, Some(s)) => s,
+            (None, None) => return Err(anyhow!("{}: impulse response is silent", path.display())),
+        };
+        left.drain(..start);
+        right.drain(..start);
+        Ok((left, right))
+    }
+}
+
+/// Interleaved samples at `target_sr` and their channel count.
+fn read_ir(path: &Path, target_sr: u32) -> Result<(Vec<f32>, usize)> {
+    let (samples, spec) = read_wav(path)?;
+    let channels = spec.channels.max(1) as usize;
+    if samples.len() < channels {
+        return Err(anyhow!("{}: impulse response is empty", path.display()));
+    }
+    if spec.sample_rate == target_sr {
+        return Ok((samples, channels));
+    }
+    resample(&samples, channels, spec.sample_rate, target_sr)
+        .map(|s| (s, channels))
+        .ok_or_else(|| anyhow!("{}: cannot resample from {} Hz to {target_sr} Hz", path.display(), spec.sample_rate))
+}
+
+#[cfg(feature = "resample")]
+fn resample(samples: &[f32], channels: usize, from: u32, to: u32) -> Option<Vec<f32>> {
+    use super::effect::Effect;
+    use super::src::{SampleRateConverter, SrcQuality};
+
+    let mut src = SampleRateConverter::new(from, to, SrcQuality::Cubic);
+    src.prepare(to, channels as u16);
+    src.convert_buffer(samples)
+}
+
+#[cfg(not(feature = "resample"))]
+fn resample(_samples: &[f32], _channels: usize, _from: u32, _to: u32) -> Option<Vec<f32>> {
+    None
+}
+
+fn first_signal(ir: &[f32]) -> Option<usize> {
+    ir.iter().position(|s| s.abs() > SILENCE)
+}
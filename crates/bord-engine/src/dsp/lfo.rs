@@ -0,0 +1,60 @@
+//! Low-frequency oscillator shared by the modulation effects.
+
+use std::f32::consts::TAU;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LfoWaveform {
+    #[default]
+    Sine,
+    Triangle,
+    Square,
+}
+
+impl LfoWaveform {
+    /// Value in [-1, 1] at `phase` in [0, 1); sine and triangle start at 0 rising, square high.
+    pub fn at(self, phase: f32) -> f32 {
+        match self {
+            LfoWaveform::Sine => (TAU * phase).sin(),
+            LfoWaveform::Triangle => 4.0 * ((phase + 0.75).fract() - 0.5).abs() - 1.0,
+            LfoWaveform::Square => if phase < 0.5 { 1.0 } else { -1.0 },
+        }
+    }
+}
+
+/// Phase accumulator stepping `waveform` once per sample at `rate_hz`.
+pub struct Lfo {
+    pub waveform: LfoWaveform,
+    rate_hz: f32,
+    sr: u32,
+    phase: f32,
+    step: f32,
+}
+
+impl Lfo {
+    pub fn new(waveform: LfoWaveform, rate_hz: f32) -> Self {
+        let mut lfo = Self { waveform, rate_hz, sr: 48_000, phase: 0.0, step: 0.0 };
+        lfo.set_rate(rate_hz);
+        lfo
+    }
+    /// Restart at phase 0 for sample rate `sr`.
+    pub fn prepare(&mut self, sr: u32) {
+        self.sr = sr.max(1);
+        self.phase = 0.0;
+        self.set_rate(self.rate_hz);
+    }
+    /// Real-time safe; the phase carries on from where it is.
+    pub fn set_rate(&mut self, hz: f32) {
+        self.rate_hz = hz.max(0.0);
+        self.step = self.rate_hz / self.sr as f32;
+    }
+    pub fn rate(&self) -> f32 {
+        self.rate_hz
+    }
+    /// Current value, then advance one sample.
+    #[inline]
+    pub fn tick(&mut self) -> f32 {
+        let v = self.waveform.at(self.phase);
+        self.phase = (self.phase + self.step).fract();
+        v
+    }
+}
@@ -0,0 +1,67 @@
+use super::effect::Effect;
+use super::utils::vec_bytes;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+/// Delays the signal by `delay_samples` frames while keeping the input that
+/// is still to come available, for effects that react before the audio does
+/// (lookahead limiting, de-essing). After `process()` the window holds the
+/// `delay_samples` frames before the current input block, then that block; the
+/// output was the start of it. The `peek_*` methods read the window.
+pub struct Lookahead {
+    pub delay_samples: usize,
+    channels: usize,
+    buf: Vec<f32>,    // interleaved window: delay before the current block, then the block
+    block_len: usize, // samples in the last block
+}
+
+impl Lookahead {
+    pub fn new(delay_samples: usize) -> Self {
+        Self { delay_samples, channels: 1, buf: Vec::new(), block_len: 0 }
+    }
+
+    /// The input block passed to the last `process()`, interleaved.
+    pub fn peek_current_block(&self) -> &[f32] {
+        &self.buf[(self.delay_samples * self.channels).min(self.buf.len())..]
+    }
+
+    /// Up to one block of the window starting `offset` frames after the audio
+    /// just output: 0 is what `process()` returned, `delay_samples` is the
+    /// current input block. Shorter (or empty) past the end of the window.
+    pub fn peek_with_offset(&self, offset: usize) -> &[f32] {
+        let start = (offset * self.channels).min(self.buf.len());
+        let end = (start + self.block_len).min(self.buf.len());
+        &self.buf[start..end]
+    }
+}
+
+impl Effect for Lookahead {
+    fn name(&self) -> &str {
+        "Lookahead"
+    }
+
+    fn prepare(&mut self, _sr: u32, channels: u16) {
+        self.channels = channels.max(1) as usize;
+        let delay = self.delay_samples * self.channels;
+        // Typical blocks fit; larger ones grow the window once.
+        self.buf = Vec::with_capacity(delay + 4096 * self.channels);
+        self.buf.resize(delay, 0.0);
+        self.block_len = 0;
+    }
+    fn process(&mut self, block: &mut [f32]) {
+        // Drop what the last block output; what remains is the pending delay.
+        self.buf.drain(..self.block_len.min(self.buf.len()));
+        self.buf.extend_from_slice(block);
+        block.copy_from_slice(&self.buf[..block.len()]);
+        self.block_len = block.len();
+    }
+    fn latency_samples(&self) -> u32 {
+        self.delay_samples as u32
+    }
+    fn clone_fresh(&self) -> Option<Box<dyn Effect>> {
+        Some(Box::new(Lookahead::new(self.delay_samples)))
+    }
+    fn memory_usage_bytes(&self) -> usize {
+        vec_bytes(&self.buf)
+    }
+}
@@ -0,0 +1,274 @@
+use super::biquad::{Biquad, Coeffs};
+use super::delay_line::DelayLine;
+use super::effect::{Effect, ParamDescriptor};
+use super::smoothed::SmoothedParam;
+use super::utils::vec_bytes;
+
+const PARAMS: &[ParamDescriptor] = &[ParamDescriptor::new("target_lufs", -16.0), ParamDescriptor::new("max_true_peak_db", -1.0)];
+
+/// How far ahead the live mode measures (and delays the output).
+const LOOKAHEAD_S: f32 = 3.0;
+/// Gating block hop, and how often the live gain is updated.
+const STEP_S: f32 = 0.1;
+/// Hops per 400 ms gating block.
+const BLOCK_STEPS: usize = 4;
+const ABSOLUTE_GATE_LUFS: f32 = -70.0;
+const RELATIVE_GATE_LU: f32 = -10.0;
+/// Gated blocks are histogrammed in 0.1 LU bins from the absolute gate up.
+const BIN_LU: f32 = 0.1;
+const BINS: usize = 800;
+/// Most the live mode will boost or cut.
+const MAX_GAIN_DB: f32 = 20.0;
+
+/// Loudness normalization to `target_lufs` (ITU-R BS.1770 integrated
+/// loudness: K-weighted, 400 ms blocks gated at -70 LUFS and then 10 LU below
+/// the mean). All channels are weighted as front channels. The peak ceiling
+/// `max_true_peak_db` is checked against sample peaks, not oversampled ones.
+///
+/// Live (as an `Effect`), the output is delayed by 3 s while the input is
+/// measured; every 100 ms the gain moves towards the one that brings the
+/// loudness integrated so far to the target without the next 3 s of output
+/// exceeding the ceiling. Offline, `analyze` measures a whole recording and
+/// `apply` applies the result.
+pub struct Loudnorm {
+    pub target_lufs: f32,
+    pub max_true_peak_db: f32,
+    meter: LoudnessMeter,
+    line: DelayLine,
+    gain: SmoothedParam, // linear
+    peaks: Vec<f32>,     // per hop over the lookahead window, a ring
+    peak_pos: usize,
+    lookahead: usize, // frames
+}
+
+impl Loudnorm {
+    pub fn new(target_lufs: f32, max_true_peak_db: f32) -> Self {
+        Self {
+            target_lufs,
+            max_true_peak_db,
+            meter: LoudnessMeter::new(),
+            line: DelayLine::new(),
+            gain: SmoothedParam::new(1.0),
+            peaks: Vec::new(),
+            peak_pos: 0,
+            lookahead: 0,
+        }
+    }
+
+    /// First pass over mono `samples`: the gain in dB `apply` should use.
+    pub fn analyze(&self, samples: &[f32], sr: u32) -> f32 {
+        self.analyze_interleaved(samples, sr, 1)
+    }
+
+    /// `analyze` for interleaved `samples` of `channels`. 0 dB if the whole
+    /// recording is below the gate.
+    pub fn analyze_interleaved(&self, samples: &[f32], sr: u32, channels: u16) -> f32 {
+        let Some(lufs) = integrated_lufs(samples, sr, channels) else { return 0.0 };
+        let peak = samples.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+        let headroom = self.max_true_peak_db - 20.0 * peak.max(1e-10).log10();
+        (self.target_lufs - lufs).min(headroom)
+    }
+
+    /// Second pass: scale `samples` by `gain_db`.
+    pub fn apply(samples: &mut [f32], gain_db: f32) {
+        let g = 10f32.powf(gain_db / 20.0);
+        for s in samples {
+            *s *= g;
+        }
+    }
+
+    fn update_gain(&mut self) {
+        self.peaks[self.peak_pos] = self.meter.take_peak();
+        self.peak_pos = (self.peak_pos + 1) % self.peaks.len();
+        let Some(lufs) = self.meter.integrated() else { return };
+        let peak = self.peaks.iter().fold(0.0f32, |m, &p| m.max(p));
+        let headroom = self.max_true_peak_db - 20.0 * peak.max(1e-10).log10();
+        let db = (self.target_lufs - lufs).clamp(-MAX_GAIN_DB, MAX_GAIN_DB).min(headroom);
+        self.gain.set_target(10f32.powf(db / 20.0));
+    }
+}
+
+/// Integrated loudness of interleaved `samples` in LUFS; `None` if every
+/// block is below the absolute gate.
+pub fn integrated_lufs(samples: &[f32], sr: u32, channels: u16) -> Option<f32> {
+    let mut meter = LoudnessMeter::new();
+    meter.prepare(sr, channels);
+    let ch = channels.max(1) as usize;
+    for frame in samples.chunks_exact(ch) {
+        for (c, &x) in frame.iter().enumerate() {
+            meter.push(c, x);
+        }
+        meter.end_frame();
+    }
+    meter.integrated()
+}
+
+impl Effect for Loudnorm {
+    fn name(&self) -> &str {
+        "Loudnorm"
+    }
+
+    fn prepare(&mut self, sr: u32, channels: u16) {
+        self.meter.prepare(sr, channels);
+        self.lookahead = (LOOKAHEAD_S * sr as f32) as usize;
+        self.line.prepare(channels.max(1), self.lookahead);
+        self.peaks = vec![0.0; (LOOKAHEAD_S / STEP_S).round() as usize];
+        self.peak_pos = 0;
+        self.gain = SmoothedParam::new(1.0);
+        self.gain.prepare(sr, STEP_S);
+    }
+    fn set_param_db(&mut self, key: &str, value: f32) {
+        match key {
+            "target_lufs" => self.target_lufs = value,
+            "max_true_peak_db" => self.max_true_peak_db = value,
+            _ => {}
+        }
+    }
+    fn param_descriptors(&self) -> &[ParamDescriptor] {
+        PARAMS
+    }
+    fn process(&mut self, block: &mut [f32]) {
+        let ch = self.line.channels();
+        if ch == 0 {
+            return;
+        }
+        for frame in block.chunks_exact_mut(ch) {
+            let g = self.gain.tick();
+            for (c, x) in frame.iter_mut().enumerate() {
+                self.meter.push(c, *x);
+                let delayed = self.line.read(c);
+                self.line.write(c, *x);
+                *x = delayed * g;
+            }
+            self.line.advance();
+            if self.meter.end_frame() {
+                self.update_gain();
+            }
+        }
+    }
+    fn latency_samples(&self) -> u32 {
+        self.lookahead as u32
+    }
+    fn clone_fresh(&self) -> Option<Box<dyn Effect>> {
+        Some(Box::new(Loudnorm::new(self.target_lufs, self.max_true_peak_db)))
+    }
+    fn memory_usage_bytes(&self) -> usize {
+        self.line.memory_bytes() + vec_bytes(&self.peaks) + vec_bytes(&self.meter.counts) + vec_bytes(&self.meter.energies)
+    }
+}
+
+/// K-weighting, 100 ms hop energies and a histogram of gated 400 ms blocks,
+/// so the integrated loudness costs the same however long it has run.
+struct LoudnessMeter {
+    shelf: Biquad,
+    high_pass: Biquad,
+    step_frames: usize,
+    frames: usize,   // into the current hop
+    energy: f64,     // sum of squares over channels in the current hop
+    peak: f32,       // sample peak since `take_peak`
+    steps: [f64; BLOCK_STEPS], // mean square per hop, a ring
+    steps_seen: usize,
+    counts: Vec<u32>,
+    energies: Vec<f64>,
+}
+
+impl LoudnessMeter {
+    fn new() -> Self {
+        Self {
+            shelf: Biquad::new(),
+            high_pass: Biquad::new(),
+            step_frames: 1,
+            frames: 0,
+            energy: 0.0,
+            peak: 0.0,
+            steps: [0.0; BLOCK_STEPS],
+            steps_seen: 0,
+            counts: Vec::new(),
+            energies: Vec::new(),
+        }
+    }
+
+    fn prepare(&mut self, sr: u32, channels: u16) {
+        let (shelf, high_pass) = k_weighting(sr);
+        self.shelf.prepare(channels);
+        self.shelf.coeffs = shelf;
+        self.high_pass.prepare(channels);
+        self.high_pass.coeffs = high_pass;
+        self.step_frames = ((STEP_S * sr as f32) as usize).max(1);
+        self.frames = 0;
+        self.energy = 0.0;
+        self.peak = 0.0;
+        self.steps_seen = 0;
+        self.counts = vec![0; BINS];
+        self.energies = vec![0.0; BINS];
+    }
+
+    #[inline]
+    fn push(&mut self, ch: usize, x: f32) {
+        let y = self.high_pass.tick(ch, self.shelf.tick(ch, x));
+        self.energy += (y as f64) * (y as f64);
+        self.peak = self.peak.max(x.abs());
+    }
+
+    /// True when the frame completed a 100 ms hop.
+    fn end_frame(&mut self) -> bool {
+        self.frames += 1;
+        if self.frames < self.step_frames {
+            return false;
+        }
+        self.steps[self.steps_seen % BLOCK_STEPS] = self.energy / self.frames as f64;
+        self.steps_seen += 1;
+        self.frames = 0;
+        self.energy = 0.0;
+        if self.steps_seen >= BLOCK_STEPS {
+            let block = self.steps.iter().sum::<f64>() / BLOCK_STEPS as f64;
+            let lufs = loudness(block);
+            if lufs >= ABSOLUTE_GATE_LUFS {
+                let bin = (((lufs - ABSOLUTE_GATE_LUFS) / BIN_LU) as usize).min(BINS - 1);
+                self.counts[bin] += 1;
+                self.energies[bin] += block;
+            }
+        }
+        true
+    }
+
+    fn take_peak(&mut self) -> f32 {
+        core::mem::take(&mut self.peak)
+    }
+
+    fn integrated(&self) -> Option<f32> {
+        let mean = |from: usize| {
+            let n: u64 = self.counts[from..].iter().map(|&c| c as u64).sum();
+            (n > 0).then(|| self.energies[from..].iter().sum::<f64>() / n as f64)
+        };
+        let threshold = loudness(mean(0)?) + RELATIVE_GATE_LU;
+        let from = ((threshold - ABSOLUTE_GATE_LUFS) / BIN_LU).max(0.0) as usize;
+        mean(from.min(BINS - 1)).map(loudness)
+    }
+}
+
+fn loudness(mean_square: f64) -> f32 {
+    (-0.691 + 10.0 * mean_square.max(1e-20).log10()) as f32
+}
+
+/// The two BS.1770 pre-filters (a high shelf for the head, then the RLB
+/// high-pass), designed for `sr` as in the standard's 48 kHz coefficients.
+fn k_weighting(sr: u32) -> (Coeffs, Coeffs) {
+    let sr = sr.max(1) as f64;
+
+    let (f0, gain_db, q) = (1681.974450955533, 3.999843853973347, 0.7071752369554196);
+    let k = (std::f64::consts::PI * f0 / sr).tan();
+    let vh = 10f64.powf(gain_db / 20.0);
+    let vb = vh.powf(0.4996667741545416);
+    let shelf = Coeffs::normalize(
+        [vh + vb * k / q + k * k, 2.0 * (k * k - vh), vh - vb * k / q + k * k],
+        [1.0 + k / q + k * k, 2.0 * (k * k - 1.0), 1.0 - k / q + k * k],
+    );
+
+    let (f0, q) = (38.13547087602444, 0.5003270373238773);
+    let k = (std::f64::consts::PI * f0 / sr).tan();
+    let a0 = 1.0 + k / q + k * k;
+    // The standard leaves the numerator unnormalised.
+    let high_pass = Coeffs::normalize([a0, -2.0 * a0, a0], [a0, 2.0 * (k * k - 1.0), 1.0 - k / q + k * k]);
+    (shelf, high_pass)
+}
@@ -0,0 +1,60 @@
+use super::butterworth::{Cascade, Response, MAX_ORDER};
+use super::effect::{Effect, ParamDescriptor};
+use alloc::boxed::Box;
+
+const PARAMS: &[ParamDescriptor] = &[ParamDescriptor::new("cutoff_hz", 8000.0)];
+
+/// Butterworth low-pass, order 1-8 (-6 dB/octave per order).
+pub struct LowPassFilter {
+    pub cutoff_hz: f32,
+    pub order: u8,
+    sr: u32,
+    channels: u16, // 0 until prepared
+    filter: Cascade,
+}
+
+impl LowPassFilter {
+    pub fn new(cutoff_hz: f32, order: u8) -> Self {
+        Self { cutoff_hz, order: order.clamp(1, MAX_ORDER), sr: 48_000, channels: 0, filter: Cascade::new(Response::LowPass) }
+    }
+
+    /// Change the cutoff; coefficients are recomputed without allocating.
+    pub fn set_cutoff_hz(&mut self, f: f32) {
+        self.cutoff_hz = f;
+        self.filter.set_cutoff(self.order, f, self.sr);
+    }
+}
+
+impl Effect for LowPassFilter {
+    fn name(&self) -> &str {
+        "LowPassFilter"
+    }
+
+    fn prepare(&mut self, sr: u32, channels: u16) {
+        self.sr = sr;
+        self.channels = channels;
+        self.filter.prepare(self.order, self.cutoff_hz, sr, channels);
+    }
+    fn prepare_hot(&mut self, sr: u32, channels: u16) -> bool {
+        if (sr, channels) != (self.sr, self.channels) {
+            return false;
+        }
+        self.filter.set_cutoff(self.order, self.cutoff_hz, sr);
+        true
+    }
+    fn set_param_db(&mut self, key: &str, value: f32) {
+        // The only runtime parameter; takes Hz despite the method name.
+        if key == "cutoff_hz" {
+            self.set_cutoff_hz(value);
+        }
+    }
+    fn param_descriptors(&self) -> &[ParamDescriptor] {
+        PARAMS
+    }
+    fn process(&mut self, block: &mut [f32]) {
+        self.filter.process(block);
+    }
+    fn clone_fresh(&self) -> Option<Box<dyn Effect>> {
+        Some(Box::new(LowPassFilter::new(self.cutoff_hz, self.order)))
+    }
+}
@@ -0,0 +1,26 @@
+//! `libm` stand-ins for the float methods `std` provides, so the no_std core
+//! keeps calling `x.powf(y)` etc. Only compiled without the `std` feature.
+
+pub(crate) trait FloatExt: Sized {
+    fn powf(self, n: Self) -> Self;
+    fn sqrt(self) -> Self;
+    fn sin(self) -> Self;
+    fn tan(self) -> Self;
+    fn sin_cos(self) -> (Self, Self);
+}
+
+impl FloatExt for f32 {
+    fn powf(self, n: Self) -> Self { libm::powf(self, n) }
+    fn sqrt(self) -> Self { libm::sqrtf(self) }
+    fn sin(self) -> Self { libm::sinf(self) }
+    fn tan(self) -> Self { libm::tanf(self) }
+    fn sin_cos(self) -> (Self, Self) { libm::sincosf(self) }
+}
+
+impl FloatExt for f64 {
+    fn powf(self, n: Self) -> Self { libm::pow(self, n) }
+    fn sqrt(self) -> Self { libm::sqrt(self) }
+    fn sin(self) -> Self { libm::sin(self) }
+    fn tan(self) -> Self { libm::tan(self) }
+    fn sin_cos(self) -> (Self, Self) { libm::sincos(self) }
+}
@@ -0,0 +1,175 @@
+use super::effect::{Effect, Frame, ParamDescriptor, ProcessContext};
+use alloc::vec::Vec;
+
+/// Frames of dry signal kept without a `set_max_block_frames`.
+const DEFAULT_DRY_FRAMES: usize = 4096;
+
+/// Wet/dry blend around an inner effect: `out = dry * (1 - mix) + wet * mix`.
+/// At `mix == 1.0` the inner effect runs in place with no extra copy.
+/// Effects that change the channel count have no matching dry signal and always run fully wet.
+/// A partial mix blends blocks longer than the dry buffer in pieces rather
+/// than growing it on the audio thread.
+pub struct Mix<E: Effect> {
+    pub inner: E,
+    mix: f32,
+    dry: Vec<f32>,
+    dry_frames: usize,
+    channels: usize,
+    reshapes: bool,
+}
+
+impl<E: Effect> Mix<E> {
+    pub fn new(inner: E, mix: f32) -> Self {
+        Self { inner, mix: mix.clamp(0.0, 1.0), dry: Vec::new(), dry_frames: DEFAULT_DRY_FRAMES, channels: 1, reshapes: false }
+    }
+    pub fn mix(&self) -> f32 {
+        self.mix
+    }
+    /// Wet amount in [0, 1]; values outside are clamped.
+    pub fn set_mix(&mut self, mix: f32) {
+        self.mix = mix.clamp(0.0, 1.0);
+    }
+    /// Size the dry buffer for blocks of up to `frames` (never below 4096),
+    /// e.g. the chain's block size, so a partial mix runs them whole.
+    pub fn set_max_block_frames(&mut self, frames: usize) {
+        self.dry_frames = frames.max(DEFAULT_DRY_FRAMES);
+        self.reserve_dry();
+    }
+    /// The wrapper's half of `prepare`, for when the inner effect is
+    /// prepared separately (`Chain::prepare_all_async`): note whether it
+    /// reshapes and size the dry buffer for `channels`.
+    pub fn prepare_blend(&mut self, channels: u16) {
+        self.reshapes = self.inner.output_channel_count(channels) != channels;
+        self.channels = channels.max(1) as usize;
+        self.reserve_dry();
+    }
+    fn reserve_dry(&mut self) {
+        self.dry.clear();
+        self.dry.reserve(self.dry_frames * self.channels);
+    }
+    /// Run `process` on the inner effect over `block`, blended with a copy
+    /// of what was there, in pieces of whole frames that fit `dry`.
+    fn blend(&mut self, block: &mut [f32], mut process: impl FnMut(&mut E, &mut [f32])) {
+        if self.mix >= 1.0 || self.reshapes {
+            process(&mut self.inner, block);
+            return;
+        }
+        let (wet, dry) = (self.mix, 1.0 - self.mix);
+        let piece = (self.dry.capacity() / self.channels).max(1) * self.channels;
+        for part in block.chunks_mut(piece) {
+            self.dry.clear();
+            self.dry.extend_from_slice(part);
+            process(&mut self.inner, part);
+            for (s, &d) in part.iter_mut().zip(self.dry.iter()) {
+                *s = *s * wet + d * dry;
+            }
+        }
+    }
+    /// `blend` for planar stereo.
+    fn blend_stereo(&mut self, left: &mut [f32], right: &mut [f32], mut process: impl FnMut(&mut E, &mut [f32], &mut [f32])) {
+        if self.mix >= 1.0 {
+            process(&mut self.inner, left, right);
+            return;
+        }
+        let (wet, dry) = (self.mix, 1.0 - self.mix);
+        let piece = (self.dry.capacity() / 2).max(1);
+        for (left, right) in left.chunks_mut(piece).zip(right.chunks_mut(piece)) {
+            // `dry` holds the left channel followed by the right.
+            self.dry.clear();
+            self.dry.extend_from_slice(left);
+            self.dry.extend_from_slice(right);
+            process(&mut self.inner, left, right);
+            let (dry_l, dry_r) = self.dry.split_at(left.len());
+            for (s, &d) in left.iter_mut().zip(dry_l).chain(right.iter_mut().zip(dry_r)) {
+                *s = *s * wet + d * dry;
+            }
+        }
+    }
+}
+
+impl<E: Effect> Effect for Mix<E> {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+    fn version(&self) -> (u32, u32, u32) {
+        self.inner.version()
+    }
+    fn prepare(&mut self, sr: u32, channels: u16) {
+        self.inner.prepare(sr, channels);
+        self.prepare_blend(channels);
+    }
+    fn prepare_hot(&mut self, sr: u32, channels: u16) -> bool {
+        self.inner.prepare_hot(sr, channels)
+    }
+    fn set_param_db(&mut self, key: &str, db: f32) {
+        self.inner.set_param_db(key, db);
+    }
+    fn param_descriptors(&self) -> &[ParamDescriptor] {
+        self.inner.param_descriptors()
+    }
+    fn reset_param_to_default(&mut self, key: &str) {
+        self.inner.reset_param_to_default(key);
+    }
+    fn process(&mut self, block: &mut [f32]) {
+        self.blend(block, |fx, block| fx.process(block));
+    }
+    fn process_with_context(&mut self, block: &mut [f32], ctx: &ProcessContext) {
+        self.blend(block, |fx, block| fx.process_with_context(block, ctx));
+    }
+    fn in_place_safe(&self) -> bool {
+        self.inner.in_place_safe()
+    }
+    fn process_into(&mut self, input: &[f32], output: &mut [f32]) {
+        self.inner.process_into(input, output);
+        if self.mix >= 1.0 || self.reshapes {
+            return;
+        }
+        // `input` is the dry signal, no copy needed.
+        let (wet, dry) = (self.mix, 1.0 - self.mix);
+        for (s, &d) in output.iter_mut().zip(input) {
+            *s = *s * wet + d * dry;
+        }
+    }
+    fn process_stereo(&mut self, left: &mut [f32], right: &mut [f32]) {
+        self.blend_stereo(left, right, |fx, l, r| fx.process_stereo(l, r));
+    }
+    fn process_frames(&mut self, frames: &mut [Frame<2>]) {
+        self.blend(Frame::as_interleaved_mut(frames), |fx, block| fx.process_frames(Frame::from_interleaved_mut(block)));
+    }
+    fn process_stereo_with_context(&mut self, left: &mut [f32], right: &mut [f32], ctx: &ProcessContext) {
+        self.blend_stereo(left, right, |fx, l, r| fx.process_stereo_with_context(l, r, ctx));
+    }
+    fn process_frames_with_context(&mut self, frames: &mut [Frame<2>], ctx: &ProcessContext) {
+        self.blend(Frame::as_interleaved_mut(frames), |fx, block| {
+            fx.process_frames_with_context(Frame::from_interleaved_mut(block), ctx)
+        });
+    }
+    fn supports_stereo_split(&self) -> bool {
+        self.inner.supports_stereo_split()
+    }
+    fn output_channel_count(&self, input_channels: u16) -> u16 {
+        self.inner.output_channel_count(input_channels)
+    }
+    fn latency_samples(&self) -> u32 {
+        self.inner.latency_samples()
+    }
+    fn memory_usage_bytes(&self) -> usize {
+        self.inner.memory_usage_bytes() + self.dry.capacity() * core::mem::size_of::<f32>()
+    }
+    fn tail_samples(&self) -> u32 {
+        self.inner.tail_samples()
+    }
+    fn supported_block_sizes(&self) -> Option<(u32, u32)> {
+        self.inner.supported_block_sizes()
+    }
+    fn branches(&self) -> &[Vec<alloc::boxed::Box<dyn Effect>>] {
+        self.inner.branches()
+    }
+    fn clone_fresh(&self) -> Option<alloc::boxed::Box<dyn Effect>> {
+        Some(alloc::boxed::Box::new(Mix::new(self.inner.clone_fresh()?, self.mix)))
+    }
+    #[cfg(feature = "async")]
+    fn as_async(&mut self) -> Option<&mut dyn super::effect::AsyncEffect> {
+        self.inner.as_async()
+    }
+}
@@ -1,2 +1,130 @@
+pub mod aligned_vec;
+#[cfg(feature = "std")]
+pub mod all_pass;
+#[cfg(feature = "std")]
+pub mod auto_pan;
+#[cfg(feature = "std")]
+pub mod balance;
+#[cfg(feature = "std")]
+pub mod bandpass;
+#[cfg(feature = "std")]
+mod biquad;
+mod butterworth;
+#[cfg(feature = "std")]
+pub mod channel_converter;
+#[cfg(feature = "std")]
+pub mod channel_insert;
+#[cfg(feature = "std")]
+pub mod comb;
+#[cfg(feature = "std")]
+pub mod convert;
+pub mod crossfade_chain;
+#[cfg(feature = "std")]
+pub mod crossfeed;
+#[cfg(feature = "std")]
+pub mod crossover;
+#[cfg(feature = "std")]
+mod delay_line;
+#[cfg(feature = "denoise")]
+pub mod denoise;
 pub mod effect;
+#[cfg(feature = "std")]
+pub mod file_source;
+#[cfg(feature = "std")]
+pub mod freeze;
 pub mod gain;
+#[cfg(feature = "std")]
+pub mod gain_automation;
+pub mod gain_ramp;
+#[cfg(feature = "std")]
+pub mod gain_rider;
+#[cfg(feature = "std")]
+pub mod glitch;
+pub mod hpf;
+#[cfg(feature = "std")]
+pub mod ir_loader;
+#[cfg(feature = "std")]
+pub mod lfo;
+pub mod lookahead;
+#[cfg(feature = "std")]
+pub mod loudnorm;
+pub mod lpf;
+#[cfg(not(feature = "std"))]
+mod math;
+pub mod mix;
+#[cfg(feature = "std")]
+pub mod mono_mix;
+#[cfg(feature = "std")]
+pub mod multitap_delay;
+#[cfg(feature = "std")]
+pub mod normalize;
+#[cfg(feature = "std")]
+pub mod notch;
+#[cfg(feature = "std")]
+pub mod parallel;
+#[cfg(feature = "std")]
+pub mod parametric_eq;
+#[cfg(feature = "std")]
+pub mod peak_hold;
+#[cfg(feature = "std")]
+pub mod pingpong;
+#[cfg(feature = "std")]
+pub mod registry;
+#[cfg(feature = "std")]
+pub mod silence;
+#[cfg(feature = "std")]
+pub mod silence_gap;
+pub mod smoothed;
+#[cfg(feature = "spectrum")]
+pub mod spectrum;
+#[cfg(feature = "resample")]
+pub mod src;
+pub mod utils;
+#[cfg(feature = "std")]
+pub mod vocoder;
+#[cfg(feature = "std")]
+pub mod wav_writer;
+#[cfg(feature = "std")]
+pub mod waveform;
+
+#[cfg(feature = "std")]
+use effect::Effect;
+
+/// Names accepted by `effect_by_name`.
+#[cfg(feature = "std")]
+pub const EFFECT_NAMES: &[&str] = &["AllPassFilter", "AutoPan", "BandPass", "CombFilter", "CrossoverLR4", "Freeze", "Gain", "GainRider", "GlitchEffect", "HeadphoneCrossfeed", "HighPassFilter", "Loudnorm", "LowPassFilter", "MonoMix", "MultitapDelay", "NormalizeBlock", "Notch", "PingpongDelay", "StereoBalance"];
+
+/// Construct a default-configured effect by (case-insensitive) name.
+/// Only effects that need no external resources are listed.
+#[cfg(feature = "std")]
+pub fn effect_by_name(name: &str) -> Option<Box<dyn Effect>> {
+    match name.to_lowercase().as_str() {
+        "allpassfilter" => Some(Box::new(all_pass::AllPassFilter::new(556, 0.5))),
+        "autopan" => Some(Box::new(auto_pan::AutoPan::new(0.5, 1.0, lfo::LfoWaveform::Sine))),
+        "bandpass" => Some(Box::new(bandpass::BandPass::new(1000.0, 500.0))),
+        "combfilter" => Some(Box::new(comb::CombFilter::new(1557, 0.84, comb::CombMode::Iir))),
+        "crossoverlr4" => Some(Box::new(crossover::CrossoverLR4::new(2000.0))),
+        "freeze" => Some(Box::new(freeze::Freeze::new(1024))),
+        "gain" => Some(Box::new(gain::Gain::new(0.0))),
+        "gainrider" => Some(Box::new(gain_rider::GainRider::new(-20.0, 12.0, -12.0, 500.0))),
+        "glitcheffect" => Some(Box::new(glitch::GlitchEffect::new(0.1, 4, glitch::GlitchMode::Stutter))),
+        "headphonecrossfeed" => Some(Box::new(crossfeed::HeadphoneCrossfeed::new(0.5))),
+        "highpassfilter" => Some(Box::new(hpf::HighPassFilter::new(100.0, 2))),
+        "loudnorm" => Some(Box::new(loudnorm::Loudnorm::new(-16.0, -1.0))),
+        "lowpassfilter" => Some(Box::new(lpf::LowPassFilter::new(8000.0, 2))),
+        "monomix" => Some(Box::new(mono_mix::MonoMix::new(true))),
+        "multitapdelay" => Some(Box::new(multitap_delay::MultitapDelay::new(
+            8,
+            vec![
+                multitap_delay::TapConfig { delay_ms: 250.0, gain_db: -6.0, pan: -0.5 },
+                multitap_delay::TapConfig { delay_ms: 500.0, gain_db: -12.0, pan: 0.5 },
+            ],
+            0.0,
+        ))),
+        "normalizeblock" => Some(Box::new(normalize::NormalizeBlock::new(-1.0, normalize::NormalizeMode::Peak))),
+        "notch" => Some(Box::new(notch::Notch::new(50.0, 10.0))),
+        "pingpongdelay" => Some(Box::new(pingpong::PingpongDelay::new(375.0, 0.5, 0.35))),
+        "stereobalance" => Some(Box::new(balance::StereoBalance::new(0.0))),
+        _ => None,
+    }
+}
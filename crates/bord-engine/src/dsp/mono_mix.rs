@@ -0,0 +1,63 @@
+use super::effect::{Effect, ParamDescriptor};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+const PARAMS: &[ParamDescriptor] = &[ParamDescriptor::new("enabled", 1.0)];
+
+/// Mono compatibility check: while enabled, every channel of a frame is
+/// replaced by the frame's average; disabled, a passthrough. Meant to go
+/// last in the chain. `set_enabled` only flips an atomic flag, shared through
+/// `enabled_flag()` so it can be toggled while the effect runs.
+pub struct MonoMix {
+    enabled: Arc<AtomicBool>,
+    channels: usize,
+}
+
+impl MonoMix {
+    pub fn new(enabled: bool) -> Self {
+        Self { enabled: Arc::new(AtomicBool::new(enabled)), channels: 0 }
+    }
+
+    pub fn set_enabled(&self, on: bool) {
+        self.enabled.store(on, Ordering::Relaxed);
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Handle on the enabled state: true folds each frame to its average,
+    /// false passes the input through. Read at every block.
+    pub fn enabled_flag(&self) -> Arc<AtomicBool> {
+        self.enabled.clone()
+    }
+}
+
+impl Effect for MonoMix {
+    fn name(&self) -> &str {
+        "MonoMix"
+    }
+
+    fn prepare(&mut self, _sr: u32, channels: u16) {
+        self.channels = channels as usize;
+    }
+    fn set_param_db(&mut self, key: &str, value: f32) {
+        // 0 or 1, despite the method name.
+        if key == "enabled" {
+            self.set_enabled(value >= 0.5);
+        }
+    }
+    fn param_descriptors(&self) -> &[ParamDescriptor] {
+        PARAMS
+    }
+    fn process(&mut self, block: &mut [f32]) {
+        if self.channels < 2 || !self.is_enabled() {
+            return;
+        }
+        let scale = 1.0 / self.channels as f32;
+        for frame in block.chunks_exact_mut(self.channels) {
+            let mean = frame.iter().sum::<f32>() * scale;
+            frame.fill(mean);
+        }
+    }
+}
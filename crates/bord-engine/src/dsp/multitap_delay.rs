@@ -0,0 +1,178 @@
+use super::delay_line::DelayLine;
+use super::effect::{Effect, ParamDescriptor};
+use super::utils::vec_bytes;
+use std::f32::consts::FRAC_PI_4;
+
+const PARAMS: &[ParamDescriptor] = &[ParamDescriptor::new("dry_db", 0.0)];
+
+/// One echo of a `MultitapDelay`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TapConfig {
+    pub delay_ms: f32,
+    pub gain_db: f32,
+    /// -1 (left) .. 1 (right); stereo only.
+    pub pan: f32,
+}
+
+/// A tap's config in frames and linear gains.
+#[derive(Debug, Clone, Copy, Default)]
+struct Tap {
+    frames: usize,
+    gain: f32,
+    left: f32,
+    right: f32,
+}
+
+/// Feed-forward delay with up to `max_taps` echoes read from one buffer, each
+/// with its own delay, level and (stereo) equal-power pan, summed with the dry
+/// signal at `dry_db`. On a stereo block each tap is the delayed L/R mix, panned;
+/// otherwise every channel is delayed on its own and `pan` is ignored.
+///
+/// The buffer is sized for the longest tap at `prepare`; `set_tap` is
+/// real-time safe as long as the new delay fits, and reallocates otherwise.
+pub struct MultitapDelay {
+    pub dry_db: f32,
+    taps: Vec<TapConfig>,
+    max_taps: usize,
+    state: Vec<Tap>,
+    dry: f32,
+    sr: u32,
+    stereo: bool,
+    line: DelayLine,
+}
+
+impl MultitapDelay {
+    /// Taps beyond `max_taps` are dropped.
+    pub fn new(max_taps: usize, mut taps: Vec<TapConfig>, dry_db: f32) -> Self {
+        taps.truncate(max_taps);
+        taps.reserve_exact(max_taps - taps.len());
+        Self {
+            dry_db,
+            taps,
+            max_taps,
+            state: Vec::with_capacity(max_taps),
+            dry: db_to_lin(dry_db),
+            sr: 0,
+            stereo: false,
+            line: DelayLine::new(),
+        }
+    }
+
+    pub fn taps(&self) -> &[TapConfig] {
+        &self.taps
+    }
+
+    pub fn max_taps(&self) -> usize {
+        self.max_taps
+    }
+
+    /// Replace tap `index`, or add one when `index == taps().len()`. False if
+    /// `index` is past that or the delay is already at `max_taps`.
+    pub fn set_tap(&mut self, index: usize, config: TapConfig) -> bool {
+        match index.cmp(&self.taps.len()) {
+            std::cmp::Ordering::Less => self.taps[index] = config,
+            std::cmp::Ordering::Equal if index < self.max_taps => self.taps.push(config),
+            _ => return false,
+        }
+        if self.sr > 0 {
+            self.update_state();
+        }
+        true
+    }
+
+    pub fn set_dry_db(&mut self, dry_db: f32) {
+        self.dry_db = dry_db;
+        self.dry = db_to_lin(dry_db);
+    }
+
+    fn frames(&self, delay_ms: f32) -> usize {
+        ((delay_ms.max(0.0) * 0.001 * self.sr as f32).round() as usize).max(1)
+    }
+
+    fn update_state(&mut self) {
+        self.state.clear();
+        for i in 0..self.taps.len() {
+            let t = self.taps[i];
+            // pan -1 (left) .. 1 (right) -> angle 0 .. pi/2
+            let (sin, cos) = ((t.pan.clamp(-1.0, 1.0) + 1.0) * FRAC_PI_4).sin_cos();
+            self.state.push(Tap { frames: self.frames(t.delay_ms), gain: db_to_lin(t.gain_db), left: cos, right: sin });
+        }
+        let longest = self.state.iter().map(|t| t.frames).max().unwrap_or(1);
+        self.line.set_delay(longest);
+    }
+}
+
+fn db_to_lin(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+impl Effect for MultitapDelay {
+    fn name(&self) -> &str {
+        "MultitapDelay"
+    }
+
+    fn prepare(&mut self, sr: u32, channels: u16) {
+        self.sr = sr;
+        self.stereo = channels == 2;
+        let longest = self.taps.iter().map(|t| self.frames(t.delay_ms)).max().unwrap_or(1);
+        self.line.prepare(channels, longest);
+        self.update_state();
+    }
+    fn set_param_db(&mut self, key: &str, value: f32) {
+        // `dry_db`, or `tap<i>_delay_ms` / `tap<i>_gain_db` / `tap<i>_pan` for an existing tap.
+        if key == "dry_db" {
+            self.set_dry_db(value);
+            return;
+        }
+        let Some((index, field)) = key.strip_prefix("tap").and_then(|k| k.split_once('_')) else { return };
+        let Ok(index) = index.parse::<usize>() else { return };
+        let Some(mut tap) = self.taps.get(index).copied() else { return };
+        match field {
+            "delay_ms" => tap.delay_ms = value,
+            "gain_db" => tap.gain_db = value,
+            "pan" => tap.pan = value,
+            _ => return,
+        }
+        self.set_tap(index, tap);
+    }
+    fn param_descriptors(&self) -> &[ParamDescriptor] {
+        PARAMS
+    }
+    fn process(&mut self, block: &mut [f32]) {
+        if self.line.is_empty() {
+            return;
+        }
+        let ch = self.line.channels();
+        // Taps are read before the frame is written, so the longest one can use the whole line.
+        for frame in block.chunks_exact_mut(ch) {
+            if self.stereo {
+                let (mut l, mut r) = (0.0, 0.0);
+                for t in &self.state {
+                    let mono = 0.5 * (self.line.read_at(0, t.frames) + self.line.read_at(1, t.frames)) * t.gain;
+                    l += mono * t.left;
+                    r += mono * t.right;
+                }
+                self.line.write(0, frame[0]);
+                self.line.write(1, frame[1]);
+                frame[0] = frame[0] * self.dry + l;
+                frame[1] = frame[1] * self.dry + r;
+            } else {
+                for (c, x) in frame.iter_mut().enumerate() {
+                    let wet: f32 = self.state.iter().map(|t| self.line.read_at(c, t.frames) * t.gain).sum();
+                    self.line.write(c, *x);
+                    *x = *x * self.dry + wet;
+                }
+            }
+            self.line.advance();
+        }
+    }
+    fn tail_samples(&self) -> u32 {
+        self.state.iter().map(|t| t.frames).max().unwrap_or(0) as u32
+    }
+    fn clone_fresh(&self) -> Option<Box<dyn Effect>> {
+        Some(Box::new(MultitapDelay::new(self.max_taps, self.taps.clone(), self.dry_db)))
+    }
+    fn memory_usage_bytes(&self) -> usize {
+        self.line.memory_bytes() + vec_bytes(&self.taps) + vec_bytes(&self.state)
+    }
+}
@@ -0,0 +1,47 @@
+use super::effect::{Effect, ParamDescriptor};
+use super::utils::{normalize_block_to_peak, normalize_block_to_rms};
+
+const PARAMS: &[ParamDescriptor] = &[ParamDescriptor::new("target_db", -1.0)];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NormalizeMode {
+    Peak,
+    Rms,
+}
+
+/// Per-block loudness correction: each block is scaled on its own, so this
+/// pumps on dynamic material. Meant for quiet sources where an AGC is overkill.
+pub struct NormalizeBlock {
+    pub target_db: f32,
+    pub mode: NormalizeMode,
+}
+
+impl NormalizeBlock {
+    pub fn new(target_db: f32, mode: NormalizeMode) -> Self {
+        Self { target_db, mode }
+    }
+}
+
+impl Effect for NormalizeBlock {
+    fn name(&self) -> &str {
+        "NormalizeBlock"
+    }
+
+    fn set_param_db(&mut self, key: &str, db: f32) {
+        if key == "target_db" {
+            self.target_db = db;
+        }
+    }
+    fn param_descriptors(&self) -> &[ParamDescriptor] {
+        PARAMS
+    }
+    fn process(&mut self, block: &mut [f32]) {
+        match self.mode {
+            NormalizeMode::Peak => normalize_block_to_peak(block, self.target_db),
+            NormalizeMode::Rms => normalize_block_to_rms(block, self.target_db),
+        }
+    }
+    fn clone_fresh(&self) -> Option<Box<dyn Effect>> {
+        Some(Box::new(NormalizeBlock::new(self.target_db, self.mode)))
+    }
+}
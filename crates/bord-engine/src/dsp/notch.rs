@@ -0,0 +1,59 @@
+use super::biquad::{Biquad, Coeffs};
+use super::effect::{Effect, ParamDescriptor};
+
+const PARAMS: &[ParamDescriptor] = &[ParamDescriptor::new("center", 50.0), ParamDescriptor::new("q", 10.0)];
+
+/// Second-order band-reject centred on `center_hz`; higher `q` is narrower.
+pub struct Notch {
+    pub center_hz: f32,
+    pub q: f32,
+    sr: u32,
+    channels: u16, // 0 until prepared
+    filter: Biquad,
+}
+
+impl Notch {
+    pub fn new(center_hz: f32, q: f32) -> Self {
+        Self { center_hz, q, sr: 48_000, channels: 0, filter: Biquad::new() }
+    }
+    fn recompute(&mut self) {
+        self.filter.coeffs = Coeffs::notch(self.center_hz, self.q, self.sr);
+    }
+}
+
+impl Effect for Notch {
+    fn name(&self) -> &str {
+        "Notch"
+    }
+
+    fn prepare(&mut self, sr: u32, channels: u16) {
+        self.sr = sr;
+        self.channels = channels;
+        self.filter.prepare(channels);
+        self.recompute();
+    }
+    fn prepare_hot(&mut self, sr: u32, channels: u16) -> bool {
+        if (sr, channels) != (self.sr, self.channels) {
+            return false;
+        }
+        self.recompute();
+        true
+    }
+    fn set_param_db(&mut self, key: &str, value: f32) {
+        match key {
+            "center" => self.center_hz = value,
+            "q" => self.q = value,
+            _ => return,
+        }
+        self.recompute();
+    }
+    fn param_descriptors(&self) -> &[ParamDescriptor] {
+        PARAMS
+    }
+    fn process(&mut self, block: &mut [f32]) {
+        self.filter.process(block);
+    }
+    fn clone_fresh(&self) -> Option<Box<dyn Effect>> {
+        Some(Box::new(Notch::new(self.center_hz, self.q)))
+    }
+}
@@ -0,0 +1,79 @@
+use super::effect::Effect;
+use super::utils::vec_bytes;
+
+/// Runs each branch (a serial list of effects) on its own copy of the input
+/// and sums the results, e.g. a dry branch next to a heavily compressed one.
+/// Branch effects must keep the channel count. The branches are not delay
+/// compensated: `latency_samples` is the slowest branch's.
+pub struct Parallel {
+    branches: Vec<Vec<Box<dyn Effect>>>,
+    scratch: Vec<f32>,
+    sum: Vec<f32>,
+}
+
+impl Parallel {
+    pub fn new(branches: Vec<Vec<Box<dyn Effect>>>) -> Self {
+        Self { branches, scratch: Vec::new(), sum: Vec::new() }
+    }
+}
+
+impl Effect for Parallel {
+    fn name(&self) -> &str {
+        "Parallel"
+    }
+
+    fn prepare(&mut self, sr: u32, channels: u16) {
+        for fx in self.branches.iter_mut().flatten() {
+            fx.prepare(sr, channels);
+        }
+        // Room for typical blocks so the audio thread rarely grows these.
+        self.scratch = Vec::with_capacity(4096 * channels as usize);
+        self.sum = Vec::with_capacity(4096 * channels as usize);
+    }
+    fn set_param_db(&mut self, key: &str, value: f32) {
+        // Reaches every effect in every branch that knows `key`.
+        for fx in self.branches.iter_mut().flatten() {
+            fx.set_param_db(key, value);
+        }
+    }
+    fn process(&mut self, block: &mut [f32]) {
+        if self.branches.is_empty() {
+            return;
+        }
+        self.sum.clear();
+        self.sum.resize(block.len(), 0.0);
+        for branch in self.branches.iter_mut() {
+            self.scratch.clear();
+            self.scratch.extend_from_slice(block);
+            for fx in branch.iter_mut() {
+                fx.process(&mut self.scratch);
+            }
+            for (s, &x) in self.sum.iter_mut().zip(&self.scratch) {
+                *s += x;
+            }
+        }
+        block.copy_from_slice(&self.sum);
+    }
+    fn latency_samples(&self) -> u32 {
+        let branch = |b: &Vec<Box<dyn Effect>>| b.iter().map(|fx| fx.latency_samples()).sum::<u32>();
+        self.branches.iter().map(branch).max().unwrap_or(0)
+    }
+    fn branches(&self) -> &[Vec<Box<dyn Effect>>] {
+        &self.branches
+    }
+    fn tail_samples(&self) -> u32 {
+        let branch = |b: &Vec<Box<dyn Effect>>| b.iter().map(|fx| fx.tail_samples()).sum::<u32>();
+        self.branches.iter().map(branch).max().unwrap_or(0)
+    }
+    fn clone_fresh(&self) -> Option<Box<dyn Effect>> {
+        let branches = self
+            .branches
+            .iter()
+            .map(|branch| branch.iter().map(|fx| fx.clone_fresh()).collect::<Option<Vec<_>>>())
+            .collect::<Option<Vec<_>>>()?;
+        Some(Box::new(Parallel::new(branches)))
+    }
+    fn memory_usage_bytes(&self) -> usize {
+        self.branches.iter().flatten().map(|fx| fx.memory_usage_bytes()).sum::<usize>() + vec_bytes(&self.scratch) + vec_bytes(&self.sum)
+    }
+}
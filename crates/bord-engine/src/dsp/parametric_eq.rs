@@ -0,0 +1,75 @@
+use super::biquad::{Biquad, Coeffs};
+use super::effect::Effect;
+use serde::{Deserialize, Serialize};
+
+/// One peaking band of a `ParametricEq`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema-gen", derive(schemars::JsonSchema))]
+#[serde(deny_unknown_fields)]
+pub struct EqBand {
+    pub freq_hz: f32,
+    pub gain_db: f32,
+    pub q: f32,
+}
+
+/// Peaking bands in series, each a second-order section.
+pub struct ParametricEq {
+    bands: Vec<EqBand>,
+    filters: Vec<Biquad>,
+    sr: u32,
+}
+
+impl ParametricEq {
+    pub fn new(bands: Vec<EqBand>) -> Self {
+        let filters = bands.iter().map(|_| Biquad::new()).collect();
+        Self { bands, filters, sr: 48_000 }
+    }
+
+    pub fn bands(&self) -> &[EqBand] {
+        &self.bands
+    }
+
+    /// Replace band `index`; false if there is no such band. Coefficients are
+    /// recomputed without allocating.
+    pub fn set_band(&mut self, index: usize, band: EqBand) -> bool {
+        let Some(b) = self.bands.get_mut(index) else { return false };
+        *b = band;
+        self.filters[index].coeffs = Coeffs::peaking(band.freq_hz, band.q, band.gain_db, self.sr);
+        true
+    }
+}
+
+impl Effect for ParametricEq {
+    fn name(&self) -> &str {
+        "ParametricEq"
+    }
+
+    fn prepare(&mut self, sr: u32, channels: u16) {
+        self.sr = sr;
+        for (f, b) in self.filters.iter_mut().zip(&self.bands) {
+            f.prepare(channels);
+            f.coeffs = Coeffs::peaking(b.freq_hz, b.q, b.gain_db, sr);
+        }
+    }
+    fn set_param_db(&mut self, key: &str, value: f32) {
+        // `band<i>_freq_hz` / `band<i>_gain_db` / `band<i>_q` for an existing band.
+        let Some((index, field)) = key.strip_prefix("band").and_then(|k| k.split_once('_')) else { return };
+        let Ok(index) = index.parse::<usize>() else { return };
+        let Some(mut band) = self.bands.get(index).copied() else { return };
+        match field {
+            "freq_hz" => band.freq_hz = value,
+            "gain_db" => band.gain_db = value,
+            "q" => band.q = value,
+            _ => return,
+        }
+        self.set_band(index, band);
+    }
+    fn process(&mut self, block: &mut [f32]) {
+        for f in &mut self.filters {
+            f.process(block);
+        }
+    }
+    fn clone_fresh(&self) -> Option<Box<dyn Effect>> {
+        Some(Box::new(ParametricEq::new(self.bands.clone())))
+    }
+}
@@ -0,0 +1,62 @@
+use std::time::{Duration, Instant};
+
+/// Peak meter ballistics: holds the highest level for `hold_duration`,
+/// then falls at `decay_rate_db_per_sec` until a new peak arrives.
+/// Plain fields, no locking: owned and driven by a single thread.
+pub struct PeakHold {
+    pub hold_duration: Duration,
+    pub decay_rate_db_per_sec: f32,
+    peak_db: f32,
+    peak_at: Option<Instant>,
+    held_db: f32,
+}
+
+impl Default for PeakHold {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(2), 24.0)
+    }
+}
+
+impl PeakHold {
+    pub fn new(hold_duration: Duration, decay_rate_db_per_sec: f32) -> Self {
+        Self {
+            hold_duration,
+            decay_rate_db_per_sec,
+            peak_db: f32::NEG_INFINITY,
+            peak_at: None,
+            held_db: f32::NEG_INFINITY,
+        }
+    }
+
+    /// Feed the latest measured level (dBFS) taken at `now`.
+    pub fn update(&mut self, db: f32, now: Instant) {
+        self.held_db = self.decayed(now);
+        if db >= self.held_db {
+            self.peak_db = db;
+            self.peak_at = Some(now);
+            self.held_db = db;
+        }
+    }
+
+    /// Held level as of the last `update()`, in dBFS.
+    pub fn read(&self) -> f32 {
+        self.held_db
+    }
+
+    /// Drop the held value back to silence (-inf dB).
+    pub fn reset(&mut self) {
+        self.peak_db = f32::NEG_INFINITY;
+        self.peak_at = None;
+        self.held_db = f32::NEG_INFINITY;
+    }
+
+    fn decayed(&self, now: Instant) -> f32 {
+        let Some(at) = self.peak_at else { return self.held_db };
+        let since = now.saturating_duration_since(at);
+        if since <= self.hold_duration {
+            return self.peak_db;
+        }
+        let falling = (since - self.hold_duration).as_secs_f32();
+        self.peak_db - self.decay_rate_db_per_sec * falling
+    }
+}
@@ -0,0 +1,115 @@
+use super::delay_line::DelayLine;
+use super::effect::{Effect, ParamDescriptor};
+use super::smoothed::{SmoothedParam, DEFAULT_RAMP_S};
+
+const PARAMS: &[ParamDescriptor] = &[ParamDescriptor::new("delay_ms", 375.0), ParamDescriptor::new("feedback", 0.5), ParamDescriptor::new("mix", 0.35)];
+
+/// Highest `feedback`; beyond it the echoes barely decay.
+const MAX_FEEDBACK: f32 = 0.95;
+
+/// Stereo delay whose echoes alternate sides: the first on the right after
+/// `delay_ms`, the second on the left after twice that, and so on, each
+/// `feedback` quieter. Two cross-coupled lines: the input (summed to mono)
+/// enters the right one, whose output feeds the left one, which feeds back
+/// into the right. `mix` blends wet and dry (0 dry .. 1 wet). `feedback`
+/// ramps over 20 ms. Stereo only; other channel counts pass through untouched.
+pub struct PingpongDelay {
+    pub delay_ms: f32,
+    pub mix: f32,
+    feedback: SmoothedParam,
+    sr: u32,
+    stereo: bool,
+    line: DelayLine, // channel 0 feeds the left output, 1 the right
+}
+
+impl PingpongDelay {
+    pub fn new(delay_ms: f32, feedback: f32, mix: f32) -> Self {
+        Self {
+            delay_ms: delay_ms.max(0.0),
+            mix: mix.clamp(0.0, 1.0),
+            feedback: SmoothedParam::new(feedback.clamp(0.0, MAX_FEEDBACK)),
+            sr: 0,
+            stereo: false,
+            line: DelayLine::new(),
+        }
+    }
+
+    pub fn feedback(&self) -> f32 {
+        self.feedback.target()
+    }
+
+    pub fn set_feedback(&mut self, feedback: f32) {
+        self.feedback.set_target(feedback.clamp(0.0, MAX_FEEDBACK));
+    }
+
+    /// Real-time safe up to the delay the effect was prepared with; a longer
+    /// delay reallocates (and clears) the lines.
+    pub fn set_delay_ms(&mut self, delay_ms: f32) {
+        self.delay_ms = delay_ms.max(0.0);
+        if !self.line.is_empty() {
+            self.line.set_delay(self.delay_frames());
+        }
+    }
+
+    fn delay_frames(&self) -> usize {
+        ((self.delay_ms * 0.001 * self.sr as f32).round() as usize).max(1)
+    }
+}
+
+impl Effect for PingpongDelay {
+    fn name(&self) -> &str {
+        "PingpongDelay"
+    }
+
+    fn prepare(&mut self, sr: u32, channels: u16) {
+        self.sr = sr;
+        self.stereo = channels == 2;
+        if !self.stereo {
+            crate::bord_log!("PingpongDelay: needs 2 channels, got {channels}; passing through");
+            return;
+        }
+        self.line.prepare(2, self.delay_frames());
+        self.feedback.prepare(sr, DEFAULT_RAMP_S);
+    }
+    fn set_param_db(&mut self, key: &str, value: f32) {
+        match key {
+            "delay_ms" => self.set_delay_ms(value),
+            "feedback" => self.set_feedback(value),
+            "mix" => self.mix = value.clamp(0.0, 1.0),
+            _ => {}
+        }
+    }
+    fn param_descriptors(&self) -> &[ParamDescriptor] {
+        PARAMS
+    }
+    fn process(&mut self, block: &mut [f32]) {
+        if !self.stereo || self.line.is_empty() {
+            return;
+        }
+        let (wet, dry) = (self.mix, 1.0 - self.mix);
+        for frame in block.chunks_exact_mut(2) {
+            let fb = self.feedback.tick();
+            let (left, right) = (self.line.read(0), self.line.read(1));
+            self.line.write(0, right * fb);
+            self.line.write(1, 0.5 * (frame[0] + frame[1]) + left * fb);
+            self.line.advance();
+            frame[0] = frame[0] * dry + left * wet;
+            frame[1] = frame[1] * dry + right * wet;
+        }
+    }
+    /// Until the echoes are 60 dB down.
+    fn tail_samples(&self) -> u32 {
+        let d = self.delay_frames() as f32;
+        let g = self.feedback.target();
+        if g < 1e-3 {
+            return d as u32;
+        }
+        (d * (1.0 + 1e-3f32.ln() / g.ln())).ceil() as u32
+    }
+    fn clone_fresh(&self) -> Option<Box<dyn Effect>> {
+        Some(Box::new(PingpongDelay::new(self.delay_ms, self.feedback(), self.mix)))
+    }
+    fn memory_usage_bytes(&self) -> usize {
+        self.line.memory_bytes()
+    }
+}
@@ -0,0 +1,55 @@
+use super::effect::Effect;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+pub type EffectCtor = Box<dyn Fn() -> Box<dyn Effect> + Send + Sync>;
+
+/// Effect constructors by type name, for building chains from config.
+/// Lookups ignore case; the registered name is the canonical one and should
+/// match the effect's `name()`.
+#[derive(Default)]
+pub struct EffectRegistry {
+    ctors: HashMap<&'static str, EffectCtor>,
+}
+
+impl EffectRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Every effect `effect_by_name` knows, default-configured.
+    pub fn with_builtins() -> Self {
+        let mut reg = Self::new();
+        for &name in super::EFFECT_NAMES {
+            reg.register(name, Box::new(move || super::effect_by_name(name).expect("EFFECT_NAMES entry builds")));
+        }
+        reg
+    }
+
+    pub fn register(&mut self, name: &'static str, ctor: EffectCtor) {
+        self.ctors.insert(name, ctor);
+    }
+
+    /// The registered spelling of `name`.
+    pub fn canonical_name(&self, name: &str) -> Option<&'static str> {
+        self.ctors.get_key_value(name).map(|(&k, _)| k)
+            .or_else(|| self.ctors.keys().copied().find(|k| k.eq_ignore_ascii_case(name)))
+    }
+
+    /// Registered names, sorted.
+    pub fn names(&self) -> Vec<&'static str> {
+        let mut names: Vec<_> = self.ctors.keys().copied().collect();
+        names.sort_unstable();
+        names
+    }
+
+    pub fn create(&self, name: &str) -> Option<Box<dyn Effect>> {
+        self.canonical_name(name).map(|k| (self.ctors[k])())
+    }
+}
+
+/// The built-in effects, registered on first use.
+pub fn builtin_registry() -> &'static EffectRegistry {
+    static REGISTRY: OnceLock<EffectRegistry> = OnceLock::new();
+    REGISTRY.get_or_init(EffectRegistry::with_builtins)
+}
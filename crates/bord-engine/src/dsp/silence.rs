@@ -0,0 +1,77 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::SyncSender;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// RMS measurement window.
+const WINDOW_S: f32 = 0.05;
+/// How far above the threshold the input must rise to end a silent stretch.
+const HYSTERESIS_DB: f32 = 3.0;
+
+/// A silent stretch starting or ending, stamped on the audio thread.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SilenceEvent {
+    Started(Instant),
+    Ended(Instant),
+}
+
+/// Watches the chain input in 50 ms RMS windows. Below `threshold_db` it sets
+/// `suspended` and the chain outputs silence without running its effects; a
+/// window louder than `threshold_db + 3 dB` clears it again. Not an `Effect`:
+/// it sits in front of the chain without taking an effect index.
+pub struct SilenceDetector {
+    pub threshold_db: f32,
+    window: usize, // frames
+    channels: usize,
+    sum_sq: f64,
+    frames: usize,
+    suspended: Arc<AtomicBool>,
+    events: Option<SyncSender<SilenceEvent>>,
+}
+
+impl SilenceDetector {
+    pub fn new(threshold_db: f32, suspended: Arc<AtomicBool>, events: Option<SyncSender<SilenceEvent>>) -> Self {
+        Self { threshold_db, window: 1, channels: 1, sum_sq: 0.0, frames: 0, suspended, events }
+    }
+
+    pub fn prepare(&mut self, sr: u32, channels: u16) {
+        self.window = ((WINDOW_S * sr as f32) as usize).max(1);
+        self.channels = channels.max(1) as usize;
+        self.sum_sq = 0.0;
+        self.frames = 0;
+    }
+
+    pub fn is_suspended(&self) -> bool {
+        self.suspended.load(Ordering::Relaxed)
+    }
+
+    /// Feed one interleaved input block; decides at each completed window.
+    pub fn update(&mut self, block: &[f32]) {
+        for frame in block.chunks_exact(self.channels) {
+            self.sum_sq += frame.iter().map(|&s| (s as f64) * (s as f64)).sum::<f64>() / self.channels as f64;
+            self.frames += 1;
+            if self.frames == self.window {
+                let rms_db = 10.0 * (self.sum_sq / self.window as f64).max(1e-20).log10() as f32;
+                self.sum_sq = 0.0;
+                self.frames = 0;
+                self.decide(rms_db);
+            }
+        }
+    }
+
+    fn decide(&mut self, rms_db: f32) {
+        let suspended = self.is_suspended();
+        let event = if !suspended && rms_db < self.threshold_db {
+            SilenceEvent::Started(Instant::now())
+        } else if suspended && rms_db > self.threshold_db + HYSTERESIS_DB {
+            SilenceEvent::Ended(Instant::now())
+        } else {
+            return;
+        };
+        self.suspended.store(!suspended, Ordering::Relaxed);
+        if let Some(tx) = &self.events {
+            // Full means nobody is reading; dropping the event is fine.
+            let _ = tx.try_send(event);
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use super::effect::{Effect, Frame, ParamDescriptor, ProcessContext};
+use std::sync::Arc;
+
+const PARAMS: &[ParamDescriptor] = &[ParamDescriptor::new("threshold_db", -50.0), ParamDescriptor::new("min_gap_ms", 2000.0)];
+
+/// Called from the audio thread with a stream time in seconds; it must not
+/// allocate, lock or block (store to an atomic, `try_send` on a channel, ...).
+pub type GapCallback = Arc<dyn Fn(f64) + Send + Sync>;
+
+/// Pause finder, e.g. for podcast chapter markers. Passes audio through
+/// unchanged. A block is silent when every sample is below `threshold_db`;
+/// once consecutive silent blocks reach `min_gap_ms`, `on_gap` gets the time
+/// the silence began, and the first block with signal after that calls
+/// `on_resume` with its own start time. Times count from `prepare`, or follow
+/// `ProcessContext::sample_pos` when a chain supplies it.
+pub struct SilenceGap {
+    pub threshold_db: f32,
+    pub min_gap_ms: f32,
+    on_gap: GapCallback,
+    on_resume: Option<GapCallback>,
+    sr: u32,
+    channels: usize,
+    pos: u64,                  // frames
+    silent_since: Option<u64>, // frame the current silent run began
+    in_gap: bool,              // `on_gap` fired for the current run
+}
+
+impl SilenceGap {
+    pub fn new(threshold_db: f32, min_gap_ms: f32, on_gap: GapCallback) -> Self {
+        Self {
+            threshold_db,
+            min_gap_ms,
+            on_gap,
+            on_resume: None,
+            sr: 48_000,
+            channels: 1,
+            pos: 0,
+            silent_since: None,
+            in_gap: false,
+        }
+    }
+
+    /// Also call `on_resume` when signal returns after a reported gap.
+    pub fn with_on_resume(mut self, on_resume: GapCallback) -> Self {
+        self.on_resume = Some(on_resume);
+        self
+    }
+
+    fn seconds(&self, frame: u64) -> f64 {
+        frame as f64 / self.sr.max(1) as f64
+    }
+}
+
+impl Effect for SilenceGap {
+    fn name(&self) -> &str {
+        "SilenceGap"
+    }
+
+    fn prepare(&mut self, sr: u32, channels: u16) {
+        self.sr = sr;
+        self.channels = channels.max(1) as usize;
+        self.pos = 0;
+        self.silent_since = None;
+        self.in_gap = false;
+    }
+    fn set_param_db(&mut self, key: &str, value: f32) {
+        match key {
+            "threshold_db" => self.threshold_db = value,
+            "min_gap_ms" => self.min_gap_ms = value,
+            _ => {}
+        }
+    }
+    fn param_descriptors(&self) -> &[ParamDescriptor] {
+        PARAMS
+    }
+    fn process(&mut self, block: &mut [f32]) {
+        let start = self.pos;
+        self.pos += (block.len() / self.channels) as u64;
+        let threshold = 10f32.powf(self.threshold_db / 20.0);
+        if block.iter().all(|s| s.abs() < threshold) {
+            let since = *self.silent_since.get_or_insert(start);
+            let min_gap = (self.min_gap_ms.max(0.0) as f64 / 1000.0 * self.sr as f64) as u64;
+            if !self.in_gap && self.pos - since >= min_gap {
+                self.in_gap = true;
+                (self.on_gap)(self.seconds(since));
+            }
+        } else {
+            self.silent_since = None;
+            if core::mem::take(&mut self.in_gap) {
+                if let Some(on_resume) = &self.on_resume {
+                    on_resume(self.seconds(start));
+                }
+            }
+        }
+    }
+    fn process_with_context(&mut self, block: &mut [f32], ctx: &ProcessContext) {
+        if ctx.sample_pos != self.pos {
+            // A seek: neither a silent run nor a reported gap carries across it.
+            self.pos = ctx.sample_pos;
+            self.silent_since = None;
+            self.in_gap = false;
+        }
+        self.process(block);
+    }
+    fn process_frames_with_context(&mut self, frames: &mut [Frame<2>], ctx: &ProcessContext) {
+        self.process_with_context(Frame::as_interleaved_mut(frames), ctx);
+    }
+    fn clone_fresh(&self) -> Option<Box<dyn Effect>> {
+        let mut fresh = SilenceGap::new(self.threshold_db, self.min_gap_ms, self.on_gap.clone());
+        fresh.on_resume = self.on_resume.clone();
+        Some(Box::new(fresh))
+    }
+}
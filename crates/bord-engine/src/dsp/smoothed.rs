@@ -0,0 +1,68 @@
+/// Default ramp length for `SmoothedParam::prepare`.
+pub const DEFAULT_RAMP_S: f32 = 0.02;
+
+/// A parameter that moves to a new target in a linear ramp instead of
+/// jumping, so gain changes don't click. Call `tick()` once per frame.
+#[derive(Debug, Clone)]
+pub struct SmoothedParam {
+    current: f32,
+    target: f32,
+    step: f32,
+    remaining: u32, // frames left in the ramp
+    ramp_frames: u32,
+}
+
+impl SmoothedParam {
+    /// Starts settled at `value`, with no ramp until `prepare` sets its length.
+    pub fn new(value: f32) -> Self {
+        Self { current: value, target: value, step: 0.0, remaining: 0, ramp_frames: 0 }
+    }
+
+    /// Ramp over `ramp_s` seconds at `sr`; jumps straight to the target.
+    pub fn prepare(&mut self, sr: u32, ramp_s: f32) {
+        self.ramp_frames = (ramp_s * sr as f32) as u32;
+        self.current = self.target;
+        self.remaining = 0;
+    }
+
+    /// Ramp over `ramp_s` seconds from the next `set_target` on, without
+    /// disturbing one in progress.
+    pub fn set_ramp(&mut self, sr: u32, ramp_s: f32) {
+        self.ramp_frames = (ramp_s * sr as f32) as u32;
+    }
+
+    pub fn set_target(&mut self, target: f32) {
+        self.target = target;
+        if self.ramp_frames == 0 {
+            self.current = target;
+            self.remaining = 0;
+        } else {
+            self.step = (target - self.current) / self.ramp_frames as f32;
+            self.remaining = self.ramp_frames;
+        }
+    }
+
+    pub fn target(&self) -> f32 {
+        self.target
+    }
+
+    /// The value the last `tick` returned, without advancing the ramp.
+    pub fn current(&self) -> f32 {
+        self.current
+    }
+
+    pub fn is_settled(&self) -> bool {
+        self.remaining == 0
+    }
+
+    /// The value for the next frame.
+    #[inline]
+    pub fn tick(&mut self) -> f32 {
+        if self.remaining > 0 {
+            self.remaining -= 1;
+            // Land exactly on the target rather than accumulating rounding.
+            self.current = if self.remaining == 0 { self.target } else { self.current + self.step };
+        }
+        self.current
+    }
+}
@@ -0,0 +1,151 @@
+use super::effect::Effect;
+use super::file_source::read_wav;
+use super::utils::vec_bytes;
+use anyhow::Result;
+use parking_lot::Mutex;
+use rustfft::num_complex::Complex;
+use rustfft::{Fft, FftPlanner};
+use std::path::Path;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+/// Pass-through tap that keeps the most recent `fft_size` frames (mixed down
+/// to mono) for a `SpectrumReader` to transform. Like `Waveform`, the audio
+/// side publishes with `try_lock` and never runs the FFT itself.
+pub struct SpectrumAnalyzer {
+    history: Vec<f32>, // circular, oldest at `pos`
+    pos: usize,
+    channels: usize,
+    shared: Arc<Mutex<Vec<f32>>>,
+    sample_rate: Arc<AtomicU32>,
+}
+
+/// Control-thread side of a `SpectrumAnalyzer`: Hann-windowed magnitude
+/// spectrum of its latest frames, in dBFS per bin (a full-scale sine reads
+/// about 0).
+pub struct SpectrumReader {
+    shared: Arc<Mutex<Vec<f32>>>,
+    sample_rate: Arc<AtomicU32>,
+    fft: Arc<dyn Fft<f32>>,
+    window: Vec<f32>,
+    frames: Vec<f32>,
+    buf: Vec<Complex<f32>>,
+    db: Vec<f32>,
+}
+
+impl SpectrumAnalyzer {
+    /// `fft_size` is rounded up to a power of two (at least 64).
+    pub fn new(fft_size: usize) -> (Self, SpectrumReader) {
+        let n = fft_size.max(64).next_power_of_two();
+        let shared = Arc::new(Mutex::new(vec![0.0; n]));
+        let sample_rate = Arc::new(AtomicU32::new(48_000));
+        let tap = Self { history: vec![0.0; n], pos: 0, channels: 1, shared: shared.clone(), sample_rate: sample_rate.clone() };
+        (tap, SpectrumReader::new(shared, sample_rate, n))
+    }
+}
+
+impl SpectrumReader {
+    fn new(shared: Arc<Mutex<Vec<f32>>>, sample_rate: Arc<AtomicU32>, n: usize) -> Self {
+        let window: Vec<f32> = (0..n).map(|i| 0.5 - 0.5 * (std::f32::consts::TAU * i as f32 / n as f32).cos()).collect();
+        Self {
+            shared,
+            sample_rate,
+            fft: FftPlanner::new().plan_fft_forward(n),
+            window,
+            frames: Vec::with_capacity(n),
+            buf: vec![Complex::default(); n],
+            db: vec![-120.0; n / 2 + 1],
+        }
+    }
+
+    pub fn fft_size(&self) -> usize {
+        self.window.len()
+    }
+
+    /// Rate the tap was prepared at (48 kHz before that).
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate.load(Ordering::Relaxed)
+    }
+
+    /// Spectrum of the tap's latest frames: `fft_size / 2 + 1` bins from 0 Hz
+    /// to Nyquist, in dBFS, floored at -120.
+    pub fn spectrum(&mut self) -> &[f32] {
+        self.frames.clear();
+        self.frames.extend_from_slice(&self.shared.lock());
+        for ((c, &x), &w) in self.buf.iter_mut().zip(&self.frames).zip(&self.window) {
+            *c = Complex::new(x * w, 0.0);
+        }
+        self.fft.process(&mut self.buf);
+        // A sine of amplitude A peaks at A * N / 4: half of it lands in each
+        // half of the spectrum, and the Hann window's coherent gain is 0.5.
+        let scale = 4.0 / self.window.len() as f32;
+        for (db, c) in self.db.iter_mut().zip(&self.buf) {
+            *db = (20.0 * (c.norm() * scale).log10()).max(-120.0);
+        }
+        &self.db
+    }
+}
+
+impl Effect for SpectrumAnalyzer {
+    fn name(&self) -> &str {
+        "SpectrumAnalyzer"
+    }
+
+    fn prepare(&mut self, sr: u32, channels: u16) {
+        self.channels = channels.max(1) as usize;
+        self.sample_rate.store(sr, Ordering::Relaxed);
+    }
+
+    fn process(&mut self, block: &mut [f32]) {
+        let n = self.history.len();
+        for frame in block.chunks(self.channels) {
+            self.history[self.pos] = frame.iter().sum::<f32>() / frame.len() as f32;
+            self.pos = (self.pos + 1) % n;
+        }
+        if let Some(mut shared) = self.shared.try_lock() {
+            let (newer, older) = self.history.split_at(self.pos);
+            shared[..older.len()].copy_from_slice(older);
+            shared[older.len()..].copy_from_slice(newer);
+        }
+    }
+    fn memory_usage_bytes(&self) -> usize {
+        vec_bytes(&self.history)
+    }
+}
+
+/// Peak level of `spectrum` (bins at `sample_rate`) in each of `bands`
+/// log-spaced bands from `lo_hz` to `hi_hz`. A band narrower than a bin takes
+/// the bin its centre falls in.
+pub fn log_bands(spectrum: &[f32], sample_rate: u32, bands: usize, lo_hz: f32, hi_hz: f32) -> Vec<f32> {
+    let bins = spectrum.len().saturating_sub(1).max(1);
+    let hz_per_bin = sample_rate as f32 / 2.0 / bins as f32;
+    let ratio = (hi_hz / lo_hz).powf(1.0 / bands.max(1) as f32);
+    let bin_of = |hz: f32| ((hz / hz_per_bin).round() as usize).min(bins);
+    (0..bands)
+        .map(|b| {
+            let (f0, f1) = (lo_hz * ratio.powi(b as i32), lo_hz * ratio.powi(b as i32 + 1));
+            let (b0, b1) = (bin_of(f0), bin_of(f1));
+            if b1 > b0 {
+                spectrum[b0..b1].iter().copied().fold(f32::NEG_INFINITY, f32::max)
+            } else {
+                spectrum[bin_of((f0 * f1).sqrt())]
+            }
+        })
+        .collect()
+}
+
+/// Spectra of a WAV file, one every `1 / per_second` s of audio, as
+/// `SpectrumReader::spectrum` would show while it played. Returns the file's
+/// sample rate with them.
+pub fn wav_spectra(path: &Path, fft_size: usize, per_second: f32) -> Result<(u32, Vec<Vec<f32>>)> {
+    let (samples, spec) = read_wav(path)?;
+    let (mut tap, mut reader) = SpectrumAnalyzer::new(fft_size);
+    tap.prepare(spec.sample_rate, spec.channels);
+    let hop = ((spec.sample_rate as f32 / per_second.max(0.1)) as usize).max(1) * spec.channels.max(1) as usize;
+    let mut spectra = Vec::with_capacity(samples.len() / hop + 1);
+    for chunk in samples.chunks(hop) {
+        tap.process(&mut chunk.to_vec());
+        spectra.push(reader.spectrum().to_vec());
+    }
+    Ok((spec.sample_rate, spectra))
+}
@@ -0,0 +1,211 @@
+use rubato::{
+    FftFixedIn, SincFixedIn, SincInterpolationParameters, SincInterpolationType, VecResampler, WindowFunction,
+};
+
+use super::effect::Effect;
+use super::utils::vec_bytes;
+
+/// Input frames per resampler call. Smaller means less latency, more overhead.
+const CHUNK: usize = 256;
+/// Room in the planar FIFOs beyond one chunk, so typical blocks never grow them.
+const FIFO_HEADROOM: usize = 8192;
+
+/// Resampling kernel. The sinc variants map onto `rubato::SincInterpolationType`
+/// and tolerate ratio changes; `Fft` is the cheapest for a fixed ratio.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SrcQuality {
+    Nearest,
+    Linear,
+    Quadratic,
+    Cubic,
+    Fft,
+}
+
+impl SrcQuality {
+    fn interpolation(self) -> Option<SincInterpolationType> {
+        match self {
+            SrcQuality::Nearest => Some(SincInterpolationType::Nearest),
+            SrcQuality::Linear => Some(SincInterpolationType::Linear),
+            SrcQuality::Quadratic => Some(SincInterpolationType::Quadratic),
+            SrcQuality::Cubic => Some(SincInterpolationType::Cubic),
+            SrcQuality::Fft => None,
+        }
+    }
+}
+
+/// Converts from `input_sr` to `output_sr` with `rubato`.
+///
+/// The block is sized for the faster of the two rates; a block adapter
+/// upstream has to provide that. Upsampling, the input is in its first
+/// `frames * input_sr / output_sr` frames and the output fills it; downsampling,
+/// the whole block is input and the output goes in the first
+/// `frames * output_sr / input_sr` frames, the rest zeroed. Rounding is carried
+/// between blocks. Output comes from a FIFO primed with two chunks of silence,
+/// so bursty chunk output never underruns. If `rubato` rejects the rates, the
+/// effect passes through.
+pub struct SampleRateConverter {
+    pub input_sr: u32,
+    pub output_sr: u32,
+    pub quality: SrcQuality,
+    channels: usize,
+    kernel: Option<Box<dyn VecResampler<f32>>>,
+    primed: usize, // frames of silence the output FIFO starts with
+    pending: f64,  // fractional input frames carried to the next block
+    fifo_in: Vec<Vec<f32>>,
+    fifo_out: Vec<Vec<f32>>,
+    chunk_out: Vec<Vec<f32>>,
+}
+
+impl SampleRateConverter {
+    pub fn new(input_sr: u32, output_sr: u32, quality: SrcQuality) -> Self {
+        Self {
+            input_sr,
+            output_sr,
+            quality,
+            channels: 0,
+            kernel: None,
+            primed: 0,
+            pending: 0.0,
+            fifo_in: Vec::new(),
+            fifo_out: Vec::new(),
+            chunk_out: Vec::new(),
+        }
+    }
+
+    fn build_kernel(&self) -> Option<Box<dyn VecResampler<f32>>> {
+        let (sr_in, sr_out, ch) = (self.input_sr as usize, self.output_sr as usize, self.channels);
+        match self.quality.interpolation() {
+            Some(interpolation) => {
+                let params = SincInterpolationParameters {
+                    sinc_len: 128,
+                    f_cutoff: 0.95,
+                    interpolation,
+                    oversampling_factor: 128,
+                    window: WindowFunction::BlackmanHarris2,
+                };
+                let ratio = sr_out as f64 / sr_in as f64;
+                let r = SincFixedIn::<f32>::new(ratio, 1.0, params, CHUNK, ch).ok()?;
+                Some(Box::new(r))
+            }
+            None => Some(Box::new(FftFixedIn::<f32>::new(sr_in, sr_out, CHUNK, 2, ch).ok()?)),
+        }
+    }
+
+    /// Resample whole chunks out of `fifo_in` onto the end of `fifo_out`.
+    fn drain_input(&mut self) {
+        let Some(r) = self.kernel.as_mut() else { return };
+        while self.fifo_in[0].len() >= r.input_frames_next() {
+            let Ok((used, made)) = r.process_into_buffer(&self.fifo_in, &mut self.chunk_out, None) else {
+                return;
+            };
+            for (fifo, out) in self.fifo_out.iter_mut().zip(&self.chunk_out) {
+                fifo.extend_from_slice(&out[..made]);
+            }
+            for fifo in &mut self.fifo_in {
+                fifo.drain(..used);
+            }
+        }
+    }
+
+    /// Resample a whole interleaved buffer at once, off the audio thread (e.g.
+    /// an impulse response). Call after `prepare()`; the result is aligned with
+    /// the input (no priming or filter delay) and
+    /// `frames * output_sr / input_sr` frames long. Leaves the streaming state
+    /// spent, so `prepare()` again before `process()`. `None` if `rubato`
+    /// rejected the rates.
+    pub fn convert_buffer(&mut self, input: &[f32]) -> Option<Vec<f32>> {
+        let ch = self.channels;
+        // rubato's sinc kernels look ahead within the chunk, so their output
+        // starts aligned; only the FFT kernel's reported delay is real here.
+        let kernel = self.kernel.as_ref()?;
+        let delay = if self.quality == SrcQuality::Fft { kernel.output_delay() } else { 0 };
+        if ch == 0 {
+            return Some(Vec::new());
+        }
+        let ratio = self.output_sr as f64 / self.input_sr as f64;
+        let frames_out = ((input.len() / ch) as f64 * ratio).round() as usize;
+        for frame in input.chunks_exact(ch) {
+            for (fifo, &s) in self.fifo_in.iter_mut().zip(frame) {
+                fifo.push(s);
+            }
+        }
+        // Enough silence to push the last input chunk and the filter delay through.
+        let flush = CHUNK + (delay as f64 / ratio).ceil() as usize;
+        for fifo in &mut self.fifo_in {
+            fifo.resize(fifo.len() + flush, 0.0);
+        }
+        self.drain_input();
+        let skip = self.primed + delay;
+        let mut out = vec![0.0; frames_out * ch];
+        for (c, fifo) in self.fifo_out.iter().enumerate() {
+            for (i, &s) in fifo.iter().skip(skip).take(frames_out).enumerate() {
+                out[i * ch + c] = s;
+            }
+        }
+        Some(out)
+    }
+}
+
+impl Effect for SampleRateConverter {
+    fn name(&self) -> &str {
+        "SampleRateConverter"
+    }
+
+    fn prepare(&mut self, _sr: u32, channels: u16) {
+        self.channels = channels as usize;
+        self.kernel = self.build_kernel();
+        self.pending = 0.0;
+        let Some(r) = self.kernel.as_ref() else { return };
+        let max_out = r.output_frames_max();
+        self.primed = 2 * max_out;
+        self.fifo_in = vec![Vec::with_capacity(CHUNK + FIFO_HEADROOM); self.channels];
+        self.fifo_out = vec![Vec::with_capacity(3 * max_out + FIFO_HEADROOM); self.channels];
+        for fifo in &mut self.fifo_out {
+            fifo.resize(self.primed, 0.0);
+        }
+        self.chunk_out = vec![vec![0.0; max_out]; self.channels];
+    }
+    fn process(&mut self, block: &mut [f32]) {
+        let ch = self.channels;
+        if self.kernel.is_none() || ch == 0 {
+            return;
+        }
+        let frames = block.len() / ch;
+        let (frames_in, frames_out) = if self.output_sr >= self.input_sr {
+            let exact = frames as f64 * self.input_sr as f64 / self.output_sr as f64 + self.pending;
+            let n = (exact.floor() as usize).min(frames);
+            self.pending = exact - n as f64;
+            (n, frames)
+        } else {
+            let exact = frames as f64 * self.output_sr as f64 / self.input_sr as f64 + self.pending;
+            let n = (exact.floor() as usize).min(frames);
+            self.pending = exact - n as f64;
+            (frames, n)
+        };
+
+        for frame in block[..frames_in * ch].chunks_exact(ch) {
+            for (fifo, &s) in self.fifo_in.iter_mut().zip(frame) {
+                fifo.push(s);
+            }
+        }
+        self.drain_input();
+
+        let ready = self.fifo_out[0].len().min(frames_out);
+        for (c, fifo) in self.fifo_out.iter_mut().enumerate() {
+            for (i, frame) in block.chunks_exact_mut(ch).enumerate() {
+                frame[c] = if i < ready { fifo[i] } else { 0.0 };
+            }
+            fifo.drain(..ready);
+        }
+    }
+    /// Filter group delay plus the silence the output FIFO is primed with.
+    fn latency_samples(&self) -> u32 {
+        self.kernel.as_ref().map_or(0, |r| (r.output_delay() + self.primed) as u32)
+    }
+    fn clone_fresh(&self) -> Option<Box<dyn Effect>> {
+        Some(Box::new(SampleRateConverter::new(self.input_sr, self.output_sr, self.quality)))
+    }
+    fn memory_usage_bytes(&self) -> usize {
+        self.fifo_in.iter().chain(&self.fifo_out).chain(&self.chunk_out).map(vec_bytes).sum()
+    }
+}
@@ -0,0 +1,70 @@
+use alloc::vec::Vec;
+
+#[cfg(not(feature = "std"))]
+use super::math::FloatExt;
+
+/// Blocks whose peak is below this are treated as silence and left alone.
+const SILENCE_DB: f32 = -120.0;
+
+fn db_to_lin(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+fn peak(block: &[f32]) -> f32 {
+    block.iter().fold(0.0f32, |m, s| m.max(s.abs()))
+}
+
+/// Bytes `v` has allocated, for `Effect::memory_usage_bytes`.
+pub(crate) fn vec_bytes<T>(v: &Vec<T>) -> usize {
+    v.capacity() * core::mem::size_of::<T>()
+}
+
+/// Scale `block` uniformly so its peak sits at `target_db` dBFS.
+pub fn normalize_block_to_peak(block: &mut [f32], target_db: f32) {
+    let peak = peak(block);
+    if peak < db_to_lin(SILENCE_DB) {
+        return;
+    }
+    apply(block, db_to_lin(target_db).min(1.0) / peak);
+}
+
+/// Scale `block` uniformly so its RMS sits at `target_db` dBFS, reduced if
+/// needed so the peak stays at or below 0 dBFS.
+pub fn normalize_block_to_rms(block: &mut [f32], target_db: f32) {
+    let peak = peak(block);
+    if peak < db_to_lin(SILENCE_DB) {
+        return;
+    }
+    let rms = (block.iter().map(|s| s * s).sum::<f32>() / block.len() as f32).sqrt();
+    apply(block, (db_to_lin(target_db) / rms).min(1.0 / peak));
+}
+
+fn apply(block: &mut [f32], gain: f32) {
+    for s in block.iter_mut() {
+        *s *= gain;
+    }
+}
+
+/// Run `f` with flush-to-zero and denormals-are-zero set, restoring the previous
+/// mode afterwards. Decaying filter and feedback state otherwise drifts into
+/// subnormals, which x86 handles many times slower. No-op wrapper elsewhere.
+#[inline]
+pub fn with_denormals_flushed<R>(f: impl FnOnce() -> R) -> R {
+    #[cfg(target_arch = "x86_64")]
+    {
+        #[allow(deprecated)] // _mm_getcsr/_mm_setcsr; SSE is always available on x86_64
+        unsafe {
+            use core::arch::x86_64::{_mm_getcsr, _mm_setcsr};
+            const FTZ_DAZ: u32 = 0x8040;
+            let saved = _mm_getcsr();
+            _mm_setcsr(saved | FTZ_DAZ);
+            let out = f();
+            _mm_setcsr(saved);
+            out
+        }
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    {
+        f()
+    }
+}
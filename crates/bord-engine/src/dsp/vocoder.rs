@@ -0,0 +1,126 @@
+use super::biquad::{Biquad, Coeffs};
+use super::effect::Effect;
+use super::utils::vec_bytes;
+use crate::ring::SpscRingF32;
+use anyhow::{anyhow, Result};
+use std::sync::Arc;
+
+const LOWEST_HZ: f32 = 100.0;
+const HIGHEST_HZ: f32 = 8000.0;
+const ATTACK_MS: f32 = 5.0;
+const RELEASE_MS: f32 = 30.0;
+
+struct Band {
+    modulator: Biquad,
+    carrier: Biquad,
+    env: f32,
+}
+
+/// Channel vocoder: the chain input is the modulator (voice) and the carrier
+/// (synth) arrives interleaved at the chain's channel count through `carrier`.
+/// Both are mixed to mono and split into log-spaced bands between 100 Hz and
+/// 8 kHz; each carrier band is scaled by the modulator band's envelope.
+/// The carrier is silent for blocks where the ring runs short.
+pub struct Vocoder {
+    carrier: Arc<SpscRingF32>,
+    bands: u8,
+    sr: u32,
+    channels: usize,
+    filters: Vec<Band>,
+    attack: f32,
+    release: f32,
+    carrier_buf: Vec<f32>,
+}
+
+impl Vocoder {
+    /// `bands` must be a power of two (16 or 32 are typical).
+    pub fn new(carrier: Arc<SpscRingF32>, bands: u8) -> Result<Self> {
+        let mut v = Self {
+            carrier,
+            bands: 16,
+            sr: 48_000,
+            channels: 1,
+            filters: Vec::new(),
+            attack: 0.0,
+            release: 0.0,
+            carrier_buf: Vec::new(),
+        };
+        v.set_bands(bands)?;
+        Ok(v)
+    }
+
+    /// Change the band count; rebuilds the filterbank if already prepared.
+    pub fn set_bands(&mut self, n: u8) -> Result<()> {
+        if !n.is_power_of_two() {
+            return Err(anyhow!("Vocoder band count must be a power of two, got {n}"));
+        }
+        self.bands = n;
+        if !self.filters.is_empty() {
+            self.build_filterbank();
+        }
+        Ok(())
+    }
+
+    fn build_filterbank(&mut self) {
+        let n = self.bands as usize;
+        let hi = HIGHEST_HZ.min(0.45 * self.sr as f32);
+        let ratio = (hi / LOWEST_HZ).powf(1.0 / n as f32);
+        // Each band spans one `ratio` step around its centre.
+        let q = ratio.sqrt() / (ratio - 1.0);
+        self.filters = (0..n)
+            .map(|i| {
+                let center = LOWEST_HZ * ratio.powf(i as f32 + 0.5);
+                let mut band = Band { modulator: Biquad::new(), carrier: Biquad::new(), env: 0.0 };
+                for f in [&mut band.modulator, &mut band.carrier] {
+                    f.prepare(1);
+                    f.coeffs = Coeffs::band_pass(center, q, self.sr);
+                }
+                band
+            })
+            .collect();
+    }
+}
+
+impl Effect for Vocoder {
+    fn name(&self) -> &str {
+        "Vocoder"
+    }
+
+    fn prepare(&mut self, sr: u32, channels: u16) {
+        self.sr = sr;
+        self.channels = channels.max(1) as usize;
+        let coeff = |ms: f32| (-1.0 / (ms * 0.001 * sr as f32)).exp();
+        self.attack = coeff(ATTACK_MS);
+        self.release = coeff(RELEASE_MS);
+        self.carrier_buf = vec![0.0; 4096 * self.channels];
+        self.build_filterbank();
+    }
+
+    fn process(&mut self, block: &mut [f32]) {
+        let ch = self.channels;
+        // Each band carries roughly 1/n of the energy; bring the sum back up.
+        let makeup = self.filters.len() as f32;
+        if self.carrier_buf.len() < block.len() {
+            self.carrier_buf.resize(block.len(), 0.0); // only for unusually large blocks
+        }
+        let carrier = &mut self.carrier_buf[..block.len()];
+        if !self.carrier.pop_into(carrier) {
+            carrier.fill(0.0);
+        }
+        for (frame, car) in block.chunks_exact_mut(ch).zip(carrier.chunks_exact(ch)) {
+            let m = frame.iter().sum::<f32>() / ch as f32;
+            let c = car.iter().sum::<f32>() / ch as f32;
+            let mut out = 0.0;
+            for b in self.filters.iter_mut() {
+                let level = b.modulator.tick(0, m).abs();
+                let k = if level > b.env { self.attack } else { self.release };
+                b.env = level + k * (b.env - level);
+                out += b.carrier.tick(0, c) * b.env;
+            }
+            frame.fill(out * makeup);
+        }
+    }
+    fn memory_usage_bytes(&self) -> usize {
+        vec_bytes(&self.filters) + vec_bytes(&self.carrier_buf)
+    }
+}
@@ -0,0 +1,101 @@
+use super::effect::Effect;
+use anyhow::{anyhow, Context, Result};
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::PathBuf;
+
+/// Passthrough sink that records every block to a 32-bit float WAV file.
+/// The file is created in `prepare()`; if that fails the error is stored,
+/// `process()` becomes a pure passthrough and `finish()` reports it.
+pub struct WavWriter {
+    path: PathBuf,
+    writer: Option<hound::WavWriter<BufWriter<File>>>,
+    error: Option<anyhow::Error>,
+    flush_every: usize, // frames between header updates
+    channels: usize,
+    unflushed: usize,
+}
+
+impl WavWriter {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            writer: None,
+            error: None,
+            flush_every: 48_000,
+            channels: 1,
+            unflushed: 0,
+        }
+    }
+
+    /// Error recorded while opening or writing, if any.
+    pub fn error(&self) -> Option<&anyhow::Error> {
+        self.error.as_ref()
+    }
+
+    /// Flush and close the file, surfacing any stored error.
+    pub fn finish(&mut self) -> Result<()> {
+        if let Some(e) = self.error.take() {
+            self.writer = None;
+            return Err(e);
+        }
+        match self.writer.take() {
+            Some(w) => w
+                .finalize()
+                .with_context(|| format!("Failed to finalize {}", self.path.display())),
+            None => Err(anyhow!("{} was never opened", self.path.display())),
+        }
+    }
+
+    fn write_block(&mut self, block: &[f32]) -> Result<()> {
+        let Some(w) = self.writer.as_mut() else { return Ok(()) };
+        for &s in block {
+            w.write_sample(s)?;
+        }
+        // Keep the header current so a killed process still leaves a readable file.
+        self.unflushed += block.len() / self.channels;
+        if self.unflushed >= self.flush_every {
+            self.unflushed = 0;
+            w.flush()?;
+        }
+        Ok(())
+    }
+}
+
+impl Effect for WavWriter {
+    fn name(&self) -> &str {
+        "WavWriter"
+    }
+
+    fn prepare(&mut self, sr: u32, channels: u16) {
+        let spec = hound::WavSpec {
+            channels,
+            sample_rate: sr,
+            bits_per_sample: 32,
+            sample_format: hound::SampleFormat::Float,
+        };
+        self.channels = channels.max(1) as usize;
+        self.flush_every = sr as usize;
+        self.unflushed = 0;
+        match hound::WavWriter::create(&self.path, spec) {
+            Ok(w) => {
+                self.writer = Some(w);
+                self.error = None;
+            }
+            Err(e) => {
+                self.writer = None;
+                self.error = Some(anyhow::Error::new(e).context(format!(
+                    "Failed to create {}",
+                    self.path.display()
+                )));
+            }
+        }
+    }
+
+    fn process(&mut self, block: &mut [f32]) {
+        if let Err(e) = self.write_block(block) {
+            self.writer = None;
+            self.error = Some(e);
+        }
+    }
+}
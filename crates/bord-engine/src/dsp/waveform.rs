@@ -0,0 +1,63 @@
+use super::effect::Effect;
+use super::utils::vec_bytes;
+use parking_lot::Mutex;
+use std::sync::Arc;
+
+/// Pass-through tap that keeps the most recent `len` frames (mixed down to
+/// mono) for display. The audio side publishes with `try_lock`, so a reader
+/// holding the snapshot only delays the next update by a block.
+pub struct Waveform {
+    history: Vec<f32>, // circular, oldest at `pos`
+    pos: usize,
+    channels: usize,
+    shared: Arc<Mutex<Vec<f32>>>,
+}
+
+/// Control-thread handle to a `Waveform`'s latest snapshot.
+#[derive(Clone)]
+pub struct WaveformReader {
+    shared: Arc<Mutex<Vec<f32>>>,
+}
+
+impl Waveform {
+    pub fn new(len: usize) -> (Self, WaveformReader) {
+        let len = len.max(1);
+        let shared = Arc::new(Mutex::new(vec![0.0; len]));
+        let tap = Self { history: vec![0.0; len], pos: 0, channels: 1, shared: shared.clone() };
+        (tap, WaveformReader { shared })
+    }
+}
+
+impl WaveformReader {
+    /// Copy the latest frames, oldest first, into `out`.
+    pub fn snapshot(&self, out: &mut Vec<f32>) {
+        out.clear();
+        out.extend_from_slice(&self.shared.lock());
+    }
+}
+
+impl Effect for Waveform {
+    fn name(&self) -> &str {
+        "Waveform"
+    }
+
+    fn prepare(&mut self, _sr: u32, channels: u16) {
+        self.channels = channels.max(1) as usize;
+    }
+
+    fn process(&mut self, block: &mut [f32]) {
+        let n = self.history.len();
+        for frame in block.chunks(self.channels) {
+            self.history[self.pos] = frame.iter().sum::<f32>() / frame.len() as f32;
+            self.pos = (self.pos + 1) % n;
+        }
+        if let Some(mut shared) = self.shared.try_lock() {
+            let (newer, older) = self.history.split_at(self.pos);
+            shared[..older.len()].copy_from_slice(older);
+            shared[older.len()..].copy_from_slice(newer);
+        }
+    }
+    fn memory_usage_bytes(&self) -> usize {
+        vec_bytes(&self.history)
+    }
+}
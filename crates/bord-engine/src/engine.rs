@@ -0,0 +1,1475 @@
+//! The cpal-backed `Engine`: device selection, stream setup and its control
+//! surfaces (IPC, MIDI, OSC). Needs the `std` feature.
+
+use crate::config::ChainSpec;
+use crate::dsp::channel_converter::ChannelConverter;
+use crate::dsp::convert::{convert_slice_to_f32, pop_converted};
+use crate::dsp::effect::Effect;
+use crate::dsp::gain::Gain;
+use crate::dsp::normalize::{NormalizeBlock, NormalizeMode};
+use crate::dsp::registry::builtin_registry;
+use crate::dsp::silence::{SilenceDetector, SilenceEvent};
+use crate::dsp::wav_writer::WavWriter;
+use crate::graph::Chain;
+use crate::ipc::RpcError;
+use crate::params::{ParamEvent, ParamId, ParamQueue};
+use crate::ring::{next_pow2, SpscRingF32};
+use crate::preset::{EffectPreset, Preset};
+use crate::{devices, dsp, graph, ipc, session};
+#[cfg(feature = "midi")]
+use crate::midi;
+#[cfg(feature = "osc")]
+use crate::osc;
+use anyhow::{anyhow, Context, Result};
+use cpal::traits::{DeviceTrait, StreamTrait};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc::Receiver;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[derive(Debug, Clone)]
+pub struct EngineConfig {
+    pub input_name: Option<String>,       // match by substring (case-insensitive)
+    pub output_name: Option<String>,
+    pub input_index: Option<usize>,       // explicit index from device list
+    pub output_index: Option<usize>,
+    pub monitor_name: Option<String>,     // also play the output on this device (by substring, no fallback)
+    pub sample_rate: Option<u32>,         // e.g., 48000
+    pub block_size: Option<u32>,          // frames per buffer (if backend supports)
+    pub channel_count: Option<u16>,       // override the output device's default channel count
+    pub channel_alignment: ChannelAlignmentPolicy, // when the devices' channel counts differ
+    pub gain_db: f32,                     // simple test effect
+    pub normalize_db: Option<f32>,        // per-block peak normalization of the output (dBFS)
+    pub silence_threshold_db: Option<f32>, // skip processing while the input RMS is below this (dBFS)
+    pub record_path: Option<PathBuf>,     // append a WavWriter sink to the chain
+    pub rt_priority: Option<u8>,          // SCHED_FIFO priority (1-99) for audio threads
+    pub exclusive_mode: bool,             // WASAPI exclusive mode (Windows); see `EXCLUSIVE_MODE_UNAVAILABLE`
+    pub asio_driver: Option<String>,      // open this ASIO driver (Windows); see `devices::list_asio_drivers`
+    pub pick_policy: PickPolicy,          // how name/index select devices
+    pub hosts: Vec<cpal::HostId>,         // hosts to search in order; empty = default host
+    pub chain: Option<ChainSpec>,         // effects from the config's `[chain]`, after the gain stage
+    pub max_chain_memory_bytes: Option<usize>, // refuse effects that would take the chain's heap past this
+}
+
+impl Default for EngineConfig {
+    fn default() -> Self {
+        Self {
+            input_name: None,
+            output_name: None,
+            input_index: None,
+            output_index: None,
+            monitor_name: None,
+            sample_rate: None,
+            block_size: None,
+            channel_count: None,
+            channel_alignment: ChannelAlignmentPolicy::default(),
+            gain_db: 0.0,
+            normalize_db: None,
+            silence_threshold_db: None,
+            record_path: None,
+            rt_priority: None,
+            exclusive_mode: false,
+            asio_driver: None,
+            pick_policy: PickPolicy::default(),
+            hosts: Vec::new(),
+            chain: None,
+            max_chain_memory_bytes: None,
+        }
+    }
+}
+
+/// What `Engine::validate` settled on: the stream configs and sample formats
+/// `start()` would open, and anything that may not behave as asked.
+#[derive(Debug, Clone)]
+pub struct StreamValidation {
+    pub input_config: cpal::StreamConfig,
+    pub output_config: cpal::StreamConfig,
+    pub input_format: cpal::SampleFormat,
+    pub output_format: cpal::SampleFormat,
+    pub warnings: Vec<String>,
+}
+
+/// Snapshot of a running engine, as reported over IPC by `bord status`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EngineStats {
+    pub running: bool,
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub chain: Vec<String>,
+    #[serde(default)]
+    pub latency_samples: Vec<u32>, // per chain entry
+    #[serde(default)]
+    pub memory_bytes: Vec<usize>, // per chain entry
+    #[serde(default)]
+    pub max_chain_memory_bytes: Option<usize>,
+    #[serde(default)]
+    pub params: Vec<BTreeMap<String, f32>>, // per chain entry: values set while running
+    #[serde(default)]
+    pub branches: Vec<Vec<Vec<graph::GraphNode>>>, // per chain entry: a parallel section's branches
+    pub rt_priority_applied: bool,
+    pub paused: bool,
+    pub underruns: u64, // output callbacks that found the ring short and played silence
+    #[serde(default)]
+    pub input_device: Option<String>,
+    #[serde(default)]
+    pub output_device: Option<String>,
+    #[serde(default)]
+    pub monitor_device: Option<String>,
+}
+
+pub struct Engine {
+    input_stream: Option<cpal::Stream>,
+    output_stream: Option<cpal::Stream>,
+    monitor_stream: Option<cpal::Stream>,
+    cfg: EngineConfig,
+    info: Arc<Mutex<EngineStats>>, // static part, filled in by start(); the chain itself lives in the callback
+    underruns: Arc<AtomicU64>,
+    rt_promoted: Arc<AtomicUsize>, // audio threads that accepted rt_priority
+    params: ParamQueue,
+    ipc: Option<ipc::Server>,
+    stop_requested: Arc<AtomicBool>, // set by the `stop` IPC method
+    front_effects: Vec<Box<dyn Effect>>, // consumed by the next start()
+    back_effects: Vec<Box<dyn Effect>>,  // likewise, appended after the built-in chain
+    #[cfg(feature = "midi")]
+    midi: midi::MidiInputs,
+    #[cfg(feature = "osc")]
+    osc: Option<osc::OscServer>,
+    input_latency_ns: Arc<AtomicU64>,  // capture -> callback, LATENCY_UNKNOWN until reported
+    output_latency_ns: Arc<AtomicU64>, // callback -> playback
+    sample_rate: Option<u32>,          // negotiated by start()
+    channels: Option<u16>,             // likewise
+    input_device_name: Option<String>, // devices start() picked
+    output_device_name: Option<String>,
+    block_frames: Arc<AtomicU32>,      // frames per output callback, 0 until known
+    processing_suspended: Arc<AtomicBool>, // set by the chain's SilenceDetector
+    silence_events: Option<Receiver<SilenceEvent>>,
+}
+
+impl Engine {
+    pub fn new(cfg: EngineConfig) -> Self {
+        Self {
+            input_stream: None,
+            output_stream: None,
+            monitor_stream: None,
+            cfg,
+            info: Arc::new(Mutex::new(EngineStats::default())),
+            underruns: Arc::new(AtomicU64::new(0)),
+            rt_promoted: Arc::new(AtomicUsize::new(0)),
+            params: ParamQueue::new(),
+            ipc: None,
+            stop_requested: Arc::new(AtomicBool::new(false)),
+            front_effects: Vec::new(),
+            back_effects: Vec::new(),
+            #[cfg(feature = "midi")]
+            midi: midi::MidiInputs::default(),
+            #[cfg(feature = "osc")]
+            osc: None,
+            input_latency_ns: Arc::new(AtomicU64::new(LATENCY_UNKNOWN)),
+            output_latency_ns: Arc::new(AtomicU64::new(LATENCY_UNKNOWN)),
+            sample_rate: None,
+            channels: None,
+            input_device_name: None,
+            output_device_name: None,
+            block_frames: Arc::new(AtomicU32::new(0)),
+            processing_suspended: Arc::new(AtomicBool::new(false)),
+            silence_events: None,
+        }
+    }
+
+    /// Run `fx` ahead of the built-in chain on the next `start()` only.
+    pub fn insert_effect_front(&mut self, fx: Box<dyn Effect>) {
+        self.front_effects.push(fx);
+    }
+
+    /// Run `fx` after the last built-in effect on the next `start()` only.
+    pub fn push_effect_back(&mut self, fx: Box<dyn Effect>) {
+        self.back_effects.push(fx);
+    }
+
+    /// Pick the devices and check the configs `start()` would open against
+    /// what they report supporting (sample rate, channels, buffer size, sample
+    /// format), without opening any stream.
+    pub fn validate(&self) -> Result<StreamValidation> {
+        let (in_dev, out_dev) = pick_devices(&self.cfg)?;
+        validate_config(&in_dev, &out_dev, &self.cfg)
+    }
+
+    /// Start a simple chain (Gain) on input -> output.
+    pub fn start(&mut self) -> Result<()> {
+        let (in_dev, out_dev) = pick_devices(&self.cfg)?;
+        let StreamValidation { input_config: in_cfg, output_config: out_cfg, input_format: in_fmt, output_format: out_fmt, .. } =
+            validate_config(&in_dev, &out_dev, &self.cfg).context("Stream configuration is not supported")?;
+        let sr = out_cfg.sample_rate.0;
+
+        // Capacity: choose a power-of-two ring >= 8 output buffers
+        let cap_frames = match out_cfg.buffer_size {
+            cpal::BufferSize::Fixed(n) => (n as usize) * 8,
+            _ => 4096,
+        };
+        let cap = next_pow2(cap_frames * out_cfg.channels as usize).max(1024);
+        let ring = Arc::new(SpscRingF32::with_capacity(cap));
+        let monitor = match pick_monitor(&self.cfg)? {
+            Some(dev) => {
+                let fmt = dev.default_output_config().context("No default monitor output config")?.sample_format();
+                let fmt = check_stream_config(&dev, false, &out_cfg, fmt, &mut Vec::new())?;
+                Some((dev, fmt, Arc::new(SpscRingF32::with_capacity(cap))))
+            }
+            None => None,
+        };
+
+        let chain = self.build_chain(sr, in_cfg.channels, out_cfg.channels)?;
+        if chain.output_channels() != out_cfg.channels {
+            return Err(anyhow!(
+                "The chain ends with {} channels but the output device was opened with {}",
+                chain.output_channels(),
+                out_cfg.channels
+            ));
+        }
+        *self.info.lock() = EngineStats {
+            running: true,
+            sample_rate: sr,
+            channels: out_cfg.channels,
+            chain: chain.effect_names().into_iter().map(String::from).collect(),
+            latency_samples: chain.effect_latencies(),
+            memory_bytes: chain.effect_memory_bytes(),
+            max_chain_memory_bytes: self.cfg.max_chain_memory_bytes,
+            params: vec![BTreeMap::new(); chain.effect_names().len()],
+            branches: chain.graph_nodes().into_iter().map(|n| n.branches).collect(),
+            input_device: in_dev.name().ok(),
+            output_device: out_dev.name().ok(),
+            monitor_device: monitor.as_ref().and_then(|(dev, ..)| dev.name().ok()),
+            ..EngineStats::default()
+        };
+        self.underruns.store(0, Ordering::Relaxed);
+        self.stop_requested.store(false, Ordering::Relaxed);
+        self.input_latency_ns.store(LATENCY_UNKNOWN, Ordering::Relaxed);
+        self.output_latency_ns.store(LATENCY_UNKNOWN, Ordering::Relaxed);
+        self.rt_promoted.store(0, Ordering::Relaxed);
+
+        // A fixed buffer size is known now; otherwise the first output callback reports it.
+        let fixed_frames = match out_cfg.buffer_size {
+            cpal::BufferSize::Fixed(n) => n,
+            cpal::BufferSize::Default => 0,
+        };
+        self.block_frames.store(fixed_frames, Ordering::Relaxed);
+
+        let shared = StreamShared {
+            chain,
+            underruns: self.underruns.clone(),
+            in_latency: self.input_latency_ns.clone(),
+            out_latency: self.output_latency_ns.clone(),
+            block_frames: self.block_frames.clone(),
+            rt_in: RtPromoter::new(self.cfg.rt_priority, self.rt_promoted.clone()),
+            rt_out: RtPromoter::new(self.cfg.rt_priority, self.rt_promoted.clone()),
+            monitor: monitor.as_ref().map(|(_, _, ring)| ring.clone()),
+        };
+        if self.cfg.exclusive_mode {
+            crate::bord_log!("Warning: {EXCLUSIVE_MODE_UNAVAILABLE}");
+        }
+        let (input_stream, output_stream) = build_streams(&in_dev, &out_dev, &in_cfg, &out_cfg, in_fmt, out_fmt, ring, shared)
+            .map_err(|e| device_in_use_hint(e, self.cfg.exclusive_mode))?;
+        let monitor_stream = match monitor {
+            Some((dev, fmt, ring)) => {
+                Some(build_monitor_stream(&dev, &out_cfg, fmt, ring).map_err(|e| device_in_use_hint(e, self.cfg.exclusive_mode))?)
+            }
+            None => None,
+        };
+
+        input_stream.play().context("Failed to play input stream")?;
+        output_stream.play().context("Failed to play output stream")?;
+        if let Some(stream) = &monitor_stream {
+            stream.play().context("Failed to play monitor stream")?;
+        }
+
+        self.input_stream  = Some(input_stream);
+        self.output_stream = Some(output_stream);
+        self.monitor_stream = monitor_stream;
+        self.sample_rate   = Some(sr);
+        self.channels      = Some(out_cfg.channels);
+        self.input_device_name  = in_dev.name().ok();
+        self.output_device_name = out_dev.name().ok();
+        Ok(())
+    }
+
+    /// The serial chain `start()` runs: a `ChannelConverter` if the devices'
+    /// channel counts differ, front effects, Gain, the optional normalizer and
+    /// recorder from the config, then back effects.
+    fn build_chain(&mut self, sr: u32, in_channels: u16, out_channels: u16) -> Result<Chain> {
+        let mut chain = Chain::new(sr, in_channels);
+        chain.set_param_queue(self.params.clone());
+        chain.set_realtime(true);
+        if let Some(frames) = self.cfg.block_size {
+            chain.set_block_size(frames);
+        }
+        chain.set_max_memory_bytes(self.cfg.max_chain_memory_bytes);
+        self.processing_suspended.store(false, Ordering::Relaxed);
+        self.silence_events = self.cfg.silence_threshold_db.map(|threshold_db| {
+            let (tx, rx) = std::sync::mpsc::sync_channel(SILENCE_EVENT_CAPACITY);
+            chain.set_silence_detector(SilenceDetector::new(threshold_db, self.processing_suspended.clone(), Some(tx)));
+            rx
+        });
+        if in_channels != out_channels {
+            chain.push(Box::new(ChannelConverter::new(out_channels)))?;
+        }
+        for fx in self.front_effects.drain(..) {
+            chain.push(fx)?;
+        }
+        chain.push(Box::new(Gain::new(self.cfg.gain_db)))?;
+        if let Some(spec) = &self.cfg.chain {
+            for (fx, mix) in spec.build(builtin_registry())? {
+                chain.push_with_mix(fx, mix)?;
+            }
+        }
+        if let Some(target_db) = self.cfg.normalize_db {
+            chain.push(Box::new(NormalizeBlock::new(target_db, NormalizeMode::Peak)))?;
+        }
+        if let Some(path) = &self.cfg.record_path {
+            // Fail early here; inside the chain a WavWriter can only store its error.
+            std::fs::File::create(path)
+                .with_context(|| format!("Cannot record to {}", path.display()))?;
+            chain.push(Box::new(WavWriter::new(path)))?;
+        }
+        for fx in self.back_effects.drain(..) {
+            chain.push(fx)?;
+        }
+        Ok(chain)
+    }
+
+    /// Save the config, chain layout and MIDI bindings to `path` (see `session`).
+    /// Before the first `start()` the chain saved is the one it would build.
+    pub fn save_session(&self, path: &Path) -> Result<()> {
+        let mut chain = self.info.lock().chain.clone();
+        // `start()` adds the converter; it isn't part of the saved chain.
+        if self.cfg.channel_alignment == ChannelAlignmentPolicy::InsertConverter && chain.first().is_some_and(|n| n == "ChannelConverter") {
+            chain.remove(0);
+        }
+        if chain.is_empty() {
+            chain = self.front_effects.iter().map(|fx| fx.name())
+                .chain(builtin_effect_names(&self.cfg))
+                .chain(self.back_effects.iter().map(|fx| fx.name()))
+                .map(String::from)
+                .collect();
+        }
+        let session = session::Session {
+            config: (&self.cfg).into(),
+            chain,
+            #[cfg(feature = "midi")]
+            midi: self.midi.session_bindings(),
+            #[cfg(not(feature = "midi"))]
+            midi: Vec::new(),
+        };
+        session.save(path)
+    }
+
+    /// Rebuild an engine from a file written by `save_session`, without starting it.
+    pub fn load_session(path: &Path) -> Result<Engine> {
+        let session = session::Session::load(path)?;
+        let cfg = session.config.to_engine_config()?;
+        let (front, back) = session.split_chain(&builtin_effect_names(&cfg))?;
+        let mut engine = Engine::new(cfg);
+        // `split_chain` checked that every name builds.
+        engine.front_effects.extend(front.into_iter().filter_map(dsp::effect_by_name));
+        engine.back_effects.extend(back.into_iter().filter_map(dsp::effect_by_name));
+        #[cfg(feature = "midi")]
+        for b in &session.midi {
+            let param_id = ParamId::new(&b.param)
+                .ok_or_else(|| anyhow!("MIDI binding parameter {:?} is longer than {} bytes", b.param, ParamId::MAX_LEN))?;
+            let binding = midi::MidiBinding { channel: b.channel, cc: b.cc, effect_index: b.effect_index, param_id, min: b.min, max: b.max };
+            engine.add_midi_binding(&b.device, binding)?;
+        }
+        #[cfg(not(feature = "midi"))]
+        if !session.midi.is_empty() {
+            return Err(anyhow!("Session has MIDI bindings but bord was built without the `midi` feature"));
+        }
+        Ok(engine)
+    }
+
+    /// Text diagram of the chain (see `graph::chain_ascii`). Before the first
+    /// `start()` it shows the chain `start()` would build, timed at the
+    /// configured sample rate (48 kHz if unset).
+    pub fn chain_ascii(&self) -> Result<String> {
+        let info = self.info.lock();
+        if info.running {
+            return Ok(graph::chain_ascii(&graph_nodes(&info), info.sample_rate));
+        }
+        let node = |name: &str, detail: String| graph::GraphNode { name: name.to_string(), detail, ..Default::default() };
+        let mut nodes: Vec<_> = self.front_effects.iter().map(|fx| graph::GraphNode::of(&**fx, false)).collect();
+        nodes.push(node("Gain", format!("db={}", self.cfg.gain_db)));
+        if let Some(spec) = &self.cfg.chain {
+            nodes.extend(spec.build(builtin_registry())?.iter().map(|(fx, mix)| graph::GraphNode::of(&**fx, *mix <= 0.0)));
+        }
+        if let Some(target_db) = self.cfg.normalize_db {
+            nodes.push(node("NormalizeBlock", format!("target_db={target_db}")));
+        }
+        if let Some(path) = &self.cfg.record_path {
+            nodes.push(node("WavWriter", path.display().to_string()));
+        }
+        nodes.extend(self.back_effects.iter().map(|fx| graph::GraphNode::of(&**fx, false)));
+        Ok(graph::chain_ascii(&nodes, self.cfg.sample_rate.unwrap_or(48_000)))
+    }
+
+    /// Replace the running chain with `preset`'s effects (see `preset`).
+    pub fn apply_preset(&self, preset: &Preset) -> Result<()> {
+        apply_preset(&self.info, &self.params, preset)
+    }
+
+    /// The running chain as a preset called `name`.
+    pub fn current_preset(&self, name: &str) -> Preset {
+        current_preset(&self.info.lock(), name)
+    }
+
+    pub fn stop(&mut self) {
+        self.input_stream  = None;
+        self.output_stream = None;
+        self.monitor_stream = None;
+        self.sample_rate   = None;
+        self.channels      = None;
+        self.input_device_name  = None;
+        self.output_device_name = None;
+        self.block_frames.store(0, Ordering::Relaxed);
+        self.info.lock().running = false;
+    }
+
+    /// Route CC messages from the MIDI input matching `device_name` to an effect parameter.
+    /// The port is opened on first use and stays open for the engine's lifetime.
+    #[cfg(feature = "midi")]
+    pub fn add_midi_binding(&mut self, device_name: &str, binding: midi::MidiBinding) -> Result<()> {
+        self.midi.add_binding(device_name, binding, &self.params)
+    }
+
+    /// Bind the next CC received on any MIDI input to `param_id` of the effect at
+    /// `effect_index`, scaled onto `range` (min, max). Opens all available inputs;
+    /// `on_learned` runs on the MIDI thread once the binding exists.
+    #[cfg(feature = "midi")]
+    pub fn start_midi_learn(
+        &mut self,
+        effect_index: usize,
+        param_id: ParamId,
+        range: (f32, f32),
+        on_learned: Box<dyn Fn(midi::MidiBinding) + Send>,
+    ) -> Result<()> {
+        self.midi.start_learn(effect_index, param_id, range, on_learned, &self.params)
+    }
+
+    /// Leave learn mode without binding anything.
+    #[cfg(feature = "midi")]
+    pub fn cancel_midi_learn(&self) {
+        self.midi.cancel_learn();
+    }
+
+    /// Queue for sending parameter changes to the running chain.
+    pub fn params(&self) -> &ParamQueue {
+        &self.params
+    }
+
+    /// True once both audio threads accepted the configured `rt_priority`.
+    pub fn rt_priority_applied(&self) -> bool {
+        self.stats().rt_priority_applied
+    }
+
+    /// Capture-to-callback latency as timestamped by the backend, once known.
+    /// Unlike the nominal buffer size this includes whatever hardware/driver
+    /// latency the backend reports (e.g. WASAPI exclusive, CoreAudio).
+    pub fn input_stream_latency(&self) -> Option<Duration> {
+        load_latency(&self.input_latency_ns)
+    }
+
+    /// Callback-to-playback latency as timestamped by the backend, once known.
+    pub fn output_stream_latency(&self) -> Option<Duration> {
+        load_latency(&self.output_latency_ns)
+    }
+
+    /// Input-to-output latency: both stream latencies plus the delay the
+    /// chain's effects add. Audio waiting in the ring between the callbacks
+    /// isn't counted. `None` until both streams have reported.
+    pub fn round_trip_latency(&self) -> Option<Duration> {
+        let streams = self.input_stream_latency()? + self.output_stream_latency()?;
+        let info = self.info.lock();
+        let chain_frames: u32 = info.latency_samples.iter().sum();
+        Some(streams + Duration::from_secs_f64(chain_frames as f64 / info.sample_rate.max(1) as f64))
+    }
+
+    /// Have the next `start()` use `preferred` frames per buffer, or the
+    /// nearest size both devices accept; returns the size chosen. Devices that
+    /// don't report their buffer sizes get `preferred`.
+    pub fn fit_block_size(&mut self, preferred: u32) -> Result<u32> {
+        let (in_dev, out_dev) = pick_devices(&self.cfg)?;
+        let mut frames = preferred;
+        let ranges = [devices::buffer_size_range(&in_dev, true), devices::buffer_size_range(&out_dev, false)];
+        if let [Some((in_min, in_max)), Some((out_min, out_max))] = ranges {
+            let (min, max) = (in_min.max(out_min), in_max.min(out_max));
+            if min > max {
+                return Err(anyhow!("The devices have no buffer size in common (input {in_min}-{in_max}, output {out_min}-{out_max} frames)"));
+            }
+            frames = frames.clamp(min, max);
+        } else if let Some((min, max)) = ranges[0].or(ranges[1]) {
+            frames = frames.clamp(min, max);
+        }
+        self.cfg.block_size = Some(frames);
+        Ok(frames)
+    }
+
+    /// Point the output at the device the input resolves to, so both ends of
+    /// the next `start()` are one physical device. False, leaving the output
+    /// as configured, if that device has no outputs.
+    pub fn loopback(&mut self) -> Result<bool> {
+        let (in_dev, _) = pick_devices(&self.cfg)?;
+        let has_outputs = in_dev.supported_output_configs().is_ok_and(|mut c| c.next().is_some());
+        if !has_outputs {
+            return Ok(false);
+        }
+        self.cfg.output_name = Some(in_dev.name().context("The input device has no name to match an output by")?);
+        self.cfg.output_index = None;
+        Ok(true)
+    }
+
+    /// Sample rate negotiated with the devices; `None` while stopped.
+    pub fn sample_rate(&self) -> Option<u32> {
+        self.sample_rate
+    }
+
+    /// Channels the output device was opened with, which the chain ends
+    /// with; `None` while stopped. Under `ChannelAlignmentPolicy::InsertConverter`
+    /// the input may have a different count.
+    pub fn channel_count(&self) -> Option<u16> {
+        self.channels
+    }
+
+    /// Same as `channel_count`.
+    pub fn channels(&self) -> Option<u16> {
+        self.channels
+    }
+
+    /// Name of the input device `start()` opened, which with the default pick
+    /// policy may be the host default rather than the one asked for.
+    pub fn input_device_name(&self) -> Option<String> {
+        self.input_device_name.clone()
+    }
+
+    /// Name of the output device `start()` opened.
+    pub fn output_device_name(&self) -> Option<String> {
+        self.output_device_name.clone()
+    }
+
+    /// Frames per output callback: the fixed `block_size` if one was requested,
+    /// else what the backend delivered in its latest callback. `None` until known.
+    pub fn block_size(&self) -> Option<u32> {
+        match self.block_frames.load(Ordering::Relaxed) {
+            0 => None,
+            n => Some(n),
+        }
+    }
+
+    /// True while `silence_threshold_db` has the chain skipping silent input.
+    pub fn processing_suspended(&self) -> bool {
+        self.processing_suspended.load(Ordering::Relaxed)
+    }
+
+    /// Silent stretches starting and ending since the last call (oldest first).
+    /// Empty unless `silence_threshold_db` is set.
+    pub fn silence_events(&self) -> Vec<SilenceEvent> {
+        self.silence_events.as_ref().map(|rx| rx.try_iter().collect()).unwrap_or_default()
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.output_stream.is_some()
+    }
+
+    pub fn stats(&self) -> EngineStats {
+        collect_stats(&self.info, &self.underruns, &self.rt_promoted)
+    }
+
+    /// Serve the JSON-RPC control protocol (see `ipc`) on `path` until the engine is dropped.
+    pub fn serve_ipc(&mut self, path: &Path) -> Result<()> {
+        let ctx = RpcContext {
+            info: self.info.clone(),
+            underruns: self.underruns.clone(),
+            rt_promoted: self.rt_promoted.clone(),
+            params: self.params.clone(),
+            stop_requested: self.stop_requested.clone(),
+        };
+        let server = ipc::Server::bind(path, move |method, params| ctx.dispatch(method, params))?;
+        self.ipc = Some(server);
+        Ok(())
+    }
+
+    /// Set once an IPC client called `stop`; the owner should then `stop()` the engine.
+    pub fn stop_requested(&self) -> bool {
+        self.stop_requested.load(Ordering::Acquire)
+    }
+
+    /// Accept OSC parameter messages on `addr` until the engine is dropped.
+    /// The returned server can take extra address mappings.
+    #[cfg(feature = "osc")]
+    pub fn serve_osc(&mut self, addr: &str) -> Result<&osc::OscServer> {
+        let server = osc::OscServer::bind(addr, self.params.clone())?;
+        Ok(self.osc.insert(server))
+    }
+
+    /// Pretty-print the running chain (nothing if stopped).
+    pub fn print_chain(&self) {
+        if !self.is_running() { return; }
+        for (i, name) in self.info.lock().chain.iter().enumerate() {
+            println!("{:>2}  {}", i, name);
+        }
+    }
+}
+
+/* ---------- stream setup (used by Engine::start) ---------- */
+
+/// Input and output devices for `cfg`: its hosts (or the default host),
+/// names/indices and pick policy.
+fn pick_devices(cfg: &EngineConfig) -> Result<(cpal::Device, cpal::Device)> {
+    let host = host_for(cfg)?;
+    let policy = &cfg.pick_policy;
+    // A side with no device given opens the ASIO driver.
+    let implied = cfg.asio_driver.as_deref();
+    let implied_for = |index: Option<usize>| implied.filter(|_| index.is_none());
+    let in_name = cfg.input_name.as_deref().or(implied_for(cfg.input_index));
+    let out_name = cfg.output_name.as_deref().or(implied_for(cfg.output_index));
+    let in_dev  = pick_device_with_policy(&host, true,  in_name, cfg.input_index, policy)?
+        .context("No input device matched (and no default available)")?;
+    let out_dev = pick_device_with_policy(&host, false, out_name, cfg.output_index, policy)?
+        .context("No output device matched (and no default available)")?;
+    Ok((in_dev, out_dev))
+}
+
+/// The monitor output named by `cfg`, if any. It is only ever the device
+/// asked for, never a default.
+fn pick_monitor(cfg: &EngineConfig) -> Result<Option<cpal::Device>> {
+    let Some(name) = cfg.monitor_name.as_deref() else { return Ok(None) };
+    pick_device_with_policy(&host_for(cfg)?, false, Some(name), None, &PickPolicy::NameOnly)?
+        .with_context(|| format!("No output device matched the monitor {name:?}"))
+        .map(Some)
+}
+
+/// The host(s) to search: ASIO alone when `asio_driver` is set, otherwise `hosts`.
+fn host_for(cfg: &EngineConfig) -> Result<devices::Host> {
+    if cfg.asio_driver.is_some() {
+        devices::Host::with_hosts(&[devices::asio_host_id().context(devices::ASIO_UNAVAILABLE)?])
+    } else if cfg.hosts.is_empty() {
+        Ok(devices::Host::default_host())
+    } else {
+        devices::Host::with_hosts(&cfg.hosts)
+    }
+}
+
+/// Why `EngineConfig::exclusive_mode` has no effect: cpal 0.15 opens every
+/// WASAPI stream in shared mode and has no way to ask for exclusive access.
+#[cfg(windows)]
+const EXCLUSIVE_MODE_UNAVAILABLE: &str =
+    "Exclusive mode was requested, but this build's audio backend can only open WASAPI devices in shared mode; using shared mode";
+#[cfg(not(windows))]
+const EXCLUSIVE_MODE_UNAVAILABLE: &str = "Exclusive mode only exists for WASAPI on Windows; ignored";
+
+/// `e` from building the streams, with a hint when the device was refused
+/// because another application holds it, as happens in exclusive mode.
+fn device_in_use_hint(e: anyhow::Error, exclusive_mode: bool) -> anyhow::Error {
+    match e.downcast_ref::<cpal::BuildStreamError>() {
+        Some(cpal::BuildStreamError::DeviceNotAvailable) if exclusive_mode => e.context(
+            "The device is in use: exclusive mode needs it to itself, so close other applications using it or run without --exclusive",
+        ),
+        _ => e,
+    }
+}
+
+/// Stream configs and sample formats for the devices: their defaults with
+/// `cfg`'s sample rate and block size applied (best-effort), the input at the
+/// output's rate, and channel counts aligned per `cfg.channel_alignment`.
+fn negotiate_config(
+    in_dev: &cpal::Device,
+    out_dev: &cpal::Device,
+    cfg: &EngineConfig,
+) -> Result<(cpal::StreamConfig, cpal::StreamConfig, cpal::SampleFormat, cpal::SampleFormat)> {
+    let in_cfg_any  = in_dev.default_input_config().context("No default input config")?;
+    let out_cfg_any = out_dev.default_output_config().context("No default output config")?;
+
+    let mut in_cfg  = in_cfg_any.config();
+    let mut out_cfg = out_cfg_any.config();
+
+    // Honor sample_rate/block_size if provided (best-effort)
+    if let Some(sr) = cfg.sample_rate {
+        in_cfg.sample_rate  = cpal::SampleRate(sr);
+        out_cfg.sample_rate = cpal::SampleRate(sr);
+    }
+    if let Some(bs) = cfg.block_size {
+        out_cfg.buffer_size = cpal::BufferSize::Fixed(bs);
+        in_cfg.buffer_size  = cpal::BufferSize::Fixed(bs);
+    }
+
+    if let Some(ch) = cfg.channel_count {
+        for (dev, want_input, side) in [(in_dev, true, "input"), (out_dev, false, "output")] {
+            let max = devices::max_channels(dev, want_input).unwrap_or(0);
+            if ch == 0 || ch > max {
+                return Err(anyhow!("{ch} channels requested, but the {side} device supports 1-{max}"));
+            }
+        }
+        out_cfg.channels = ch;
+        in_cfg.channels  = ch;
+    }
+
+    // Align channels/SR
+    match cfg.channel_alignment {
+        ChannelAlignmentPolicy::ForceOutputChannels => in_cfg.channels = out_cfg.channels,
+        ChannelAlignmentPolicy::ForceInputChannels => out_cfg.channels = in_cfg.channels,
+        ChannelAlignmentPolicy::InsertConverter => {}
+        ChannelAlignmentPolicy::RequireMatch if in_cfg.channels != out_cfg.channels => {
+            return Err(anyhow!(
+                "The input device has {} channels and the output device {}; set a channel count or another alignment policy",
+                in_cfg.channels,
+                out_cfg.channels
+            ));
+        }
+        ChannelAlignmentPolicy::RequireMatch => {}
+    }
+    in_cfg.sample_rate = out_cfg.sample_rate;
+
+    Ok((in_cfg, out_cfg, in_cfg_any.sample_format(), out_cfg_any.sample_format()))
+}
+
+/// `negotiate_config` checked against the devices' supported configurations.
+/// A sample format the device lacks at the negotiated rate and channel count
+/// is swapped for one it has; anything else unsupported is an error listing
+/// what the device does support.
+fn validate_config(in_dev: &cpal::Device, out_dev: &cpal::Device, cfg: &EngineConfig) -> Result<StreamValidation> {
+    let (in_cfg, out_cfg, in_fmt, out_fmt) = negotiate_config(in_dev, out_dev, cfg)?;
+    let mut warnings = Vec::new();
+    if cfg.exclusive_mode {
+        warnings.push(EXCLUSIVE_MODE_UNAVAILABLE.to_string());
+    }
+    let in_fmt = check_stream_config(in_dev, true, &in_cfg, in_fmt, &mut warnings)?;
+    let out_fmt = check_stream_config(out_dev, false, &out_cfg, out_fmt, &mut warnings)?;
+    Ok(StreamValidation { input_config: in_cfg, output_config: out_cfg, input_format: in_fmt, output_format: out_fmt, warnings })
+}
+
+/// The sample format to open `dev` with for `stream`, preferring `fmt`.
+fn check_stream_config(
+    dev: &cpal::Device,
+    want_input: bool,
+    stream: &cpal::StreamConfig,
+    fmt: cpal::SampleFormat,
+    warnings: &mut Vec<String>,
+) -> Result<cpal::SampleFormat> {
+    const FORMATS: [cpal::SampleFormat; 3] = [cpal::SampleFormat::F32, cpal::SampleFormat::I16, cpal::SampleFormat::U16];
+    let side = if want_input { "input" } else { "output" };
+    let name = dev.name().unwrap_or_else(|_| "<unknown>".to_string());
+    let ranges: Vec<cpal::SupportedStreamConfigRange> = if want_input {
+        dev.supported_input_configs().map(|c| c.collect()).unwrap_or_default()
+    } else {
+        dev.supported_output_configs().map(|c| c.collect()).unwrap_or_default()
+    };
+    if ranges.is_empty() {
+        warnings.push(format!("The {side} device {name:?} reports no supported configurations; they cannot be checked"));
+        return Ok(fmt);
+    }
+    let sr = stream.sample_rate;
+    let matching: Vec<_> = ranges
+        .iter()
+        .filter(|r| r.channels() == stream.channels && (r.min_sample_rate()..=r.max_sample_rate()).contains(&sr))
+        .filter(|r| FORMATS.contains(&r.sample_format()))
+        .collect();
+    let Some(range) = matching.iter().find(|r| r.sample_format() == fmt).or(matching.first()) else {
+        return Err(anyhow!(
+            "The {side} device {name:?} does not support {} ch at {} Hz in F32, I16 or U16; it supports: {}",
+            stream.channels,
+            sr.0,
+            devices::config_summaries(dev, want_input).join(", ")
+        ));
+    };
+    if range.sample_format() != fmt {
+        warnings.push(format!(
+            "The {side} device {name:?} has no {fmt:?} at {} ch, {} Hz; using {:?}",
+            stream.channels,
+            sr.0,
+            range.sample_format()
+        ));
+    }
+    if let cpal::BufferSize::Fixed(frames) = stream.buffer_size {
+        match *range.buffer_size() {
+            cpal::SupportedBufferSize::Range { min, max } if !(min..=max).contains(&frames) => {
+                return Err(anyhow!("The {side} device {name:?} takes {min}-{max} frames per buffer, not {frames}"));
+            }
+            cpal::SupportedBufferSize::Range { .. } => {}
+            cpal::SupportedBufferSize::Unknown => {
+                warnings.push(format!("The {side} device {name:?} does not report its buffer sizes; {frames} frames may be refused"));
+            }
+        }
+    }
+    Ok(range.sample_format())
+}
+
+/// What the stream callbacks own or share with the `Engine`.
+struct StreamShared {
+    chain: Chain,
+    underruns: Arc<AtomicU64>,
+    in_latency: Arc<AtomicU64>,
+    out_latency: Arc<AtomicU64>,
+    block_frames: Arc<AtomicU32>,
+    rt_in: RtPromoter,
+    rt_out: RtPromoter,
+    monitor: Option<Arc<SpscRingF32>>, // a copy of what goes to the output, for the monitor stream
+}
+
+/// Run an input block through `chain` and queue the result for the output
+/// callback, and the monitor's if there is one. With `wide` (the devices'
+/// channel counts differ and the chain starts with a `ChannelConverter`) the
+/// chain's output goes there; otherwise the block is processed in place.
+#[inline]
+fn process_and_push(chain: &mut Chain, block: &mut [f32], wide: &mut Option<Vec<f32>>, ring: &SpscRingF32, monitor: Option<&SpscRingF32>) {
+    let out: &[f32] = match wide {
+        Some(wide) => {
+            let frames = block.len() / chain.channels().max(1) as usize;
+            wide.resize(frames * chain.output_channels() as usize, 0.0);
+            chain.process_into(block, wide);
+            wide
+        }
+        None => {
+            chain.process(block);
+            block
+        }
+    };
+    let _ = ring.push_slice(out);
+    if let Some(monitor) = monitor {
+        // A full monitor ring (its device running slow) only drops monitor audio.
+        let _ = monitor.push_slice(out);
+    }
+}
+
+/// Build (but don't play) input -> chain -> `ring` -> output for the given formats.
+#[allow(clippy::too_many_arguments)]
+fn build_streams(
+    in_dev: &cpal::Device,
+    out_dev: &cpal::Device,
+    in_cfg: &cpal::StreamConfig,
+    out_cfg: &cpal::StreamConfig,
+    in_fmt: cpal::SampleFormat,
+    out_fmt: cpal::SampleFormat,
+    ring: Arc<SpscRingF32>,
+    shared: StreamShared,
+) -> Result<(cpal::Stream, cpal::Stream)> {
+    let StreamShared { chain, underruns, in_latency, out_latency, block_frames, mut rt_in, mut rt_out, monitor } = shared;
+    let channels = out_cfg.channels as usize;
+    let cap = ring.capacity();
+    let ring_tx = ring.clone();
+    let ring_rx = ring;
+
+    // Scratch buffer reused in the input callback (avoid allocs)
+    let mut scratch = Vec::<f32>::with_capacity(cap);
+    // Same for the f32 side of integer output callbacks; the ring never holds more than `cap`
+    let mut out_scratch = Vec::<f32>::with_capacity(cap);
+    // The chain's output, when it converts the input's channel count to the output's
+    let mut wide = (in_cfg.channels != out_cfg.channels).then(|| Vec::<f32>::with_capacity(cap));
+
+    /* --------- INPUT (format-specific) --------- */
+    let input_stream = match in_fmt {
+        cpal::SampleFormat::F32 => {
+            in_dev.build_input_stream::<f32, _, _>(
+                in_cfg,
+                {
+                    let mut chain = chain;
+                    let ring = ring_tx;
+                    move |data: &[f32], info: &cpal::InputCallbackInfo| {
+                        rt_in.once();
+                        let ts = info.timestamp();
+                        store_latency(&in_latency, ts.callback.duration_since(&ts.capture));
+                        // reuse scratch
+                        scratch.clear();
+                        scratch.extend_from_slice(data);
+                        process_and_push(&mut chain, &mut scratch, &mut wide, &ring, monitor.as_deref());
+                    }
+                },
+                move |err| crate::bord_log!("input stream error: {err}"),
+                None,
+            )?
+        }
+        cpal::SampleFormat::I16 => {
+            in_dev.build_input_stream::<i16, _, _>(
+                in_cfg,
+                {
+                    let mut chain = chain;
+                    let ring = ring_tx;
+                    move |data: &[i16], info: &cpal::InputCallbackInfo| {
+                        rt_in.once();
+                        let ts = info.timestamp();
+                        store_latency(&in_latency, ts.callback.duration_since(&ts.capture));
+                        convert_slice_to_f32(data, &mut scratch);
+                        process_and_push(&mut chain, &mut scratch, &mut wide, &ring, monitor.as_deref());
+                    }
+                },
+                move |err| crate::bord_log!("input stream error: {err}"),
+                None,
+            )?
+        }
+        cpal::SampleFormat::U16 => {
+            in_dev.build_input_stream::<u16, _, _>(
+                in_cfg,
+                {
+                    let mut chain = chain;
+                    let ring = ring_tx;
+                    move |data: &[u16], info: &cpal::InputCallbackInfo| {
+                        rt_in.once();
+                        let ts = info.timestamp();
+                        store_latency(&in_latency, ts.callback.duration_since(&ts.capture));
+                        convert_slice_to_f32(data, &mut scratch);
+                        process_and_push(&mut chain, &mut scratch, &mut wide, &ring, monitor.as_deref());
+                    }
+                },
+                move |err| crate::bord_log!("input stream error: {err}"),
+                None,
+            )?
+        }
+        other => return Err(anyhow!("Unsupported input format: {other:?}")),
+    };
+
+    /* --------- OUTPUT (format-specific) -------- */
+    let output_stream = match out_fmt {
+        cpal::SampleFormat::F32 => {
+            out_dev.build_output_stream::<f32, _, _>(
+                out_cfg,
+                move |out: &mut [f32], info: &cpal::OutputCallbackInfo| {
+                    rt_out.once();
+                    let ts = info.timestamp();
+                    store_latency(&out_latency, ts.playback.duration_since(&ts.callback));
+                    block_frames.store((out.len() / channels) as u32, Ordering::Relaxed);
+                    if !ring_rx.pop_into(out) {
+                        underruns.fetch_add(1, Ordering::Relaxed);
+                        out.fill(0.0);
+                    }
+                },
+                move |err| crate::bord_log!("output stream error: {err}"),
+                None,
+            )?
+        }
+        cpal::SampleFormat::I16 => {
+            out_dev.build_output_stream::<i16, _, _>(
+                out_cfg,
+                move |out: &mut [i16], info: &cpal::OutputCallbackInfo| {
+                    rt_out.once();
+                    let ts = info.timestamp();
+                    store_latency(&out_latency, ts.playback.duration_since(&ts.callback));
+                    block_frames.store((out.len() / channels) as u32, Ordering::Relaxed);
+                    if !pop_converted(&ring_rx, &mut out_scratch, out) {
+                        underruns.fetch_add(1, Ordering::Relaxed);
+                    }
+                },
+                move |err| crate::bord_log!("output stream error: {err}"),
+                None,
+            )?
+        }
+        cpal::SampleFormat::U16 => {
+            out_dev.build_output_stream::<u16, _, _>(
+                out_cfg,
+                move |out: &mut [u16], info: &cpal::OutputCallbackInfo| {
+                    rt_out.once();
+                    let ts = info.timestamp();
+                    store_latency(&out_latency, ts.playback.duration_since(&ts.callback));
+                    block_frames.store((out.len() / channels) as u32, Ordering::Relaxed);
+                    if !pop_converted(&ring_rx, &mut out_scratch, out) {
+                        underruns.fetch_add(1, Ordering::Relaxed);
+                    }
+                },
+                move |err| crate::bord_log!("output stream error: {err}"),
+                None,
+            )?
+        }
+        other => return Err(anyhow!("Unsupported output format: {other:?}")),
+    };
+
+    Ok((input_stream, output_stream))
+}
+
+/// Build (but don't play) a second output on `dev` playing what `ring` is fed:
+/// the routed signal, for monitoring. It opens with the main output's config;
+/// when the ring runs short it plays silence without counting an underrun.
+fn build_monitor_stream(dev: &cpal::Device, cfg: &cpal::StreamConfig, fmt: cpal::SampleFormat, ring: Arc<SpscRingF32>) -> Result<cpal::Stream> {
+    let mut scratch = Vec::<f32>::with_capacity(ring.capacity());
+    let stream = match fmt {
+        cpal::SampleFormat::F32 => dev.build_output_stream::<f32, _, _>(
+            cfg,
+            move |out: &mut [f32], _: &cpal::OutputCallbackInfo| {
+                if !ring.pop_into(out) {
+                    out.fill(0.0);
+                }
+            },
+            move |err| crate::bord_log!("monitor stream error: {err}"),
+            None,
+        )?,
+        cpal::SampleFormat::I16 => dev.build_output_stream::<i16, _, _>(
+            cfg,
+            move |out: &mut [i16], _: &cpal::OutputCallbackInfo| {
+                pop_converted(&ring, &mut scratch, out);
+            },
+            move |err| crate::bord_log!("monitor stream error: {err}"),
+            None,
+        )?,
+        cpal::SampleFormat::U16 => dev.build_output_stream::<u16, _, _>(
+            cfg,
+            move |out: &mut [u16], _: &cpal::OutputCallbackInfo| {
+                pop_converted(&ring, &mut scratch, out);
+            },
+            move |err| crate::bord_log!("monitor stream error: {err}"),
+            None,
+        )?,
+        other => return Err(anyhow!("Unsupported monitor format: {other:?}")),
+    };
+    Ok(stream)
+}
+
+/// Names of the effects `Engine::start` builds from `cfg`, in chain order.
+fn builtin_effect_names(cfg: &EngineConfig) -> Vec<&'static str> {
+    let mut names = vec!["Gain"];
+    if let Some(spec) = &cfg.chain {
+        names.extend(spec.names(builtin_registry()));
+    }
+    if cfg.normalize_db.is_some() {
+        names.push("NormalizeBlock");
+    }
+    if cfg.record_path.is_some() {
+        names.push("WavWriter");
+    }
+    names
+}
+
+/// What `start()` does when the input device's default channel count differs
+/// from the output's. With `EngineConfig::channel_count` set both devices are
+/// opened with that count instead and this doesn't apply.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema-gen", derive(schemars::JsonSchema))]
+pub enum ChannelAlignmentPolicy {
+    /// Open the input with the output's channel count.
+    #[default]
+    ForceOutputChannels,
+    /// Open the output with the input's channel count.
+    ForceInputChannels,
+    /// Open each device with its own count and start the chain with a
+    /// `ChannelConverter` from one to the other.
+    InsertConverter,
+    /// Refuse to start.
+    RequireMatch,
+}
+
+impl std::str::FromStr for ChannelAlignmentPolicy {
+    type Err = String;
+
+    /// The variant name, case-insensitive (e.g. "insertconverter").
+    fn from_str(s: &str) -> std::result::Result<Self, String> {
+        const ALL: [ChannelAlignmentPolicy; 4] = [
+            ChannelAlignmentPolicy::ForceOutputChannels,
+            ChannelAlignmentPolicy::ForceInputChannels,
+            ChannelAlignmentPolicy::InsertConverter,
+            ChannelAlignmentPolicy::RequireMatch,
+        ];
+        ALL.into_iter()
+            .find(|p| format!("{p:?}").eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("unknown channel alignment {s:?}; expected one of {ALL:?}"))
+    }
+}
+
+/* ---------- device picking (by name or index) ---------- */
+
+/// How `pick_device_with_policy` resolves `--in/--out` and `--in-idx/--out-idx`.
+/// Only `WithFallbackToDefault` may return a device the user didn't ask for;
+/// when neither a name nor an index is given the default is always used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PickPolicy {
+    IndexOnly,
+    NameOnly,
+    IndexThenName,
+    NameThenIndex,
+    WithFallbackToDefault(Box<PickPolicy>),
+}
+
+impl Default for PickPolicy {
+    /// Index, then name, then the host default.
+    fn default() -> Self {
+        PickPolicy::WithFallbackToDefault(Box::new(PickPolicy::IndexThenName))
+    }
+}
+
+pub fn pick_device_with_policy(
+    host: &devices::Host,
+    want_input: bool,
+    name_substr: Option<&str>,
+    index: Option<usize>,
+    policy: &PickPolicy,
+) -> Result<Option<cpal::Device>> {
+    if name_substr.is_none() && index.is_none() {
+        return Ok(if want_input { host.default_input_device() } else { host.default_output_device() });
+    }
+    let by_index = || match index {
+        Some(idx) => pick_by_index(host, want_input, idx),
+        None => Ok(None),
+    };
+    let by_name = || match name_substr {
+        Some(q) => pick_by_name(host, want_input, q),
+        None => Ok(None),
+    };
+    match policy {
+        PickPolicy::IndexOnly => by_index(),
+        PickPolicy::NameOnly => by_name(),
+        PickPolicy::IndexThenName => match by_index()? {
+            Some(dev) => Ok(Some(dev)),
+            None => by_name(),
+        },
+        PickPolicy::NameThenIndex => match by_name()? {
+            Some(dev) => Ok(Some(dev)),
+            None => by_index(),
+        },
+        PickPolicy::WithFallbackToDefault(inner) => {
+            match pick_device_with_policy(host, want_input, name_substr, index, inner)? {
+                Some(dev) => Ok(Some(dev)),
+                None => Ok(if want_input { host.default_input_device() } else { host.default_output_device() }),
+            }
+        }
+    }
+}
+
+/// `idx` counts only devices with the wanted direction, across all hosts.
+fn pick_by_index(host: &devices::Host, want_input: bool, idx: usize) -> Result<Option<cpal::Device>> {
+    Ok(host.all_devices().filter(|d| devices::supports_direction(d, want_input)).nth(idx))
+}
+
+/// First device whose name contains `q` (case-insensitive).
+fn pick_by_name(host: &devices::Host, want_input: bool, q: &str) -> Result<Option<cpal::Device>> {
+    let qn = q.to_lowercase();
+    Ok(host.all_devices().find(|d| {
+        d.name().unwrap_or_default().to_lowercase().contains(&qn) && devices::supports_direction(d, want_input)
+    }))
+}
+
+const LATENCY_UNKNOWN: u64 = u64::MAX;
+
+/// Silence start/end events kept for `Engine::silence_events`; more are dropped.
+const SILENCE_EVENT_CAPACITY: usize = 64;
+
+#[inline]
+fn store_latency(slot: &AtomicU64, latency: Option<Duration>) {
+    if let Some(d) = latency {
+        slot.store(d.as_nanos().min(LATENCY_UNKNOWN as u128 - 1) as u64, Ordering::Relaxed);
+    }
+}
+
+fn load_latency(slot: &AtomicU64) -> Option<Duration> {
+    match slot.load(Ordering::Relaxed) {
+        LATENCY_UNKNOWN => None,
+        ns => Some(Duration::from_nanos(ns)),
+    }
+}
+
+fn collect_stats(info: &Mutex<EngineStats>, underruns: &AtomicU64, rt_promoted: &AtomicUsize) -> EngineStats {
+    EngineStats {
+        underruns: underruns.load(Ordering::Relaxed),
+        rt_priority_applied: rt_promoted.load(Ordering::Relaxed) >= 2,
+        ..info.lock().clone()
+    }
+}
+
+/// Diagram nodes from the control-side view, with the parameters set while
+/// running as details. Pausing bypasses the whole chain.
+fn graph_nodes(info: &EngineStats) -> Vec<graph::GraphNode> {
+    let detail = |p: &BTreeMap<String, f32>| p.iter().map(|(k, v)| format!("{k}={v}")).collect::<Vec<_>>().join(", ");
+    info.chain
+        .iter()
+        .enumerate()
+        .map(|(i, name)| graph::GraphNode {
+            name: name.clone(),
+            detail: info.params.get(i).map(detail).unwrap_or_default(),
+            latency_samples: info.latency_samples.get(i).copied().unwrap_or(0),
+            bypassed: info.paused,
+            branches: info.branches.get(i).cloned().unwrap_or_default(),
+        })
+        .collect()
+}
+
+fn current_preset(info: &EngineStats, name: &str) -> Preset {
+    let effects = info.chain.iter().zip(&info.params)
+        .map(|(type_name, state)| EffectPreset { type_name: type_name.clone(), state: state.clone() })
+        .collect();
+    Preset { name: name.to_string(), effects }
+}
+
+/// Swap the running effects for the preset's in one block, each prepared
+/// here with its state already set.
+fn apply_preset(info: &Mutex<EngineStats>, params: &ParamQueue, preset: &Preset) -> Result<()> {
+    preset.check_buildable()?;
+    if preset.effects.len() > graph::MAX_EFFECTS {
+        return Err(anyhow!("Preset {:?} has more than {} effects", preset.name, graph::MAX_EFFECTS));
+    }
+    let mut info = info.lock();
+    if !info.running {
+        return Err(anyhow!("Engine is not running"));
+    }
+    let mut slots = Vec::with_capacity(preset.effects.len());
+    for p in &preset.effects {
+        // `check_buildable` passed, so every name builds.
+        let Some(fx) = dsp::effect_by_name(&p.type_name) else { continue };
+        let mut slot = graph::prepared_slot(fx, 1.0, info.sample_rate, info.channels);
+        for (key, &value) in &p.state {
+            slot.set_param_db(key, value);
+        }
+        slots.push(slot);
+    }
+    let added = slots.iter().map(|fx| fx.memory_usage_bytes()).sum();
+    if let Some(e) = memory_limit_error(&info, info.memory_bytes.iter().sum(), added) {
+        return Err(anyhow!("Preset {:?}: {e}", preset.name));
+    }
+    info.chain.clear();
+    info.latency_samples.clear();
+    info.memory_bytes.clear();
+    info.params.clear();
+    info.branches.clear();
+    for (fx, p) in slots.iter().zip(&preset.effects) {
+        info.chain.push(fx.name().to_string());
+        info.latency_samples.push(fx.latency_samples());
+        info.memory_bytes.push(fx.memory_usage_bytes());
+        info.params.push(p.state.clone());
+        info.branches.push(Vec::new());
+    }
+    params.replace_all(slots);
+    Ok(())
+}
+
+/// Why the running chain can't take `added` bytes of effects in place of
+/// `removed`, if it is over `max_chain_memory_bytes` then.
+fn memory_limit_error(info: &EngineStats, removed: usize, added: usize) -> Option<String> {
+    let limit = info.max_chain_memory_bytes?;
+    let bytes = info.memory_bytes.iter().sum::<usize>() - removed + added;
+    (bytes > limit).then(|| format!("the chain would hold {bytes} bytes, over its {limit} byte limit"))
+}
+
+/* ---------- JSON-RPC dispatch ---------- */
+
+/// Handles the IPC server thread keeps on the engine; see `ipc` for the wire format.
+struct RpcContext {
+    info: Arc<Mutex<EngineStats>>,
+    underruns: Arc<AtomicU64>,
+    rt_promoted: Arc<AtomicUsize>,
+    params: ParamQueue,
+    stop_requested: Arc<AtomicBool>,
+}
+
+#[derive(Deserialize)]
+struct SetGainParams { db: f32 }
+
+#[derive(Deserialize)]
+struct SetEffectParamParams { index: usize, key: String, value: f32 }
+
+#[derive(Deserialize)]
+struct ResetEffectParamParams { index: usize, key: String }
+
+#[derive(Deserialize)]
+struct InsertEffectParams { effect: String, index: Option<usize>, mix: Option<f32> }
+
+#[derive(Deserialize)]
+struct RemoveEffectParams { index: usize }
+
+#[derive(Deserialize)]
+struct SwapEffectParams { index: usize, effect: String }
+
+#[derive(Deserialize)]
+struct GetPresetParams { name: String }
+
+impl RpcContext {
+    fn dispatch(&self, method: &str, params: serde_json::Value) -> Result<serde_json::Value, RpcError> {
+        use serde_json::{json, Value};
+        match method {
+            "getStats" => {
+                let stats = collect_stats(&self.info, &self.underruns, &self.rt_promoted);
+                serde_json::to_value(stats).map_err(|e| RpcError::internal(e.to_string()))
+            }
+            "setGain" => {
+                let p: SetGainParams = ipc::parse_params(params)?;
+                let index = self.info.lock().chain.iter().position(|n| n == "Gain")
+                    .ok_or_else(|| RpcError::internal("no Gain effect in the running chain"))?;
+                self.set_param(index, "db", p.db)?;
+                Ok(Value::Null)
+            }
+            "setEffectParam" => {
+                let p: SetEffectParamParams = ipc::parse_params(params)?;
+                self.set_param(p.index, &p.key, p.value)?;
+                Ok(Value::Null)
+            }
+            "resetEffectParam" => {
+                let p: ResetEffectParamParams = ipc::parse_params(params)?;
+                let key = ParamId::new(&p.key)
+                    .ok_or_else(|| RpcError::invalid_params(format!("parameter key longer than {} bytes", ParamId::MAX_LEN)))?;
+                let mut info = self.running()?;
+                let Some(state) = info.params.get_mut(p.index) else {
+                    return Err(RpcError::invalid_params(format!("no effect at index {}", p.index)));
+                };
+                // Back to the default, which presets and sessions needn't store.
+                state.remove(&p.key);
+                self.params.send(ParamEvent::ResetParam { index: p.index, key });
+                Ok(Value::Null)
+            }
+            "insertEffect" => {
+                let p: InsertEffectParams = ipc::parse_params(params)?;
+                let fx = dsp::effect_by_name(&p.effect)
+                    .ok_or_else(|| RpcError::invalid_params(format!("unknown effect {:?}", p.effect)))?;
+                let mut info = self.running()?;
+                let index = p.index.unwrap_or(info.chain.len()).min(info.chain.len());
+                let fx = graph::prepared_slot(fx, p.mix.unwrap_or(1.0), info.sample_rate, info.channels);
+                if let Some(e) = memory_limit_error(&info, 0, fx.memory_usage_bytes()) {
+                    return Err(RpcError::invalid_params(e));
+                }
+                info.chain.insert(index, fx.name().to_string());
+                info.latency_samples.insert(index, fx.latency_samples());
+                info.memory_bytes.insert(index, fx.memory_usage_bytes());
+                info.params.insert(index, BTreeMap::new());
+                info.branches.insert(index, Vec::new());
+                self.params.send(ParamEvent::InsertEffect { index, fx });
+                Ok(json!({ "index": index }))
+            }
+            "removeEffect" => {
+                let p: RemoveEffectParams = ipc::parse_params(params)?;
+                let mut info = self.running()?;
+                if p.index >= info.chain.len() {
+                    return Err(RpcError::invalid_params(format!("no effect at index {}", p.index)));
+                }
+                info.chain.remove(p.index);
+                info.latency_samples.remove(p.index);
+                info.memory_bytes.remove(p.index);
+                info.params.remove(p.index);
+                info.branches.remove(p.index);
+                self.params.send(ParamEvent::RemoveEffect { index: p.index });
+                Ok(Value::Null)
+            }
+            "swapEffect" => {
+                let p: SwapEffectParams = ipc::parse_params(params)?;
+                let fx = dsp::effect_by_name(&p.effect)
+                    .ok_or_else(|| RpcError::invalid_params(format!("unknown effect {:?}", p.effect)))?;
+                let mut info = self.running()?;
+                if p.index >= info.chain.len() {
+                    return Err(RpcError::invalid_params(format!("no effect at index {}", p.index)));
+                }
+                let fx = graph::prepared_slot(fx, 1.0, info.sample_rate, info.channels);
+                if let Some(e) = memory_limit_error(&info, info.memory_bytes[p.index], fx.memory_usage_bytes()) {
+                    return Err(RpcError::invalid_params(e));
+                }
+                info.chain[p.index] = fx.name().to_string();
+                info.latency_samples[p.index] = fx.latency_samples();
+                info.memory_bytes[p.index] = fx.memory_usage_bytes();
+                info.params[p.index] = BTreeMap::new();
+                info.branches[p.index] = Vec::new();
+                // Nobody waits for the old slot: the chain retires it to the queue.
+                drop(self.params.swap_effect(p.index, fx));
+                Ok(Value::Null)
+            }
+            "listEffects" => Ok(json!(dsp::EFFECT_NAMES)),
+            "getChain" => {
+                let chain = self.info.lock().chain.clone();
+                Ok(Value::Array(
+                    chain.into_iter().enumerate().map(|(index, name)| json!({ "index": index, "name": name })).collect(),
+                ))
+            }
+            "getChainDot" => {
+                // Built from the control-side view; channel changes inside the chain aren't tracked here.
+                let info = self.info.lock();
+                let bypassed = info.paused;
+                let nodes = info.chain.iter().zip(&info.latency_samples).map(|(name, &latency_samples)| graph::DotNode {
+                    name,
+                    latency_samples,
+                    bypassed,
+                    channels: info.channels,
+                });
+                Ok(json!(graph::chain_dot(nodes, info.sample_rate, info.channels)))
+            }
+            "getChainAscii" => {
+                let info = self.info.lock();
+                Ok(json!(graph::chain_ascii(&graph_nodes(&info), info.sample_rate)))
+            }
+            "getPreset" => {
+                let p: GetPresetParams = ipc::parse_params(params)?;
+                let preset = current_preset(&*self.running()?, &p.name);
+                serde_json::to_value(preset).map_err(|e| RpcError::internal(e.to_string()))
+            }
+            "applyPreset" => {
+                let preset: Preset = ipc::parse_params(params)?;
+                apply_preset(&self.info, &self.params, &preset).map_err(|e| RpcError::invalid_params(format!("{e:#}")))?;
+                Ok(Value::Null)
+            }
+            "pause" | "resume" => {
+                let paused = method == "pause";
+                self.running()?.paused = paused;
+                self.params.send(ParamEvent::Pause(paused));
+                Ok(Value::Null)
+            }
+            "stop" => {
+                self.stop_requested.store(true, Ordering::Release);
+                Ok(Value::Null)
+            }
+            _ => Err(RpcError::method_not_found(method)),
+        }
+    }
+
+    fn running(&self) -> Result<parking_lot::MutexGuard<'_, EngineStats>, RpcError> {
+        let info = self.info.lock();
+        if !info.running {
+            return Err(RpcError::internal("engine is not running"));
+        }
+        Ok(info)
+    }
+
+    fn set_param(&self, index: usize, key: &str, db: f32) -> Result<(), RpcError> {
+        let key = ParamId::new(key)
+            .ok_or_else(|| RpcError::invalid_params(format!("parameter key longer than {} bytes", ParamId::MAX_LEN)))?;
+        let mut info = self.running()?;
+        let Some(state) = info.params.get_mut(index) else {
+            return Err(RpcError::invalid_params(format!("no effect at index {index}")));
+        };
+        state.insert(key.as_str().to_string(), db);
+        self.params.send(ParamEvent::SetParamDb { index, key, db });
+        Ok(())
+    }
+}
+
+/* ---------- real-time thread priority ---------- */
+
+/// Promotes the calling audio thread on its first callback; later calls are no-ops.
+struct RtPromoter {
+    priority: Option<u8>,
+    done: bool,
+    promoted: Arc<AtomicUsize>,
+}
+
+impl RtPromoter {
+    fn new(priority: Option<u8>, promoted: Arc<AtomicUsize>) -> Self {
+        Self { priority, done: priority.is_none(), promoted }
+    }
+
+    #[inline]
+    fn once(&mut self) {
+        if self.done { return; }
+        self.done = true;
+        if let Some(prio) = self.priority {
+            match set_current_thread_rt(prio) {
+                Ok(()) => { self.promoted.fetch_add(1, Ordering::Relaxed); }
+                Err(e) => crate::bord_log!("could not set real-time priority: {e}"),
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn set_current_thread_rt(prio: u8) -> std::result::Result<(), String> {
+    let param = libc::sched_param { sched_priority: prio.min(99) as libc::c_int };
+    // Safety: pthread_self() is always a valid handle for the calling thread.
+    let rc = unsafe { libc::pthread_setschedparam(libc::pthread_self(), libc::SCHED_FIFO, &param) };
+    if rc == 0 { Ok(()) } else { Err(std::io::Error::from_raw_os_error(rc).to_string()) }
+}
+
+#[cfg(target_os = "macos")]
+fn set_current_thread_rt(_prio: u8) -> std::result::Result<(), String> {
+    // macOS has no SCHED_FIFO for user threads; the closest is the interactive QoS class.
+    let rc = unsafe { libc::pthread_set_qos_class_self_np(libc::qos_class_t::QOS_CLASS_USER_INTERACTIVE, 0) };
+    if rc == 0 { Ok(()) } else { Err(std::io::Error::from_raw_os_error(rc).to_string()) }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn set_current_thread_rt(_prio: u8) -> std::result::Result<(), String> {
+    Err("not supported on this platform".into())
+}
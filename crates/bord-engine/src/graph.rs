@@ -1,24 +1,1104 @@
-use crate::dsp::effect::Effect;
+use crate::dsp::effect::{Effect, Frame, ProcessContext};
+use crate::dsp::mix::Mix;
+use crate::dsp::utils::with_denormals_flushed;
+#[cfg(feature = "std")]
+use crate::dsp::silence::SilenceDetector;
+#[cfg(feature = "std")]
+use crate::params::{ParamEvent, ParamQueue};
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::sync::mpsc::{SyncSender, TrySendError};
+#[cfg(feature = "std")]
+use crate::timing::{EffectTiming, Timing, TimingCallback};
+#[cfg(feature = "std")]
+use std::time::Instant;
 
-/// A serial chain of effects. Owns the effects.
+#[cfg(feature = "std")]
+pub use crate::node::{Node, TryLockNode};
+
+// Per-effect timing needs a clock, so there is none without `std`.
+#[cfg(feature = "std")]
+type TimingState = Option<Box<Timing>>;
+#[cfg(not(feature = "std"))]
+type TimingState = ();
+
+#[cfg(feature = "std")]
+#[inline]
+fn timed<R>(timing: &mut TimingState, index: usize, f: impl FnOnce() -> R) -> R {
+    match timing {
+        Some(t) => t.time(index, f),
+        None => f(),
+    }
+}
+#[cfg(not(feature = "std"))]
+#[inline]
+fn timed<R>(_timing: &mut TimingState, _index: usize, f: impl FnOnce() -> R) -> R {
+    f()
+}
+#[cfg(feature = "std")]
+fn end_timed_block(timing: &mut TimingState, effects: &[Slot]) {
+    if let Some(t) = timing {
+        t.end_block(effects);
+    }
+}
+#[cfg(not(feature = "std"))]
+fn end_timed_block(_timing: &mut TimingState, _effects: &[Slot]) {}
+
+/// Run one effect on `block`, whose first `input_len` samples are its input:
+/// in place, or from a copy in `scratch` if it isn't `in_place_safe`.
+#[inline]
+fn run_slot(fx: &mut Slot, block: &mut [f32], input_len: usize, scratch: &mut Vec<f32>, ctx: &ProcessContext) {
+    if fx.in_place_safe() {
+        fx.process_with_context(block, ctx);
+    } else {
+        scratch.clear();
+        scratch.extend_from_slice(&block[..input_len]);
+        fx.process_into(scratch, block);
+    }
+}
+
+/// Timing of `Chain::dry_run`, in microseconds per block.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy)]
+pub struct DryRunResult {
+    pub mean_us: f64,
+    pub max_us: f64,
+    /// Real time available per block at the given sample rate.
+    pub budget_us: f64,
+    /// Worst block finished inside the budget.
+    pub is_realtime_safe: bool,
+}
+
+/// An effect can't run at the chain's block size (see `Effect::supported_block_sizes`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockSizeError {
+    pub effect: String,
+    pub block_size: u32,
+    pub min: u32,
+    pub max: u32,
+}
+
+impl core::fmt::Display for BlockSizeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{} needs {}-{} frames per block, not {}", self.effect, self.min, self.max, self.block_size)
+    }
+}
+
+impl core::error::Error for BlockSizeError {}
+
+/// Why `Chain::push` refused an effect.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PushError {
+    BlockSize(BlockSizeError),
+    /// The prepared effect would take the chain past `Chain::set_max_memory_bytes`.
+    MemoryLimit { effect: String, bytes: usize, limit: usize },
+}
+
+impl core::fmt::Display for PushError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            PushError::BlockSize(e) => e.fmt(f),
+            PushError::MemoryLimit { effect, bytes, limit } => {
+                write!(f, "{effect} would bring the chain to {bytes} bytes, over its {limit} byte limit")
+            }
+        }
+    }
+}
+
+impl core::error::Error for PushError {}
+
+impl From<BlockSizeError> for PushError {
+    fn from(e: BlockSizeError) -> Self {
+        PushError::BlockSize(e)
+    }
+}
+
+fn check_block_size(fx: &dyn Effect, block_size: u32) -> Result<(), BlockSizeError> {
+    match fx.supported_block_sizes() {
+        Some((min, max)) if !(min..=max).contains(&block_size) => {
+            Err(BlockSizeError { effect: String::from(fx.name()), block_size, min, max })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// One chain position: an effect behind its wet/dry `Mix`.
+pub type Slot = Mix<Box<dyn Effect>>;
+
+/// Wrap and prepare `fx` off the audio thread, ready for `ParamEvent::InsertEffect`.
+pub fn prepared_slot(fx: Box<dyn Effect>, mix: f32, sample_rate: u32, channels: u16) -> Slot {
+    let mut slot = Mix::new(fx, mix);
+    slot.prepare(sample_rate, channels);
+    slot
+}
+
+/// One chain position as drawn by `to_dot`.
+pub struct DotNode<'a> {
+    pub name: &'a str,
+    pub latency_samples: u32,
+    pub bypassed: bool,
+    /// Channels leaving this node.
+    pub channels: u16,
+}
+
+/// Graphviz DOT for a serial chain `in -> nodes... -> out`; edges are labelled
+/// with the sample rate and channel count flowing along them.
+/// Render with `dot -Tsvg > chain.svg`.
+pub fn chain_dot<'a>(nodes: impl IntoIterator<Item = DotNode<'a>>, sample_rate: u32, channels: u16) -> String {
+    use core::fmt::Write;
+    let escape = |s: &str| s.replace('\\', "\\\\").replace('"', "\\\"");
+    let mut out = String::from("digraph chain {\n    rankdir=LR;\n    node [shape=box];\n");
+    out.push_str("    input [shape=circle, label=\"in\"];\n");
+    let mut prev = String::from("input");
+    let mut ch = channels;
+    for (i, n) in nodes.into_iter().enumerate() {
+        let id = format!("fx{i}");
+        let state = if n.bypassed { "\\nbypassed\", style=dashed" } else { "\"" };
+        let _ = writeln!(
+            out,
+            "    {id} [label=\"{i}: {}\\nlatency {} samples{state}];",
+            escape(n.name),
+            n.latency_samples
+        );
+        let _ = writeln!(out, "    {prev} -> {id} [label=\"{sample_rate} Hz, {ch} ch\"];");
+        prev = id;
+        ch = n.channels;
+    }
+    out.push_str("    output [shape=circle, label=\"out\"];\n");
+    let _ = writeln!(out, "    {prev} -> output [label=\"{sample_rate} Hz, {ch} ch\"];");
+    out.push_str("}\n");
+    out
+}
+
+/// One chain position as drawn by `to_ascii`. A parallel section lists its
+/// serial branches in `branches`.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub struct GraphNode {
+    pub name: String,
+    /// Shown in parentheses after the name, e.g. `db=-6`.
+    #[cfg_attr(feature = "std", serde(default))]
+    pub detail: String,
+    pub latency_samples: u32,
+    pub bypassed: bool,
+    #[cfg_attr(feature = "std", serde(default))]
+    pub branches: Vec<Vec<GraphNode>>,
+}
+
+impl GraphNode {
+    pub fn of(fx: &dyn Effect, bypassed: bool) -> Self {
+        let branch = |b: &Vec<Box<dyn Effect>>| b.iter().map(|fx| GraphNode::of(&**fx, bypassed)).collect();
+        Self {
+            name: String::from(fx.name()),
+            detail: String::new(),
+            latency_samples: fx.latency_samples(),
+            bypassed,
+            branches: fx.branches().iter().map(branch).collect(),
+        }
+    }
+}
+
+/// Text diagram of `[Input] → nodes... → [Output]` plus the total latency.
+/// Parallel sections become a box with one row per branch:
+///
+/// ```text
+/// [Input] → [Gain 0 smp] → ┬─ [LowPassFilter 0 smp] ─┬ → [Output]
+///                          └─ [Gain 0 smp] ──────────┘
+/// ```
+pub fn chain_ascii(nodes: &[GraphNode], sample_rate: u32) -> String {
+    let mut rows = if nodes.is_empty() { alloc::vec![String::from("→")] } else { ascii_serial(nodes) };
+    for (i, row) in rows.iter_mut().enumerate() {
+        let (head, tail) = match (i, nodes.is_empty()) {
+            (0, true) => ("[Input] ", " [Output]"),
+            (0, false) => ("[Input] → ", " → [Output]"),
+            _ => ("          ", ""),
+        };
+        row.insert_str(0, head);
+        row.push_str(tail);
+    }
+    let latency: u32 = nodes.iter().map(|n| n.latency_samples).sum();
+    let ms = latency as f64 * 1000.0 / sample_rate.max(1) as f64;
+    let mut out = String::new();
+    for row in rows {
+        out.push_str(row.trim_end());
+        out.push('\n');
+    }
+    out.push_str(&format!("Total latency: {latency} samples ({ms:.2} ms)\n"));
+    out
+}
+
+/// Rows of equal width, the signal path on the first.
+fn ascii_serial(nodes: &[GraphNode]) -> Vec<String> {
+    let mut rows = alloc::vec![String::new()];
+    for (i, node) in nodes.iter().enumerate() {
+        let block = ascii_node(node);
+        let width = rows[0].chars().count();
+        let (arrow, gap) = if i == 0 { ("", "") } else { (" → ", "   ") };
+        rows.resize(rows.len().max(block.len()), String::new());
+        for (r, row) in rows.iter_mut().enumerate() {
+            pad(row, width, ' ');
+            row.push_str(if r == 0 { arrow } else { gap });
+            if let Some(part) = block.get(r) {
+                row.push_str(part);
+            }
+        }
+    }
+    let width = rows.iter().map(|r| r.chars().count()).max().unwrap_or(0);
+    for row in &mut rows {
+        pad(row, width, ' ');
+    }
+    rows
+}
+
+fn ascii_node(node: &GraphNode) -> Vec<String> {
+    if node.branches.is_empty() {
+        let detail = if node.detail.is_empty() { String::new() } else { format!("({})", node.detail) };
+        let bypass = if node.bypassed { "BYPASS: " } else { "" };
+        return alloc::vec![format!("[{bypass}{}{detail} {} smp]", node.name, node.latency_samples)];
+    }
+    let branches: Vec<_> = node.branches.iter().map(|b| ascii_serial(b)).collect();
+    let width = branches.iter().flatten().map(|r| r.chars().count()).max().unwrap_or(0);
+    let last = branches.len() - 1;
+    let mut rows = Vec::new();
+    for (b, branch) in branches.into_iter().enumerate() {
+        for (r, mut row) in branch.into_iter().enumerate() {
+            let first = r == 0;
+            let (open, close) = match (first, b) {
+                (true, 0) if last == 0 => ("── ", "──"),
+                (true, 0) => ("┬─ ", "─┬"),
+                (true, b) if b == last => ("└─ ", "─┘"),
+                (true, _) => ("├─ ", "─┤"),
+                (false, b) if b == last => ("   ", "  "),
+                (false, _) => ("│  ", " │"),
+            };
+            if first {
+                row.truncate(row.trim_end().len());
+            }
+            pad(&mut row, width + 1, if first { '─' } else { ' ' });
+            rows.push(format!("{open}{row}{close}"));
+        }
+    }
+    if node.bypassed {
+        rows[0].insert_str(0, "BYPASS ");
+        for row in &mut rows[1..] {
+            row.insert_str(0, "       ");
+        }
+    }
+    rows
+}
+
+/// Extend `row` with `fill` to `width` characters; a trailing space before
+/// `─` fill is kept so boxes read `[Fx] ───`.
+fn pad(row: &mut String, width: usize, fill: char) {
+    let n = row.chars().count();
+    if n < width {
+        if fill == '─' {
+            row.push(' ');
+            row.extend(core::iter::repeat_n(fill, width - n - 1));
+        } else {
+            row.extend(core::iter::repeat_n(fill, width - n));
+        }
+    }
+}
+
+/// Chain capacity reserved up front so inserts on the audio thread don't reallocate.
+pub(crate) const MAX_EFFECTS: usize = 32;
+
+/// A popped slot fed silence until its tail has played, then sent on `reply`.
+#[cfg(feature = "std")]
+struct Draining {
+    slot: Slot,
+    remaining: u32, // frames
+    reply: SyncSender<Slot>,
+}
+
+/// A serial chain of effects. Owns the effects, each behind a wet/dry `Mix`.
 pub struct Chain {
-    effects: Vec<Box<dyn Effect>>,
+    effects: Vec<Slot>,
     channels: u16,
     sample_rate: u32,
+    block_size: Option<u32>, // frames, if the caller said
+    max_memory_bytes: Option<usize>,
+    bypass_all: bool,
+    paused: bool,
+    sample_pos: u64, // frames processed, for `ProcessContext`
+    realtime: bool,
+    #[cfg(feature = "std")]
+    params: Option<ParamQueue>,
+    #[cfg(feature = "std")]
+    retiring: Vec<Slot>, // removed slots waiting to go back to the control thread
+    #[cfg(feature = "std")]
+    draining: Vec<Draining>, // popped slots still ringing out
+    out_channels: u16,   // channel count after the last effect
+    reshapes: bool,      // some effect changes the channel count
+    in_place: bool,      // every effect is `in_place_safe`
+    work: Vec<f32>,      // scratch for reshaping chains
+    input: Vec<f32>,     // copy of the block for effects that aren't in-place safe
+    // Planar scratch for the stereo split path.
+    left: Vec<f32>,
+    right: Vec<f32>,
+    #[cfg(feature = "std")]
+    tail: Vec<f32>, // scratch for draining slots
+    #[cfg(feature = "std")]
+    silence: Option<SilenceDetector>,
+    timing: TimingState,
 }
 
 impl Chain {
     pub fn new(sample_rate: u32, channels: u16) -> Self {
-        Self { effects: Vec::new(), channels, sample_rate }
+        Self {
+            effects: Vec::with_capacity(MAX_EFFECTS),
+            channels,
+            sample_rate,
+            block_size: None,
+            max_memory_bytes: None,
+            bypass_all: false,
+            paused: false,
+            sample_pos: 0,
+            realtime: false,
+            #[cfg(feature = "std")]
+            params: None,
+            #[cfg(feature = "std")]
+            retiring: Vec::with_capacity(MAX_EFFECTS),
+            #[cfg(feature = "std")]
+            draining: Vec::with_capacity(MAX_EFFECTS),
+            out_channels: channels,
+            reshapes: false,
+            in_place: true,
+            work: Vec::new(),
+            input: Vec::new(),
+            left: Vec::with_capacity(4096),
+            right: Vec::with_capacity(4096),
+            #[cfg(feature = "std")]
+            tail: Vec::with_capacity(4096),
+            #[cfg(feature = "std")]
+            silence: None,
+            timing: TimingState::default(),
+        }
     }
-    pub fn push(&mut self, mut fx: Box<dyn Effect>) {
-        fx.prepare(self.sample_rate, self.channels);
-        self.effects.push(fx);
+    pub fn push(&mut self, fx: Box<dyn Effect>) -> Result<(), PushError> {
+        self.push_with_mix(fx, 1.0)
     }
-    /// Process one interleaved block in-place.
-    pub fn process(&mut self, block: &mut [f32]) {
+    /// Push `fx` blended at `mix` wet (0 = dry only, 1 = fully processed).
+    /// It is prepared with the channel count the chain has at that point.
+    /// Refused if `set_block_size` was called with a size `fx` doesn't support,
+    /// or if once prepared it would exceed `set_max_memory_bytes`.
+    pub fn push_with_mix(&mut self, fx: Box<dyn Effect>, mix: f32) -> Result<(), PushError> {
+        if let Some(block_size) = self.block_size {
+            check_block_size(&*fx, block_size)?;
+        }
+        let mut slot = prepared_slot(fx, mix, self.sample_rate, self.out_channels);
+        if let Some(frames) = self.block_size {
+            slot.set_max_block_frames(frames as usize);
+        }
+        if let Some(limit) = self.max_memory_bytes {
+            let bytes = self.total_memory_bytes() + slot.memory_usage_bytes();
+            if bytes > limit {
+                return Err(PushError::MemoryLimit { effect: String::from(slot.name()), bytes, limit });
+            }
+        }
+        self.effects.push(slot);
+        self.update_channels();
+        Ok(())
+    }
+    #[cfg(feature = "std")]
+    /// Push a shared effect; other handles from `Node::clone_handle` can go in
+    /// other chains. Prefer `push(Box::new(node.try_lock_handle()))` for a
+    /// chain on the audio thread (see `Node`).
+    pub fn push_node(&mut self, node: Node) -> Result<(), PushError> {
+        self.push(Box::new(node))
+    }
+    /// Heap the chain's effects may hold in total; `push` refuses an effect
+    /// that would go over it. Effects already in the chain are kept.
+    pub fn set_max_memory_bytes(&mut self, limit: Option<usize>) {
+        self.max_memory_bytes = limit;
+    }
+    /// Sum of the effects' `memory_usage_bytes`.
+    pub fn total_memory_bytes(&self) -> usize {
+        self.effects.iter().map(|fx| fx.memory_usage_bytes()).sum()
+    }
+    /// Frames per block the chain will be run with, so `push` can check it.
+    pub fn set_block_size(&mut self, frames: u32) {
+        self.block_size = Some(frames);
+        let frames_usize = frames as usize;
+        self.left.reserve(frames_usize.saturating_sub(self.left.len()));
+        self.right.reserve(frames_usize.saturating_sub(self.right.len()));
+        #[cfg(feature = "std")]
+        {
+            let samples = frames_usize * self.channels as usize;
+            self.tail.reserve(samples.saturating_sub(self.tail.len()));
+        }
         for fx in self.effects.iter_mut() {
-            fx.process(block);
+            fx.set_max_block_frames(frames_usize);
+        }
+    }
+    /// Check every effect accepts blocks of `block_size` frames.
+    pub fn validate_block_size(&self, block_size: u32) -> Result<(), BlockSizeError> {
+        self.effects.iter().try_for_each(|fx| check_block_size(fx, block_size))
+    }
+    #[cfg(feature = "std")]
+    /// Load a plugin shared library (see `plugin`) and push its effect.
+    pub fn push_plugin(&mut self, path: &std::path::Path) -> anyhow::Result<()> {
+        let fx = crate::plugin::PluginHost::load(path)?;
+        self.push(fx)?;
+        Ok(())
+    }
+    /// Push without preparing; pair with `prepare_all_async` for slow effects.
+    pub fn push_deferred(&mut self, fx: Box<dyn Effect>) {
+        self.effects.push(Mix::new(fx, 1.0));
+        self.update_channels();
+    }
+    /// Re-prepare every effect at (`sr`, `channels`), awaiting all async
+    /// prepares concurrently; other effects run `prepare()` inline.
+    #[cfg(feature = "async")]
+    pub async fn prepare_all_async(&mut self, sr: u32, channels: u16) -> anyhow::Result<()> {
+        self.sample_rate = sr;
+        self.channels = channels;
+        let mut pending = Vec::new();
+        let mut ch = channels;
+        let mut inputs = Vec::with_capacity(self.effects.len());
+        for fx in self.effects.iter_mut() {
+            if fx.as_async().is_none() {
+                fx.prepare(sr, ch);
+            } else {
+                fx.prepare_blend(ch);
+            }
+            inputs.push(ch);
+            ch = fx.output_channel_count(ch);
+        }
+        self.update_channels();
+        for (fx, ch) in self.effects.iter_mut().zip(inputs) {
+            if let Some(a) = fx.as_async() {
+                pending.push(a.prepare_async(sr, ch));
+            }
+        }
+        for res in futures::future::join_all(pending).await {
+            res?;
+        }
+        Ok(())
+    }
+    /// `prepare_all_async` on the current thread, for callers without a runtime.
+    #[cfg(feature = "async")]
+    pub fn prepare_all_blocking(&mut self, sr: u32, channels: u16) -> anyhow::Result<()> {
+        futures::executor::block_on(self.prepare_all_async(sr, channels))
+    }
+    /// Remove and return the effect at `index` right away, cutting off any tail.
+    /// Once the chain runs on the audio thread use `ParamQueue::pop_effect`,
+    /// which lets the tail ring out first.
+    pub fn pop_effect(&mut self, index: usize) -> Option<Box<dyn Effect>> {
+        if index >= self.effects.len() {
+            return None;
+        }
+        let slot = self.effects.remove(index);
+        self.update_channels();
+        Some(slot.inner)
+    }
+    /// Replace the effect at `index` with `new_fx`, prepared for that position
+    /// and keeping the old wet/dry, and return the old one. `None` (and `new_fx`
+    /// dropped) if `index` is out of range. Once the chain runs on the audio
+    /// thread use `ParamQueue::swap_effect`.
+    pub fn swap_effect(&mut self, index: usize, new_fx: Box<dyn Effect>) -> Option<Box<dyn Effect>> {
+        let ch = self.effects.get(..index)?.iter().fold(self.channels, |ch, fx| fx.output_channel_count(ch));
+        let old = self.effects.get_mut(index)?;
+        let slot = prepared_slot(new_fx, old.mix(), self.sample_rate, ch);
+        let old = core::mem::replace(old, slot);
+        self.update_channels();
+        Some(old.inner)
+    }
+    /// Remove and return every effect right away, in chain order. Once the
+    /// chain runs on the audio thread use `ParamQueue::remove_all`.
+    pub fn remove_all(&mut self) -> Vec<Box<dyn Effect>> {
+        let old = self.effects.drain(..).map(|slot| slot.inner).collect();
+        self.update_channels();
+        old
+    }
+    /// Replace every effect with `new_effects` (fully wet, each prepared for
+    /// its position) and return the old ones. All or nothing: refused, with
+    /// the chain untouched, if `push` would refuse any of them. Once the
+    /// chain runs on the audio thread use `ParamQueue::replace_all`.
+    pub fn replace_all(&mut self, new_effects: Vec<Box<dyn Effect>>) -> Result<Vec<Box<dyn Effect>>, PushError> {
+        let mut slots = Vec::with_capacity(MAX_EFFECTS.max(new_effects.len()));
+        let (mut ch, mut bytes) = (self.channels, 0);
+        for fx in new_effects {
+            if let Some(block_size) = self.block_size {
+                check_block_size(&*fx, block_size)?;
+            }
+            let slot = prepared_slot(fx, 1.0, self.sample_rate, ch);
+            bytes += slot.memory_usage_bytes();
+            if let Some(limit) = self.max_memory_bytes.filter(|&limit| bytes > limit) {
+                return Err(PushError::MemoryLimit { effect: String::from(slot.name()), bytes, limit });
+            }
+            ch = slot.output_channel_count(ch);
+            slots.push(slot);
+        }
+        let old = core::mem::replace(&mut self.effects, slots);
+        self.update_channels();
+        Ok(old.into_iter().map(|slot| slot.inner).collect())
+    }
+    /// Reset every listed parameter of every effect to its default (see
+    /// `Effect::param_descriptors`). Like `iter_effects_mut`, only while the
+    /// chain isn't processing audio.
+    pub fn reset_all_params_to_default(&mut self) {
+        let mut ch = self.channels;
+        for fx in &mut self.effects {
+            for i in 0..fx.param_descriptors().len() {
+                let key = fx.param_descriptors()[i].key;
+                fx.reset_param_to_default(key);
+            }
+            fx.prepare_hot(self.sample_rate, ch);
+            ch = fx.output_channel_count(ch);
+        }
+    }
+    /// Update the wet/dry of the effect at `index`; out-of-range is ignored.
+    pub fn set_effect_mix(&mut self, index: usize, mix: f32) {
+        if let Some(fx) = self.effects.get_mut(index) {
+            fx.set_mix(mix);
+        }
+    }
+    /// The effects in chain order (without their `Mix` wrappers), e.g. to read meters.
+    pub fn iter_effects(&self) -> impl Iterator<Item = &dyn Effect> + '_ {
+        self.effects.iter().map(|fx| &*fx.inner)
+    }
+    /// Mutable `iter_effects`. Only while the chain isn't processing audio: once
+    /// it runs on the audio thread, changes go through `ParamQueue`.
+    pub fn iter_effects_mut(&mut self) -> impl Iterator<Item = &mut dyn Effect> + '_ {
+        self.effects.iter_mut().map(|fx| &mut *fx.inner as &mut dyn Effect)
+    }
+    pub fn effect_at(&self, index: usize) -> Option<&dyn Effect> {
+        self.effects.get(index).map(|fx| &*fx.inner)
+    }
+    /// Same caveat as `iter_effects_mut`.
+    pub fn effect_at_mut(&mut self, index: usize) -> Option<&mut dyn Effect> {
+        self.effects.get_mut(index).map(|fx| &mut *fx.inner as &mut dyn Effect)
+    }
+    /// Name of the effect at `index`, if any.
+    pub fn effect_name(&self, index: usize) -> Option<&str> {
+        self.effects.get(index).map(|fx| fx.name())
+    }
+    pub fn effect_names(&self) -> Vec<&str> {
+        self.effects.iter().map(|fx| fx.name()).collect()
+    }
+    /// Per-effect `latency_samples`, in chain order.
+    pub fn effect_latencies(&self) -> Vec<u32> {
+        self.effects.iter().map(|fx| fx.latency_samples()).collect()
+    }
+    /// `memory_usage_bytes` of each effect, in chain order.
+    pub fn effect_memory_bytes(&self) -> Vec<usize> {
+        self.effects.iter().map(|fx| fx.memory_usage_bytes()).collect()
+    }
+    #[cfg(feature = "std")]
+    /// Pretty-print for debug output
+    pub fn print_chain(&self) {
+        if self.effects.is_empty() {
+            println!("(empty chain)");
+            return;
+        }
+        for (i, fx) in self.effects.iter().enumerate() {
+            let (major, minor, patch) = fx.version();
+            println!("{:>2}  {} v{major}.{minor}.{patch}", i, fx.name());
+        }
+    }
+    /// Graphviz DOT of the chain (see `chain_dot`). Effects at zero mix, or all
+    /// of them while bypassed, are drawn dashed.
+    pub fn to_dot(&self) -> String {
+        let mut ch = self.channels;
+        let nodes = self.effects.iter().map(|fx| {
+            ch = fx.output_channel_count(ch);
+            DotNode {
+                name: fx.name(),
+                latency_samples: fx.latency_samples(),
+                bypassed: self.bypass_all || fx.mix() <= 0.0,
+                channels: ch,
+            }
+        });
+        chain_dot(nodes, self.sample_rate, self.channels)
+    }
+    /// Diagram nodes for each effect, in order. Effects at zero mix, or all of
+    /// them while bypassed, are marked bypassed.
+    pub fn graph_nodes(&self) -> Vec<GraphNode> {
+        self.effects.iter().map(|fx| GraphNode::of(fx, self.bypass_all || fx.mix() <= 0.0)).collect()
+    }
+    /// Text diagram of the chain (see `chain_ascii`).
+    pub fn to_ascii(&self) -> String {
+        chain_ascii(&self.graph_nodes(), self.sample_rate)
+    }
+    /// Skip every effect (audio passes through untouched) while `on`.
+    pub fn set_bypass_all(&mut self, on: bool) {
+        self.bypass_all = on;
+    }
+    pub fn is_bypassed_all(&self) -> bool {
+        self.bypass_all
+    }
+    #[cfg(feature = "std")]
+    /// Events sent to `queue` are applied at the start of each `process()`.
+    pub fn set_param_queue(&mut self, queue: ParamQueue) {
+        self.params = Some(queue);
+    }
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+    /// Interleaved channels the chain is fed.
+    pub fn channels(&self) -> u16 {
+        self.channels
+    }
+    /// Interleaved channels produced by `process_into` (input channels unless an
+    /// effect such as `CrossoverLR4` changes the count).
+    pub fn output_channels(&self) -> u16 {
+        self.out_channels
+    }
+    /// Channel count effect `index` is fed, after any reshaping before it.
+    #[cfg(feature = "std")]
+    fn input_channels(&self, index: usize) -> u16 {
+        self.effects.iter().take(index).fold(self.channels, |ch, fx| fx.output_channel_count(ch))
+    }
+    fn update_channels(&mut self) {
+        #[cfg(feature = "std")]
+        if let Some(t) = self.timing.as_mut() {
+            t.reset();
+        }
+        self.out_channels = self.effects.iter().fold(self.channels, |ch, fx| fx.output_channel_count(ch));
+        let mut ch = self.channels;
+        self.reshapes = self.effects.iter().any(|fx| {
+            let out = fx.output_channel_count(ch);
+            core::mem::replace(&mut ch, out) != out
+        });
+        self.in_place = self.effects.iter().all(|fx| fx.in_place_safe());
+        if !self.in_place {
+            // Typical blocks fit, as for the other scratch buffers.
+            let widest = self.effects.iter().fold((self.channels, self.channels), |(ch, max), fx| {
+                let out = fx.output_channel_count(ch);
+                (out, max.max(out))
+            });
+            self.input.reserve(4096 * widest.1 as usize);
+        }
+    }
+    #[cfg(feature = "std")]
+    pub fn apply(&mut self, ev: ParamEvent) {
+        match ev {
+            ParamEvent::SetParamDb { index, key, db } => {
+                let ch = self.input_channels(index);
+                if let Some(fx) = self.effects.get_mut(index) {
+                    fx.set_param_db(key.as_str(), db);
+                    // Coefficients only; an effect that needs a full prepare()
+                    // keeps its old ones rather than allocating here.
+                    fx.prepare_hot(self.sample_rate, ch);
+                }
+            }
+            ParamEvent::ResetParam { index, key } => {
+                let ch = self.input_channels(index);
+                if let Some(fx) = self.effects.get_mut(index) {
+                    fx.reset_param_to_default(key.as_str());
+                    fx.prepare_hot(self.sample_rate, ch);
+                }
+            }
+            ParamEvent::BypassAll(on) => self.bypass_all = on,
+            ParamEvent::Pause(on) => self.paused = on,
+            ParamEvent::InsertEffect { index, fx } => {
+                if self.effects.len() < self.effects.capacity() {
+                    let index = index.min(self.effects.len());
+                    self.effects.insert(index, fx);
+                    self.update_channels();
+                } else {
+                    self.retire(fx);
+                }
+            }
+            ParamEvent::RemoveEffect { index } => {
+                if index < self.effects.len() {
+                    let fx = self.effects.remove(index);
+                    self.retire(fx);
+                    self.update_channels();
+                }
+            }
+            ParamEvent::RemoveAll => {
+                while let Some(fx) = self.effects.pop() {
+                    self.retire(fx);
+                }
+                self.update_channels();
+            }
+            ParamEvent::PopEffect { index, reply } => {
+                if index < self.effects.len() {
+                    let ch = self.effects[..index].iter().fold(self.channels, |ch, fx| fx.output_channel_count(ch));
+                    let slot = self.effects.remove(index);
+                    self.update_channels();
+                    // Tails are mixed into the chain's input-width block, so only same-width slots ring out.
+                    let remaining = slot.tail_samples();
+                    let fits = ch == self.channels && slot.output_channel_count(ch) == ch;
+                    if remaining > 0 && fits && self.draining.len() < self.draining.capacity() {
+                        self.draining.push(Draining { slot, remaining, reply });
+                    } else {
+                        self.hand_back(slot, reply);
+                    }
+                }
+            }
+            ParamEvent::SwapEffect { index, mut fx, reply } => match self.effects.get_mut(index) {
+                Some(old) => {
+                    fx.set_mix(old.mix());
+                    let old = core::mem::replace(old, fx);
+                    self.update_channels();
+                    self.hand_back(old, reply);
+                }
+                None => self.retire(fx),
+            },
+        }
+    }
+    #[cfg(feature = "std")]
+    /// Send a popped slot to its waiting caller, or retire it if nobody is listening.
+    fn hand_back(&mut self, slot: Slot, reply: SyncSender<Slot>) {
+        if let Err(TrySendError::Full(slot) | TrySendError::Disconnected(slot)) = reply.try_send(slot) {
+            self.retire(slot);
+        }
+    }
+    #[cfg(feature = "std")]
+    /// Add the tails of popped slots to `block`, handing each back once it has rung out.
+    /// Blocks longer than `tail` holds are rung out in whole-frame pieces.
+    fn ring_out_tails(&mut self, block: &mut [f32]) {
+        if self.draining.is_empty() {
+            return;
+        }
+        let ch = self.channels.max(1) as usize;
+        let frames = (block.len() / ch) as u32;
+        let piece = (self.tail.capacity() / ch).max(1) * ch;
+        let mut i = 0;
+        while i < self.draining.len() {
+            let d = &mut self.draining[i];
+            for part in block.chunks_mut(piece) {
+                self.tail.clear();
+                self.tail.resize(part.len(), 0.0);
+                d.slot.process(&mut self.tail);
+                for (s, t) in part.iter_mut().zip(&self.tail) {
+                    *s += t;
+                }
+            }
+            d.remaining = d.remaining.saturating_sub(frames);
+            if d.remaining == 0 {
+                let d = self.draining.swap_remove(i);
+                self.hand_back(d.slot, d.reply);
+            } else {
+                i += 1;
+            }
+        }
+    }
+    #[cfg(feature = "std")]
+    /// Hand `fx` back to the control thread to be dropped there; if the queue
+    /// can't take it now it waits in `retiring` (dropped here only as a last resort).
+    /// Without a queue there is no audio thread involved, so it is dropped directly.
+    fn retire(&mut self, fx: Slot) {
+        let Some(queue) = &self.params else { return };
+        if let Err(fx) = queue.retire(fx) {
+            if self.retiring.len() < self.retiring.capacity() {
+                self.retiring.push(fx);
+            }
+        }
+    }
+    /// Process one interleaved block in-place. If the chain changes the channel
+    /// count, the output's channel groups are summed back to the input width
+    /// (for a crossover that re-joins the bands); use `process_into` to keep them.
+    /// Effects run with denormals flushed to zero.
+    pub fn process(&mut self, block: &mut [f32]) {
+        let ctx = self.context();
+        self.process_with_context(block, &ctx);
+    }
+    /// `process`, with effects seeing `ctx`'s rate and realtime flag. The
+    /// position is the chain's own: `ctx.sample_pos` is replaced by
+    /// `sample_pos()`, which then moves on by the block's frames.
+    pub fn process_with_context(&mut self, block: &mut [f32], ctx: &ProcessContext) {
+        let ctx = ProcessContext { sample_pos: self.sample_pos, ..*ctx };
+        self.advance(block.len());
+        with_denormals_flushed(|| self.process_flushed(block, &ctx));
+    }
+    /// Frames processed so far; effects get it as `ProcessContext::sample_pos`.
+    pub fn sample_pos(&self) -> u64 {
+        self.sample_pos
+    }
+    /// Move the position, e.g. on a seek; it keeps counting from there.
+    pub fn set_sample_pos(&mut self, pos: u64) {
+        self.sample_pos = pos;
+    }
+    /// `ProcessContext::is_realtime` for `process` and friends. The engine
+    /// sets it on the chains it runs from the audio callback.
+    pub fn set_realtime(&mut self, realtime: bool) {
+        self.realtime = realtime;
+    }
+    /// The context the next block will be processed with.
+    pub fn context(&self) -> ProcessContext {
+        ProcessContext { sample_pos: self.sample_pos, sample_rate: self.sample_rate, is_realtime: self.realtime }
+    }
+    fn advance(&mut self, samples: usize) {
+        self.sample_pos += (samples / self.channels.max(1) as usize) as u64;
+    }
+    fn process_flushed(&mut self, block: &mut [f32], ctx: &ProcessContext) {
+        #[cfg(feature = "std")]
+        self.apply_pending();
+        if self.paused {
+            block.fill(0.0);
+            return;
+        }
+        if self.input_is_silent(block) {
+            block.fill(0.0);
+        } else if !self.bypass_all {
+            self.run_effects(block, ctx);
+        }
+        #[cfg(feature = "std")]
+        self.ring_out_tails(block);
+    }
+    /// Process stereo frames in place through each effect's `process_frames_with_context`,
+    /// otherwise like `process`. Chains that aren't stereo, or change the
+    /// channel count, fall back to `process` on the interleaved view.
+    pub fn process_frames_stereo(&mut self, frames: &mut [Frame<2>]) {
+        if self.channels != 2 || self.reshapes || !self.in_place {
+            self.process(Frame::as_interleaved_mut(frames));
+            return;
+        }
+        let ctx = self.context();
+        self.advance(frames.len() * 2);
+        with_denormals_flushed(|| {
+            #[cfg(feature = "std")]
+            self.apply_pending();
+            let block = Frame::as_interleaved_mut(frames);
+            if self.paused {
+                block.fill(0.0);
+                return;
+            }
+            if self.input_is_silent(block) {
+                block.fill(0.0);
+            } else if !self.bypass_all {
+                let frames = Frame::from_interleaved_mut(block);
+                for (i, fx) in self.effects.iter_mut().enumerate() {
+                    timed(&mut self.timing, i, || fx.process_frames_with_context(frames, &ctx));
+                }
+                end_timed_block(&mut self.timing, &self.effects);
+            }
+            #[cfg(feature = "std")]
+            self.ring_out_tails(Frame::as_interleaved_mut(frames));
+        });
+    }
+    /// Record how long each effect's `process()` takes. Off by default;
+    /// switching it allocates or frees the history, so do it before the chain
+    /// goes to the audio thread.
+    #[cfg(feature = "std")]
+    pub fn enable_timing(&mut self, enabled: bool) {
+        match (enabled, self.timing.is_some()) {
+            (true, false) => self.timing = Some(Box::new(Timing::new())),
+            (false, true) => self.timing = None,
+            _ => {}
+        }
+    }
+    /// Enable timing and report mean/p99 per effect every `every_blocks` blocks.
+    #[cfg(feature = "std")]
+    pub fn set_timing_callback(&mut self, every_blocks: usize, cb: TimingCallback) {
+        self.enable_timing(true);
+        if let Some(t) = self.timing.as_mut() {
+            t.set_callback(every_blocks, cb);
+        }
+    }
+    /// Mean and p99 per effect over the recorded blocks; empty while timing is off.
+    #[cfg(feature = "std")]
+    pub fn timing_histogram(&self) -> Vec<EffectTiming> {
+        self.timing.as_ref().map(|t| t.histogram(&self.effects)).unwrap_or_default()
+    }
+    /// Run `det` on every block's input before the effects; while it reports
+    /// silence the chain outputs zeros without processing.
+    #[cfg(feature = "std")]
+    pub fn set_silence_detector(&mut self, mut det: SilenceDetector) {
+        det.prepare(self.sample_rate, self.channels);
+        self.silence = Some(det);
+    }
+    #[cfg(feature = "std")]
+    fn input_is_silent(&mut self, input: &[f32]) -> bool {
+        let Some(det) = self.silence.as_mut() else { return false };
+        det.update(input);
+        det.is_suspended()
+    }
+    #[cfg(not(feature = "std"))]
+    fn input_is_silent(&mut self, _input: &[f32]) -> bool {
+        false
+    }
+    fn run_effects(&mut self, block: &mut [f32], ctx: &ProcessContext) {
+        if self.reshapes {
+            self.run_reshaping(block, ctx);
+            self.fold_work_into(block);
+            return;
+        }
+        if self.channels == 2 && self.in_place && self.supports_stereo_split() {
+            self.process_split(block, ctx);
+            return;
+        }
+        for (i, fx) in self.effects.iter_mut().enumerate() {
+            timed(&mut self.timing, i, || run_slot(fx, block, block.len(), &mut self.input, ctx));
+        }
+        end_timed_block(&mut self.timing, &self.effects);
+    }
+    /// Process `input` into `output`, leaving `input` untouched. `output` holds
+    /// `output_channels()` interleaved channels, i.e. it is `input.len()` long
+    /// unless the chain reshapes. Channels an effect would have added are silent
+    /// while bypassed.
+    pub fn process_into(&mut self, input: &[f32], output: &mut [f32]) {
+        if !self.reshapes {
+            output.copy_from_slice(input);
+            self.process(output);
+            return;
+        }
+        #[cfg(feature = "std")]
+        self.apply_pending();
+        let ctx = self.context();
+        self.advance(input.len());
+        let (inc, outc) = (self.channels as usize, self.out_channels as usize);
+        let frames = input.len() / inc.max(1);
+        output.fill(0.0);
+        if self.paused || self.input_is_silent(input) {
+            return;
+        }
+        if self.bypass_all {
+            for (o, i) in output.chunks_exact_mut(outc).zip(input.chunks_exact(inc)) {
+                o[..inc.min(outc)].copy_from_slice(&i[..inc.min(outc)]);
+            }
+            return;
+        }
+        with_denormals_flushed(|| self.run_reshaping(input, &ctx));
+        output.copy_from_slice(&self.work[..frames * outc]);
+    }
+    /// `process_into`, then blend: `output = wet_gain * processed + dry_gain * input`.
+    /// On a reshaping chain the dry signal goes to each frame's first channels.
+    pub fn process_add(&mut self, input: &[f32], output: &mut [f32], wet_gain: f32, dry_gain: f32) {
+        self.process_into(input, output);
+        let (inc, outc) = (self.channels as usize, self.out_channels as usize);
+        for (o, i) in output.chunks_exact_mut(outc).zip(input.chunks_exact(inc)) {
+            for s in o.iter_mut() {
+                *s *= wet_gain;
+            }
+            for (s, &d) in o.iter_mut().zip(i) {
+                *s += dry_gain * d;
+            }
+        }
+    }
+    /// Run every effect over a copy of `block` in `work`, growing or shrinking
+    /// it at each effect that changes the channel count.
+    fn run_reshaping(&mut self, block: &[f32], ctx: &ProcessContext) {
+        self.work.clear();
+        self.work.extend_from_slice(block);
+        let mut ch = self.channels as usize;
+        for (i, fx) in self.effects.iter_mut().enumerate() {
+            let out = fx.output_channel_count(ch as u16) as usize;
+            let frames = self.work.len() / ch.max(1);
+            if out > ch {
+                self.work.resize(frames * out, 0.0);
+            }
+            timed(&mut self.timing, i, || run_slot(fx, &mut self.work, frames * ch, &mut self.input, ctx));
+            self.work.truncate(frames * out);
+            ch = out;
+        }
+        end_timed_block(&mut self.timing, &self.effects);
+    }
+    fn fold_work_into(&self, block: &mut [f32]) {
+        let (inc, outc) = (self.channels as usize, self.out_channels as usize);
+        for (o, w) in block.chunks_exact_mut(inc).zip(self.work.chunks_exact(outc)) {
+            o.fill(0.0);
+            for group in w.chunks(inc) {
+                for (s, &v) in o.iter_mut().zip(group) {
+                    *s += v;
+                }
+            }
+        }
+    }
+    #[cfg(feature = "std")]
+    /// Apply queued events and hand back retired effects.
+    fn apply_pending(&mut self) {
+        // Cloning the queue only bumps refcounts; it lets `apply` borrow `self`.
+        if let Some(queue) = self.params.clone() {
+            while let Some(fx) = self.retiring.pop() {
+                if let Err(fx) = queue.retire(fx) {
+                    self.retiring.push(fx);
+                    break;
+                }
+            }
+            queue.drain(|ev| self.apply(ev));
+        }
+    }
+    #[cfg(feature = "std")]
+    /// CPU preflight: process `num_blocks` blocks of silence and time each one
+    /// against the real-time budget of `block_size` frames at `sr`.
+    pub fn dry_run(&mut self, sr: u32, block_size: usize, num_blocks: usize) -> DryRunResult {
+        let mut block = vec![0.0f32; block_size * self.channels as usize];
+        let (mut total, mut max) = (0.0f64, 0.0f64);
+        for _ in 0..num_blocks {
+            block.fill(0.0);
+            let t = Instant::now();
+            self.process(&mut block);
+            let us = t.elapsed().as_secs_f64() * 1e6;
+            total += us;
+            max = max.max(us);
+        }
+        let budget_us = 1_000_000.0 * block_size as f64 / sr as f64;
+        DryRunResult {
+            mean_us: total / num_blocks.max(1) as f64,
+            max_us: max,
+            budget_us,
+            is_realtime_safe: max < budget_us,
+        }
+    }
+    #[cfg(feature = "std")]
+    /// A new chain with the same effects, wet/dry and bypass, each made by
+    /// `Effect::clone_fresh` and prepared: no delay lines, envelopes or queued
+    /// events carry over. Fails if an effect can't be duplicated.
+    pub fn clone_for_parallel(&self) -> anyhow::Result<Chain> {
+        let mut chain = Chain::new(self.sample_rate, self.channels);
+        chain.block_size = self.block_size;
+        chain.bypass_all = self.bypass_all;
+        for slot in &self.effects {
+            let fx = slot.inner.clone_fresh().ok_or_else(|| anyhow::anyhow!("{} can't be cloned", slot.name()))?;
+            chain.push_with_mix(fx, slot.mix())?;
+        }
+        Ok(chain)
+    }
+    #[cfg(feature = "std")]
+    /// Offline batch processing: run each block through its own
+    /// `clone_for_parallel` copy, so every block starts from fresh state and
+    /// they can be spread over all cores. `self` is left untouched. For one
+    /// continuous signal use `process` instead.
+    pub fn process_parallel_blocks(&self, blocks: &mut [&mut [f32]]) -> anyhow::Result<()> {
+        let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+        let per_thread = blocks.len().div_ceil(threads).max(1);
+        // `Chain` isn't `Sync`, so each thread gets its own copy to clone from.
+        let work = blocks
+            .chunks_mut(per_thread)
+            .map(|chunk| Ok((self.clone_for_parallel()?, chunk)))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        std::thread::scope(|s| {
+            let handles: Vec<_> = work
+                .into_iter()
+                .map(|(template, chunk)| {
+                    s.spawn(move || -> anyhow::Result<()> {
+                        for block in chunk.iter_mut() {
+                            template.clone_for_parallel()?.process(block);
+                        }
+                        Ok(())
+                    })
+                })
+                .collect();
+            handles.into_iter().try_for_each(|h| h.join().expect("block worker panicked"))
+        })
+    }
+    /// True when every effect has a native `process_stereo`.
+    pub fn supports_stereo_split(&self) -> bool {
+        !self.effects.is_empty() && self.effects.iter().all(|fx| fx.supports_stereo_split())
+    }
+    fn process_split(&mut self, block: &mut [f32], ctx: &ProcessContext) {
+        self.left.clear();
+        self.right.clear();
+        for frame in block.chunks_exact(2) {
+            self.left.push(frame[0]);
+            self.right.push(frame[1]);
+        }
+        for (i, fx) in self.effects.iter_mut().enumerate() {
+            timed(&mut self.timing, i, || fx.process_stereo_with_context(&mut self.left, &mut self.right, ctx));
+        }
+        end_timed_block(&mut self.timing, &self.effects);
+        for (frame, (&l, &r)) in block.chunks_exact_mut(2).zip(self.left.iter().zip(self.right.iter())) {
+            frame[0] = l;
+            frame[1] = r;
         }
     }
 }
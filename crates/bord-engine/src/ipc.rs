@@ -0,0 +1,456 @@
+//! Control socket for a running engine: JSON-RPC 2.0, one message per line.
+//!
+//! Requests look like `{"jsonrpc":"2.0","id":1,"method":"getStats","params":{}}`;
+//! requests without an `id` are notifications and get no reply.
+//! The transport is a Unix socket, or a named pipe (`\\.\pipe\bord`) on
+//! Windows; elsewhere `Server::bind` and `Client::connect` fail.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+#[cfg(any(unix, windows))]
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+
+pub const JSONRPC_VERSION: &str = "2.0";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Request {
+    pub jsonrpc: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<Value>,
+    pub method: String,
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub params: Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Response {
+    pub jsonrpc: String,
+    pub id: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<RpcError>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl RpcError {
+    pub const PARSE_ERROR: i64 = -32700;
+    pub const INVALID_REQUEST: i64 = -32600;
+    pub const METHOD_NOT_FOUND: i64 = -32601;
+    pub const INVALID_PARAMS: i64 = -32602;
+    pub const INTERNAL_ERROR: i64 = -32603;
+
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        Self { code, message: message.into() }
+    }
+    pub fn method_not_found(method: &str) -> Self {
+        Self::new(Self::METHOD_NOT_FOUND, format!("Method not found: {method}"))
+    }
+    pub fn invalid_params(message: impl Into<String>) -> Self {
+        Self::new(Self::INVALID_PARAMS, message)
+    }
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::new(Self::INTERNAL_ERROR, message)
+    }
+}
+
+impl std::fmt::Display for RpcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (code {})", self.message, self.code)
+    }
+}
+
+impl std::error::Error for RpcError {}
+
+/// Decode `params` into `T`, mapping failures to an "invalid params" error.
+pub fn parse_params<T: serde::de::DeserializeOwned>(params: Value) -> Result<T, RpcError> {
+    let params = if params.is_null() { Value::Object(Default::default()) } else { params };
+    serde_json::from_value(params).map_err(|e| RpcError::invalid_params(e.to_string()))
+}
+
+/// Turn one request line into the reply line, if the request expects one.
+pub fn handle_line<F>(line: &str, dispatch: &F) -> Option<Response>
+where
+    F: Fn(&str, Value) -> Result<Value, RpcError>,
+{
+    let reply = |id, res: Result<Value, RpcError>| {
+        let (result, error) = match res {
+            Ok(v) => (Some(v), None),
+            Err(e) => (None, Some(e)),
+        };
+        Response { jsonrpc: JSONRPC_VERSION.into(), id, result, error }
+    };
+    let raw: Value = match serde_json::from_str(line) {
+        Ok(v) => v,
+        Err(e) => return Some(reply(Value::Null, Err(RpcError::new(RpcError::PARSE_ERROR, e.to_string())))),
+    };
+    let req: Request = match serde_json::from_value(raw) {
+        Ok(r) => r,
+        Err(e) => return Some(reply(Value::Null, Err(RpcError::new(RpcError::INVALID_REQUEST, e.to_string())))),
+    };
+    if req.jsonrpc != JSONRPC_VERSION {
+        let id = req.id.unwrap_or(Value::Null);
+        return Some(reply(id, Err(RpcError::new(RpcError::INVALID_REQUEST, "jsonrpc must be \"2.0\""))));
+    }
+    let res = dispatch(&req.method, req.params);
+    req.id.map(|id| reply(id, res))
+}
+
+/// `$XDG_RUNTIME_DIR/bord.sock`, or `/tmp/bord.sock` when unset; the pipe
+/// `\\.\pipe\bord` on Windows.
+pub fn default_socket_path() -> PathBuf {
+    runtime_dir().join(format!("bord{SOCKET_SUFFIX}"))
+}
+
+/// Socket of the `bord route` with process id `pid`, next to the default one.
+/// Each route has its own, so several can run at once.
+pub fn route_socket_path(pid: u32) -> PathBuf {
+    runtime_dir().join(format!("bord-route-{pid}{SOCKET_SUFFIX}"))
+}
+
+/// Sockets of `bord route`s, sorted; some may be stale, left by a route that
+/// didn't exit cleanly.
+pub fn route_socket_paths() -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(runtime_dir()) else { return Vec::new() };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| {
+            let name = p.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            name.starts_with("bord-route-") && name.ends_with(SOCKET_SUFFIX)
+        })
+        .collect();
+    paths.sort();
+    paths
+}
+
+#[cfg(not(windows))]
+const SOCKET_SUFFIX: &str = ".sock";
+#[cfg(windows)]
+const SOCKET_SUFFIX: &str = "";
+
+#[cfg(not(windows))]
+fn runtime_dir() -> PathBuf {
+    std::env::var_os("XDG_RUNTIME_DIR").map(PathBuf::from).unwrap_or_else(|| PathBuf::from("/tmp"))
+}
+
+/// Named pipes live in their own namespace, which `read_dir` can list.
+#[cfg(windows)]
+fn runtime_dir() -> PathBuf {
+    PathBuf::from(r"\\.\pipe\")
+}
+
+#[cfg(unix)]
+pub use unix::Server;
+#[cfg(windows)]
+pub use windows::Server;
+#[cfg(unix)]
+use unix::{connect, Stream};
+#[cfg(windows)]
+use windows::{connect, Stream};
+
+/// Answer each request line on `conn` until the client hangs up.
+#[cfg(any(unix, windows))]
+fn serve_conn<F>(conn: Stream, dispatch: &F) -> anyhow::Result<()>
+where
+    F: Fn(&str, Value) -> Result<Value, RpcError>,
+{
+    let mut writer = conn.try_clone()?;
+    for line in BufReader::new(conn).lines() {
+        let line = line?;
+        if line.trim().is_empty() { continue; }
+        if let Some(resp) = handle_line(&line, dispatch) {
+            serde_json::to_writer(&mut writer, &resp)?;
+            writer.write_all(b"\n")?;
+        }
+    }
+    Ok(())
+}
+
+/// Synchronous JSON-RPC client over one connection.
+#[cfg(any(unix, windows))]
+pub struct Client {
+    writer: Stream,
+    reader: BufReader<Stream>,
+    next_id: u64,
+}
+
+#[cfg(any(unix, windows))]
+impl Client {
+    pub fn connect(path: &std::path::Path) -> anyhow::Result<Client> {
+        use anyhow::Context;
+        let conn = connect(path).with_context(|| format!("No running bord at {}", path.display()))?;
+        Ok(Client { writer: conn.try_clone()?, reader: BufReader::new(conn), next_id: 1 })
+    }
+
+    /// Call `method` and wait for its result; JSON-RPC errors become `Err(RpcError)`.
+    pub fn call(&mut self, method: &str, params: Value) -> anyhow::Result<Value> {
+        let id = self.next_id;
+        self.next_id += 1;
+        let req = Request { jsonrpc: JSONRPC_VERSION.into(), id: Some(id.into()), method: method.into(), params };
+        serde_json::to_writer(&mut self.writer, &req)?;
+        self.writer.write_all(b"\n")?;
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(anyhow::anyhow!("Connection closed before a reply to {method}"));
+        }
+        let resp: Response = serde_json::from_str(&line)?;
+        match (resp.result, resp.error) {
+            (_, Some(e)) => Err(e.into()),
+            (Some(v), None) => Ok(v),
+            (None, None) => Ok(Value::Null),
+        }
+    }
+}
+
+#[cfg(unix)]
+mod unix {
+    use super::{serve_conn, RpcError};
+    use anyhow::{anyhow, Context, Result};
+    use serde_json::Value;
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::path::{Path, PathBuf};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::thread::JoinHandle;
+
+    pub(super) type Stream = UnixStream;
+
+    pub(super) fn connect(path: &Path) -> std::io::Result<Stream> {
+        UnixStream::connect(path)
+    }
+
+    /// Background listener; dropping it stops the thread and removes the socket.
+    pub struct Server {
+        path: PathBuf,
+        stop: Arc<AtomicBool>,
+        thread: Option<JoinHandle<()>>,
+    }
+
+    impl Drop for Server {
+        fn drop(&mut self) {
+            self.stop.store(true, Ordering::Release);
+            // Wake the blocking accept() so the thread can observe `stop`.
+            let _ = UnixStream::connect(&self.path);
+            if let Some(t) = self.thread.take() {
+                let _ = t.join();
+            }
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+
+    impl Server {
+        /// Bind `path` and answer requests with `dispatch(method, params)` on a background thread.
+        pub fn bind<F>(path: &Path, dispatch: F) -> Result<Server>
+        where
+            F: Fn(&str, Value) -> Result<Value, RpcError> + Send + 'static,
+        {
+            if path.exists() {
+                if UnixStream::connect(path).is_ok() {
+                    return Err(anyhow!("Another bord is already listening on {}", path.display()));
+                }
+                // Stale socket from a process that didn't exit cleanly.
+                std::fs::remove_file(path)
+                    .with_context(|| format!("Failed to remove stale socket {}", path.display()))?;
+            }
+            let listener = UnixListener::bind(path)
+                .with_context(|| format!("Failed to bind {}", path.display()))?;
+            let stop = Arc::new(AtomicBool::new(false));
+            let thread = {
+                let stop = stop.clone();
+                std::thread::Builder::new()
+                    .name("bord-ipc".into())
+                    .spawn(move || {
+                        for conn in listener.incoming() {
+                            if stop.load(Ordering::Acquire) { break; }
+                            if let Ok(conn) = conn {
+                                if let Err(e) = serve_conn(conn, &dispatch) {
+                                    crate::bord_log!("ipc connection error: {e}");
+                                }
+                            }
+                        }
+                    })?
+            };
+            Ok(Server { path: path.to_path_buf(), stop, thread: Some(thread) })
+        }
+    }
+}
+
+#[cfg(windows)]
+mod windows {
+    use super::{serve_conn, RpcError};
+    use anyhow::{anyhow, Context, Result};
+    use serde_json::Value;
+    use std::ffi::c_void;
+    use std::fs::{File, OpenOptions};
+    use std::os::windows::ffi::OsStrExt;
+    use std::os::windows::io::{AsRawHandle, FromRawHandle};
+    use std::path::{Path, PathBuf};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::thread::JoinHandle;
+    use std::time::Duration;
+
+    pub(super) type Stream = File;
+
+    const PIPE_ACCESS_DUPLEX: u32 = 0x0000_0003;
+    const FILE_FLAG_FIRST_PIPE_INSTANCE: u32 = 0x0008_0000;
+    // PIPE_TYPE_BYTE | PIPE_READMODE_BYTE | PIPE_WAIT | PIPE_REJECT_REMOTE_CLIENTS
+    const PIPE_MODE: u32 = 0x0000_0008;
+    const PIPE_UNLIMITED_INSTANCES: u32 = 255;
+    const BUFFER_BYTES: u32 = 64 * 1024;
+    const ERROR_PIPE_BUSY: i32 = 231;
+    const ERROR_PIPE_CONNECTED: i32 = 535;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn CreateNamedPipeW(
+            name: *const u16,
+            open_mode: u32,
+            pipe_mode: u32,
+            max_instances: u32,
+            out_buffer_size: u32,
+            in_buffer_size: u32,
+            default_timeout_ms: u32,
+            security_attributes: *mut c_void,
+        ) -> *mut c_void;
+        fn ConnectNamedPipe(pipe: *mut c_void, overlapped: *mut c_void) -> i32;
+    }
+
+    /// Open the pipe as a client, waiting briefly while the server is busy
+    /// with another connection.
+    pub(super) fn connect(path: &Path) -> std::io::Result<Stream> {
+        let mut tries = 0;
+        loop {
+            match OpenOptions::new().read(true).write(true).open(path) {
+                Err(e) if e.raw_os_error() == Some(ERROR_PIPE_BUSY) && tries < 50 => {
+                    tries += 1;
+                    std::thread::sleep(Duration::from_millis(20));
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// A new server end of the pipe, not yet connected. With `first` it fails
+    /// if another process already owns the name.
+    fn create_pipe(name: &[u16], first: bool) -> std::io::Result<File> {
+        let open_mode = PIPE_ACCESS_DUPLEX | if first { FILE_FLAG_FIRST_PIPE_INSTANCE } else { 0 };
+        // Safety: `name` is NUL-terminated; no security attributes or overlapped I/O.
+        let handle = unsafe {
+            CreateNamedPipeW(name.as_ptr(), open_mode, PIPE_MODE, PIPE_UNLIMITED_INSTANCES, BUFFER_BYTES, BUFFER_BYTES, 0, std::ptr::null_mut())
+        };
+        // INVALID_HANDLE_VALUE
+        if handle as isize == -1 {
+            return Err(std::io::Error::last_os_error());
+        }
+        // Safety: a freshly created handle that nothing else owns.
+        Ok(unsafe { File::from_raw_handle(handle) })
+    }
+
+    /// Block until a client opens `pipe`.
+    fn wait_for_client(pipe: &File) -> std::io::Result<()> {
+        // Safety: a valid pipe handle, synchronous mode.
+        if unsafe { ConnectNamedPipe(pipe.as_raw_handle(), std::ptr::null_mut()) } != 0 {
+            return Ok(());
+        }
+        match std::io::Error::last_os_error() {
+            // The client connected between CreateNamedPipeW and ConnectNamedPipe.
+            e if e.raw_os_error() == Some(ERROR_PIPE_CONNECTED) => Ok(()),
+            e => Err(e),
+        }
+    }
+
+    /// Background listener; dropping it stops the thread and closes the pipe.
+    pub struct Server {
+        path: PathBuf,
+        stop: Arc<AtomicBool>,
+        thread: Option<JoinHandle<()>>,
+    }
+
+    impl Drop for Server {
+        fn drop(&mut self) {
+            self.stop.store(true, Ordering::Release);
+            // Wake the blocking ConnectNamedPipe so the thread can observe `stop`.
+            let _ = OpenOptions::new().read(true).write(true).open(&self.path);
+            if let Some(t) = self.thread.take() {
+                let _ = t.join();
+            }
+        }
+    }
+
+    impl Server {
+        /// Create the pipe `path` and answer requests with `dispatch(method, params)` on a background thread.
+        pub fn bind<F>(path: &Path, dispatch: F) -> Result<Server>
+        where
+            F: Fn(&str, Value) -> Result<Value, RpcError> + Send + 'static,
+        {
+            let name: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+            let mut pipe = create_pipe(&name, true).map_err(|e| match e.kind() {
+                std::io::ErrorKind::PermissionDenied => anyhow!("Another bord is already listening on {}", path.display()),
+                _ => anyhow::Error::new(e).context(format!("Failed to create pipe {}", path.display())),
+            })?;
+            let stop = Arc::new(AtomicBool::new(false));
+            let thread = {
+                let stop = stop.clone();
+                std::thread::Builder::new()
+                    .name("bord-ipc".into())
+                    .spawn(move || loop {
+                        let connected = wait_for_client(&pipe);
+                        if stop.load(Ordering::Acquire) { break; }
+                        // The next instance exists before this one is served, so
+                        // clients arriving meanwhile wait instead of failing.
+                        let next = create_pipe(&name, false);
+                        match connected {
+                            Ok(()) => {
+                                if let Err(e) = serve_conn(pipe, &dispatch) {
+                                    crate::bord_log!("ipc connection error: {e}");
+                                }
+                            }
+                            Err(e) => crate::bord_log!("ipc connection error: {e}"),
+                        }
+                        match next {
+                            Ok(p) => pipe = p,
+                            Err(e) => {
+                                crate::bord_log!("ipc: could not reopen the pipe: {e}");
+                                break;
+                            }
+                        }
+                    })
+                    .context("Failed to start the ipc thread")?
+            };
+            Ok(Server { path: path.to_path_buf(), stop, thread: Some(thread) })
+        }
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+pub struct Server;
+
+#[cfg(not(any(unix, windows)))]
+impl Server {
+    pub fn bind<F>(_path: &std::path::Path, _dispatch: F) -> anyhow::Result<Server>
+    where
+        F: Fn(&str, Value) -> Result<Value, RpcError> + Send + 'static,
+    {
+        Err(anyhow::anyhow!("IPC is not supported on this platform"))
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+pub struct Client;
+
+#[cfg(not(any(unix, windows)))]
+impl Client {
+    pub fn connect(_path: &std::path::Path) -> anyhow::Result<Client> {
+        Err(anyhow::anyhow!("IPC is not supported on this platform"))
+    }
+    pub fn call(&mut self, _method: &str, _params: Value) -> anyhow::Result<Value> {
+        Err(anyhow::anyhow!("IPC is not supported on this platform"))
+    }
+}
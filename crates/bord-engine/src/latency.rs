@@ -0,0 +1,188 @@
+//! Loopback measurements. These need a physical (or virtual) cable from the
+//! output back to the input and drive a running `Engine`.
+
+use crate::dsp::effect::Effect;
+use crate::dsp::parametric_eq::{EqBand, ParametricEq};
+use crate::Engine;
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Tone level sent out; the response is reported relative to this.
+const PROBE_AMPLITUDE: f32 = 0.5;
+const SETTLE: Duration = Duration::from_millis(200);
+const MEASURE: Duration = Duration::from_millis(300);
+/// Range `calibrate` measures over.
+const CALIBRATION_RANGE: (f32, f32) = (20.0, 20_000.0);
+/// Largest boost or cut `Calibration::inverse_eq` applies per band.
+const MAX_CORRECTION_DB: f32 = 12.0;
+
+#[derive(Default)]
+struct ProbeShared {
+    freq_bits: AtomicU32,  // f32 bits of the tone frequency
+    generation: AtomicU64, // bumped by the control thread to restart accumulation
+    sum_sq_bits: AtomicU64, // f64 bits, published by the audio thread
+    count: AtomicU64,
+}
+
+/// First effect in the chain during a sweep: measures the RMS of what came
+/// back on the input, then replaces the block with the current sine tone.
+struct LoopbackProbe {
+    shared: Arc<ProbeShared>,
+    sr: f32,
+    channels: usize,
+    phase: f32,
+    seen_generation: u64,
+    sum_sq: f64,
+    count: u64,
+}
+
+impl Effect for LoopbackProbe {
+    fn name(&self) -> &str {
+        "LoopbackProbe"
+    }
+
+    fn prepare(&mut self, sr: u32, channels: u16) {
+        self.sr = sr as f32;
+        self.channels = channels.max(1) as usize;
+    }
+
+    fn process(&mut self, block: &mut [f32]) {
+        let generation = self.shared.generation.load(Ordering::Acquire);
+        if generation != self.seen_generation {
+            self.seen_generation = generation;
+            self.sum_sq = 0.0;
+            self.count = 0;
+        }
+        for &s in block.iter() {
+            self.sum_sq += (s as f64) * (s as f64);
+        }
+        self.count += block.len() as u64;
+        self.shared.sum_sq_bits.store(self.sum_sq.to_bits(), Ordering::Relaxed);
+        self.shared.count.store(self.count, Ordering::Release);
+
+        let freq = f32::from_bits(self.shared.freq_bits.load(Ordering::Relaxed));
+        let step = std::f32::consts::TAU * freq / self.sr;
+        for frame in block.chunks_mut(self.channels) {
+            frame.fill(PROBE_AMPLITUDE * self.phase.sin());
+            self.phase = (self.phase + step) % std::f32::consts::TAU;
+        }
+    }
+}
+
+/// Sweep `steps` log-spaced sine tones over `freq_range` (Hz) through the
+/// engine's chain and return `(freq_hz, gain_db)` relative to the sent level.
+/// The engine is restarted with the probe at the front and stopped afterwards.
+pub fn measure_frequency_response(
+    engine: &mut Engine,
+    freq_range: (f32, f32),
+    steps: u32,
+) -> Result<Vec<(f32, f32)>> {
+    let (lo, hi) = freq_range;
+    if !(lo > 0.0 && hi >= lo) || steps == 0 {
+        return Err(anyhow!("Invalid sweep: {lo}..{hi} Hz in {steps} steps"));
+    }
+    let shared = Arc::new(ProbeShared::default());
+    shared.freq_bits.store(lo.to_bits(), Ordering::Relaxed);
+
+    engine.stop();
+    engine.insert_effect_front(Box::new(LoopbackProbe {
+        shared: shared.clone(),
+        sr: 48_000.0,
+        channels: 1,
+        phase: 0.0,
+        seen_generation: 0,
+        sum_sq: 0.0,
+        count: 0,
+    }));
+    engine.start()?;
+
+    let sent_rms = PROBE_AMPLITUDE as f64 / std::f64::consts::SQRT_2;
+    let mut out = Vec::with_capacity(steps as usize);
+    for i in 0..steps {
+        let t = if steps == 1 { 0.0 } else { i as f32 / (steps - 1) as f32 };
+        let freq = lo * (hi / lo).powf(t);
+        shared.freq_bits.store(freq.to_bits(), Ordering::Relaxed);
+        // Let the tone make the round trip before accumulating.
+        std::thread::sleep(SETTLE);
+        shared.generation.fetch_add(1, Ordering::Release);
+        std::thread::sleep(MEASURE);
+
+        let count = shared.count.load(Ordering::Acquire);
+        let sum_sq = f64::from_bits(shared.sum_sq_bits.load(Ordering::Relaxed));
+        let rms = if count > 0 { (sum_sq / count as f64).sqrt() } else { 0.0 };
+        out.push((freq, (20.0 * (rms / sent_rms).log10()) as f32));
+    }
+    engine.stop();
+    Ok(out)
+}
+
+/// Measured frequency response of the output -> input loopback (speaker and
+/// microphone, or an interface's cable), stored as TOML by `bord calibrate`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Calibration {
+    /// Log-spaced, ascending in frequency.
+    pub points: Vec<CalibrationPoint>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CalibrationPoint {
+    pub freq_hz: f32,
+    /// Level that came back relative to the level sent.
+    pub gain_db: f32,
+}
+
+impl Calibration {
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        toml::from_str(&text).with_context(|| format!("{} is not a bord calibration", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, toml::to_string(self)?).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// One peaking band per point cancelling its deviation from the mean
+    /// level (the overall loss of the loop isn't corrected), limited to
+    /// +/-12 dB.
+    pub fn inverse_eq(&self) -> ParametricEq {
+        let n = self.points.len();
+        if n == 0 {
+            return ParametricEq::new(Vec::new());
+        }
+        let mean = self.points.iter().map(|p| p.gain_db).sum::<f32>() / n as f32;
+        let q = match (self.points.first(), self.points.last()) {
+            (Some(lo), Some(hi)) if n > 1 && hi.freq_hz > lo.freq_hz => {
+                // Half the spacing between points as the bandwidth, so that
+                // neighbouring bands don't pile up.
+                let ratio = (hi.freq_hz / lo.freq_hz).powf(1.0 / (n - 1) as f32);
+                2.0 * ratio.sqrt() / (ratio - 1.0)
+            }
+            _ => std::f32::consts::FRAC_1_SQRT_2,
+        };
+        let bands = self
+            .points
+            .iter()
+            .map(|p| EqBand {
+                freq_hz: p.freq_hz,
+                gain_db: (mean - p.gain_db).clamp(-MAX_CORRECTION_DB, MAX_CORRECTION_DB),
+                q,
+            })
+            .collect();
+        ParametricEq::new(bands)
+    }
+}
+
+/// Measure `steps` points over 20 Hz - 20 kHz with `measure_frequency_response`.
+pub fn calibrate(engine: &mut Engine, steps: u32) -> Result<Calibration> {
+    let points = measure_frequency_response(engine, CALIBRATION_RANGE, steps)?
+        .into_iter()
+        .map(|(freq_hz, gain_db)| CalibrationPoint { freq_hz, gain_db })
+        .collect();
+    Ok(Calibration { points })
+}
@@ -0,0 +1,43 @@
+//! Where the engine's warnings go. With `std` they are printed to stderr
+//! unless `set_log_handler` installs something else; without it they are
+//! dropped until a handler is set (e.g. one writing to a UART).
+use core::fmt::Arguments;
+use core::sync::atomic::{AtomicPtr, Ordering};
+
+/// Receives one formatted message, without a trailing newline.
+pub type LogHandler = fn(Arguments<'_>);
+
+static HANDLER: AtomicPtr<()> = AtomicPtr::new(core::ptr::null_mut());
+
+/// Send later messages to `handler` instead of the default.
+pub fn set_log_handler(handler: LogHandler) {
+    HANDLER.store(handler as *mut (), Ordering::Release);
+}
+
+/// Pass `args` to the installed handler. Use `bord_log!` rather than calling this.
+pub fn log(args: Arguments<'_>) {
+    let ptr = HANDLER.load(Ordering::Acquire);
+    if ptr.is_null() {
+        default_handler(args);
+    } else {
+        // Safety: only `set_log_handler` stores here, and always a `LogHandler`.
+        let handler = unsafe { core::mem::transmute::<*mut (), LogHandler>(ptr) };
+        handler(args);
+    }
+}
+
+#[cfg(feature = "std")]
+fn default_handler(args: Arguments<'_>) {
+    eprintln!("{args}");
+}
+
+#[cfg(not(feature = "std"))]
+fn default_handler(_args: Arguments<'_>) {}
+
+/// `format!`-style message to the handler set with `log::set_log_handler`.
+#[macro_export]
+macro_rules! bord_log {
+    ($($arg:tt)*) => {
+        $crate::log::log(::core::format_args!($($arg)*))
+    };
+}
@@ -0,0 +1,184 @@
+//! MIDI control surfaces: Control Change messages mapped onto effect parameters.
+//!
+//! `midir` delivers messages on its own thread per open port; matching CCs are
+//! scaled into the binding's range and sent through the engine's `ParamQueue`.
+
+use crate::params::{ParamEvent, ParamId, ParamQueue};
+use crate::session::SessionMidiBinding;
+use anyhow::{anyhow, Context, Result};
+use midir::{MidiInput, MidiInputConnection, MidiInputPort};
+use parking_lot::Mutex;
+use std::sync::Arc;
+
+const CLIENT_NAME: &str = "bord";
+
+#[derive(Debug, Clone)]
+pub struct MidiDeviceInfo {
+    pub name: String,
+    pub port_index: usize,
+}
+
+/// Maps one CC on one channel to `set_param_db(param_id, ..)` on the effect at
+/// `effect_index`; CC values 0..=127 are scaled linearly onto `min..=max`.
+#[derive(Debug, Clone, Copy)]
+pub struct MidiBinding {
+    pub channel: u8, // 0-15
+    pub cc: u8,
+    pub effect_index: usize,
+    pub param_id: ParamId,
+    pub min: f32,
+    pub max: f32,
+}
+
+impl MidiBinding {
+    fn value(&self, cc_value: u8) -> f32 {
+        self.min + (self.max - self.min) * (cc_value.min(127) as f32 / 127.0)
+    }
+}
+
+pub fn list_midi_inputs() -> Result<Vec<MidiDeviceInfo>> {
+    let input = MidiInput::new(CLIENT_NAME).context("Failed to open MIDI client")?;
+    let mut out = Vec::new();
+    for (port_index, port) in input.ports().iter().enumerate() {
+        let name = input.port_name(port).unwrap_or_else(|_| "<unknown>".into());
+        out.push(MidiDeviceInfo { name, port_index });
+    }
+    Ok(out)
+}
+
+/// One open input port and the bindings its callback applies.
+struct OpenInput {
+    name: String,
+    bindings: Arc<Mutex<Vec<MidiBinding>>>,
+    _conn: MidiInputConnection<()>,
+}
+
+/// Pending MIDI-learn request, consumed by the first CC on any open input.
+struct Learn {
+    effect_index: usize,
+    param_id: ParamId,
+    min: f32,
+    max: f32,
+    on_learned: Box<dyn Fn(MidiBinding) + Send>,
+}
+
+/// Open MIDI inputs of an `Engine`; ports stay open until this is dropped.
+#[derive(Default)]
+pub(crate) struct MidiInputs {
+    open: Vec<OpenInput>,
+    learn: Arc<Mutex<Option<Learn>>>,
+}
+
+impl MidiInputs {
+    /// Bind on the first input whose name contains `device_name` (case-insensitive),
+    /// opening it on first use.
+    pub(crate) fn add_binding(&mut self, device_name: &str, binding: MidiBinding, params: &ParamQueue) -> Result<()> {
+        let needle = device_name.to_lowercase();
+        if let Some(open) = self.open.iter().find(|o| o.name.to_lowercase().contains(&needle)) {
+            open.bindings.lock().push(binding);
+            return Ok(());
+        }
+
+        let input = MidiInput::new(CLIENT_NAME).context("Failed to open MIDI client")?;
+        let (port, name) = input
+            .ports()
+            .into_iter()
+            .find_map(|p| {
+                let name = input.port_name(&p).ok()?;
+                name.to_lowercase().contains(&needle).then_some((p, name))
+            })
+            .ok_or_else(|| anyhow!("No MIDI input matched {device_name:?}"))?;
+        self.connect(input, &port, name, vec![binding], params)
+    }
+
+    /// Open every input not open yet and bind the next CC seen on any of them.
+    /// A learn request already pending is replaced.
+    pub(crate) fn start_learn(
+        &mut self,
+        effect_index: usize,
+        param_id: ParamId,
+        (min, max): (f32, f32),
+        on_learned: Box<dyn Fn(MidiBinding) + Send>,
+        params: &ParamQueue,
+    ) -> Result<()> {
+        let names: Vec<String> = {
+            let input = MidiInput::new(CLIENT_NAME).context("Failed to open MIDI client")?;
+            input.ports().iter().filter_map(|p| input.port_name(p).ok()).collect()
+        };
+        for name in names {
+            if self.open.iter().any(|o| o.name == name) {
+                continue;
+            }
+            let input = MidiInput::new(CLIENT_NAME).context("Failed to open MIDI client")?;
+            // Ports can disappear between listing and connecting; skip those.
+            let Some(port) = input.ports().into_iter().find(|p| input.port_name(p).ok().as_ref() == Some(&name)) else {
+                continue;
+            };
+            self.connect(input, &port, name, Vec::new(), params)?;
+        }
+        if self.open.is_empty() {
+            return Err(anyhow!("No MIDI inputs to learn from"));
+        }
+        *self.learn.lock() = Some(Learn { effect_index, param_id, min, max, on_learned });
+        Ok(())
+    }
+
+    pub(crate) fn cancel_learn(&self) {
+        self.learn.lock().take();
+    }
+
+    /// Every binding, tagged with the input it is on, for `Engine::save_session`.
+    pub(crate) fn session_bindings(&self) -> Vec<SessionMidiBinding> {
+        self.open
+            .iter()
+            .flat_map(|o| {
+                o.bindings.lock().iter().map(|b| SessionMidiBinding {
+                    device: o.name.clone(),
+                    channel: b.channel,
+                    cc: b.cc,
+                    effect_index: b.effect_index,
+                    param: b.param_id.as_str().to_string(),
+                    min: b.min,
+                    max: b.max,
+                }).collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    fn connect(
+        &mut self,
+        input: MidiInput,
+        port: &MidiInputPort,
+        name: String,
+        bindings: Vec<MidiBinding>,
+        params: &ParamQueue,
+    ) -> Result<()> {
+        let bindings = Arc::new(Mutex::new(bindings));
+        let conn = {
+            let bindings = bindings.clone();
+            let learn = self.learn.clone();
+            let params = params.clone();
+            input
+                .connect(port, "bord-in", move |_stamp, msg, _| on_message(msg, &bindings, &learn, &params), ())
+                .map_err(|e| anyhow!("Failed to connect to MIDI input {name}: {e}"))?
+        };
+        self.open.push(OpenInput { name, bindings, _conn: conn });
+        Ok(())
+    }
+}
+
+fn on_message(msg: &[u8], bindings: &Mutex<Vec<MidiBinding>>, learn: &Mutex<Option<Learn>>, params: &ParamQueue) {
+    let [status, cc, value, ..] = *msg else { return };
+    if status & 0xF0 != 0xB0 {
+        return;
+    }
+    let channel = status & 0x0F;
+    if let Some(l) = learn.lock().take() {
+        let binding = MidiBinding { channel, cc, effect_index: l.effect_index, param_id: l.param_id, min: l.min, max: l.max };
+        bindings.lock().push(binding);
+        (l.on_learned)(binding);
+    }
+    for b in bindings.lock().iter().filter(|b| b.channel == channel && b.cc == cc) {
+        params.send(ParamEvent::SetParamDb { index: b.effect_index, key: b.param_id, db: b.value(value) });
+    }
+}
@@ -0,0 +1,164 @@
+//! Effects shared between chains, re-exported from `graph`.
+//!
+//! A `Chain` owns its effects, so one effect instance can't sit in two chains
+//! (e.g. a meter fed from both). `Node` puts it behind `Arc<Mutex<..>>` and
+//! implements `Effect` by locking around every call.
+//!
+//! This is not real-time safe: `Node` blocks the audio thread while another
+//! holder has the lock, and the holder may be a control thread. Use
+//! `TryLockNode` on the audio thread; it outputs silence for a block it
+//! can't lock instead of waiting.
+
+use crate::dsp::effect::{Effect, Frame, ParamDescriptor, ProcessContext};
+use parking_lot::Mutex;
+use std::sync::Arc;
+
+/// Shared handle to an effect. Every handle processes the same instance, so
+/// in two chains it runs (and is prepared) once per chain.
+pub struct Node {
+    inner: Arc<Mutex<Box<dyn Effect>>>,
+    name: String, // `name()` can't return a borrow through the lock
+    params: Vec<ParamDescriptor>, // likewise `param_descriptors()`
+}
+
+impl Node {
+    pub fn new(fx: Box<dyn Effect>) -> Self {
+        let name = fx.name().to_string();
+        let params = fx.param_descriptors().to_vec();
+        Self { inner: Arc::new(Mutex::new(fx)), name, params }
+    }
+
+    /// Another handle to the same effect.
+    pub fn clone_handle(&self) -> Node {
+        Self { inner: self.inner.clone(), name: self.name.clone(), params: self.params.clone() }
+    }
+
+    /// A handle to the same effect that never waits for the lock.
+    pub fn try_lock_handle(&self) -> TryLockNode {
+        TryLockNode(self.clone_handle())
+    }
+
+    /// Run `f` on the effect, waiting for the lock.
+    pub fn with<R>(&self, f: impl FnOnce(&mut dyn Effect) -> R) -> R {
+        f(&mut **self.inner.lock())
+    }
+}
+
+impl Effect for Node {
+    fn name(&self) -> &str { &self.name }
+    fn version(&self) -> (u32, u32, u32) { self.inner.lock().version() }
+    fn prepare(&mut self, sr: u32, channels: u16) { self.inner.lock().prepare(sr, channels) }
+    fn prepare_hot(&mut self, sr: u32, channels: u16) -> bool { self.inner.lock().prepare_hot(sr, channels) }
+    fn set_param_db(&mut self, key: &str, db: f32) { self.inner.lock().set_param_db(key, db) }
+    fn param_descriptors(&self) -> &[ParamDescriptor] { &self.params }
+    fn reset_param_to_default(&mut self, key: &str) { self.inner.lock().reset_param_to_default(key) }
+    fn process(&mut self, block: &mut [f32]) { self.inner.lock().process(block) }
+    fn process_with_context(&mut self, block: &mut [f32], ctx: &ProcessContext) { self.inner.lock().process_with_context(block, ctx) }
+    fn in_place_safe(&self) -> bool { self.inner.lock().in_place_safe() }
+    fn process_into(&mut self, input: &[f32], output: &mut [f32]) { self.inner.lock().process_into(input, output) }
+    fn process_stereo(&mut self, left: &mut [f32], right: &mut [f32]) { self.inner.lock().process_stereo(left, right) }
+    fn process_frames(&mut self, frames: &mut [Frame<2>]) { self.inner.lock().process_frames(frames) }
+    fn process_stereo_with_context(&mut self, left: &mut [f32], right: &mut [f32], ctx: &ProcessContext) {
+        self.inner.lock().process_stereo_with_context(left, right, ctx)
+    }
+    fn process_frames_with_context(&mut self, frames: &mut [Frame<2>], ctx: &ProcessContext) {
+        self.inner.lock().process_frames_with_context(frames, ctx)
+    }
+    fn supports_stereo_split(&self) -> bool { self.inner.lock().supports_stereo_split() }
+    fn output_channel_count(&self, input_channels: u16) -> u16 { self.inner.lock().output_channel_count(input_channels) }
+    fn latency_samples(&self) -> u32 { self.inner.lock().latency_samples() }
+    fn memory_usage_bytes(&self) -> usize { self.inner.lock().memory_usage_bytes() }
+    fn tail_samples(&self) -> u32 { self.inner.lock().tail_samples() }
+    fn supported_block_sizes(&self) -> Option<(u32, u32)> { self.inner.lock().supported_block_sizes() }
+}
+
+/// `Node` for the audio thread: processing and parameter calls only
+/// `try_lock`. A contended block is filled with silence and a contended
+/// `set_param_db` is dropped. `prepare()` and the queries still wait.
+pub struct TryLockNode(Node);
+
+impl TryLockNode {
+    pub fn new(fx: Box<dyn Effect>) -> Self {
+        Self(Node::new(fx))
+    }
+
+    /// A blocking handle to the same effect, e.g. to read it from a control thread.
+    pub fn node(&self) -> Node {
+        self.0.clone_handle()
+    }
+}
+
+impl Effect for TryLockNode {
+    fn name(&self) -> &str { self.0.name() }
+    fn version(&self) -> (u32, u32, u32) { self.0.version() }
+    fn prepare(&mut self, sr: u32, channels: u16) { self.0.prepare(sr, channels) }
+    fn prepare_hot(&mut self, sr: u32, channels: u16) -> bool {
+        self.0.inner.try_lock().is_some_and(|mut fx| fx.prepare_hot(sr, channels))
+    }
+    fn set_param_db(&mut self, key: &str, db: f32) {
+        if let Some(mut fx) = self.0.inner.try_lock() {
+            fx.set_param_db(key, db);
+        }
+    }
+    fn param_descriptors(&self) -> &[ParamDescriptor] { self.0.param_descriptors() }
+    fn reset_param_to_default(&mut self, key: &str) {
+        if let Some(mut fx) = self.0.inner.try_lock() {
+            fx.reset_param_to_default(key);
+        }
+    }
+    fn process(&mut self, block: &mut [f32]) {
+        match self.0.inner.try_lock() {
+            Some(mut fx) => fx.process(block),
+            None => block.fill(0.0),
+        }
+    }
+    fn process_with_context(&mut self, block: &mut [f32], ctx: &ProcessContext) {
+        match self.0.inner.try_lock() {
+            Some(mut fx) => fx.process_with_context(block, ctx),
+            None => block.fill(0.0),
+        }
+    }
+    fn in_place_safe(&self) -> bool { self.0.in_place_safe() }
+    fn process_into(&mut self, input: &[f32], output: &mut [f32]) {
+        match self.0.inner.try_lock() {
+            Some(mut fx) => fx.process_into(input, output),
+            None => output.fill(0.0),
+        }
+    }
+    fn process_stereo(&mut self, left: &mut [f32], right: &mut [f32]) {
+        match self.0.inner.try_lock() {
+            Some(mut fx) => fx.process_stereo(left, right),
+            None => {
+                left.fill(0.0);
+                right.fill(0.0);
+            }
+        }
+    }
+    fn process_frames(&mut self, frames: &mut [Frame<2>]) {
+        match self.0.inner.try_lock() {
+            Some(mut fx) => fx.process_frames(frames),
+            None => frames.fill(Frame::default()),
+        }
+    }
+    fn process_stereo_with_context(&mut self, left: &mut [f32], right: &mut [f32], ctx: &ProcessContext) {
+        match self.0.inner.try_lock() {
+            Some(mut fx) => fx.process_stereo_with_context(left, right, ctx),
+            None => {
+                left.fill(0.0);
+                right.fill(0.0);
+            }
+        }
+    }
+    fn process_frames_with_context(&mut self, frames: &mut [Frame<2>], ctx: &ProcessContext) {
+        match self.0.inner.try_lock() {
+            Some(mut fx) => fx.process_frames_with_context(frames, ctx),
+            None => frames.fill(Frame::default()),
+        }
+    }
+    fn supports_stereo_split(&self) -> bool { self.0.supports_stereo_split() }
+    fn output_channel_count(&self, input_channels: u16) -> u16 { self.0.output_channel_count(input_channels) }
+    fn latency_samples(&self) -> u32 { self.0.latency_samples() }
+    fn memory_usage_bytes(&self) -> usize { self.0.memory_usage_bytes() }
+    fn tail_samples(&self) -> u32 { self.0.tail_samples() }
+    fn supported_block_sizes(&self) -> Option<(u32, u32)> { self.0.supported_block_sizes() }
+}
@@ -0,0 +1,121 @@
+//! Open Sound Control parameter input over UDP.
+//!
+//! `/bord/effect/<index>/param/<key> <float>` calls `set_param_db(key, value)`
+//! on the effect at `index`. Extra addresses can be mapped with
+//! `OscServer::add_address_mapping`; those are checked first and must match exactly.
+
+use crate::params::{ParamEvent, ParamId, ParamQueue};
+use anyhow::{anyhow, Context, Result};
+use parking_lot::Mutex;
+use rosc::{OscPacket, OscType};
+use std::net::{SocketAddr, UdpSocket};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// How often the receive thread checks for shutdown.
+const POLL: Duration = Duration::from_millis(200);
+
+struct Mapping {
+    address: String,
+    effect_index: usize,
+    param_id: ParamId,
+}
+
+/// Background UDP listener; dropping it stops the thread and closes the socket.
+pub struct OscServer {
+    local_addr: SocketAddr,
+    mappings: Arc<Mutex<Vec<Mapping>>>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Drop for OscServer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Release);
+        if let Some(t) = self.thread.take() {
+            let _ = t.join();
+        }
+    }
+}
+
+impl OscServer {
+    /// Listen on `addr` (e.g. `"0.0.0.0:9000"`) and forward parameter messages to `params`.
+    pub fn bind(addr: &str, params: ParamQueue) -> Result<OscServer> {
+        let socket = UdpSocket::bind(addr).with_context(|| format!("Failed to bind OSC socket {addr}"))?;
+        socket.set_read_timeout(Some(POLL))?;
+        let local_addr = socket.local_addr()?;
+        let mappings = Arc::new(Mutex::new(Vec::new()));
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let (mappings, stop) = (mappings.clone(), stop.clone());
+            std::thread::Builder::new()
+                .name("bord-osc".into())
+                .spawn(move || {
+                    let mut buf = [0u8; rosc::decoder::MTU];
+                    while !stop.load(Ordering::Acquire) {
+                        // Timeouts just bring us back to the stop check.
+                        let Ok((n, _from)) = socket.recv_from(&mut buf) else { continue };
+                        match rosc::decoder::decode_udp(&buf[..n]) {
+                            Ok((_, packet)) => handle_packet(packet, &mappings, &params),
+                            Err(e) => crate::bord_log!("osc: bad packet: {e}"),
+                        }
+                    }
+                })?
+        };
+        Ok(OscServer { local_addr, mappings, stop, thread: Some(thread) })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Route messages sent to exactly `pattern` to `param_id` of the effect at `effect_index`.
+    pub fn add_address_mapping(&self, pattern: &str, effect_index: usize, param_id: ParamId) {
+        self.mappings.lock().push(Mapping { address: pattern.to_string(), effect_index, param_id });
+    }
+}
+
+fn handle_packet(packet: OscPacket, mappings: &Mutex<Vec<Mapping>>, params: &ParamQueue) {
+    match packet {
+        OscPacket::Bundle(bundle) => {
+            for p in bundle.content {
+                handle_packet(p, mappings, params);
+            }
+        }
+        OscPacket::Message(msg) => {
+            let value = match msg.args.first() {
+                Some(OscType::Float(v)) => *v,
+                Some(OscType::Double(v)) => *v as f32,
+                Some(OscType::Int(v)) => *v as f32,
+                _ => return,
+            };
+            if let Some((index, key)) = resolve(&msg.addr, mappings) {
+                params.send(ParamEvent::SetParamDb { index, key, db: value });
+            }
+        }
+    }
+}
+
+fn resolve(addr: &str, mappings: &Mutex<Vec<Mapping>>) -> Option<(usize, ParamId)> {
+    if let Some(m) = mappings.lock().iter().find(|m| m.address == addr) {
+        return Some((m.effect_index, m.param_id));
+    }
+    let rest = addr.strip_prefix("/bord/effect/")?;
+    let (index, rest) = rest.split_once('/')?;
+    let key = rest.strip_prefix("param/")?;
+    if key.is_empty() || key.contains('/') {
+        return None;
+    }
+    Some((index.parse().ok()?, ParamId::new(key)?))
+}
+
+/// `"9000"` becomes `"0.0.0.0:9000"`; anything with a host part is used as is.
+pub fn listen_addr(port_or_addr: &str) -> Result<String> {
+    if port_or_addr.contains(':') {
+        return Ok(port_or_addr.to_string());
+    }
+    let port: u16 = port_or_addr.parse().map_err(|_| anyhow!("Invalid OSC port {port_or_addr:?}"))?;
+    Ok(format!("0.0.0.0:{port}"))
+}
@@ -0,0 +1,156 @@
+use crate::graph::Slot;
+use parking_lot::Mutex;
+use std::fmt;
+use std::sync::mpsc::{Receiver, SyncSender};
+use std::sync::Arc;
+
+/// Parameter key (e.g. `"db"`) stored inline so it can travel to the audio
+/// thread without allocating. Keys longer than `ParamId::MAX_LEN` are rejected.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ParamId {
+    len: u8,
+    bytes: [u8; ParamId::MAX_LEN],
+}
+
+impl ParamId {
+    pub const MAX_LEN: usize = 31;
+
+    pub fn new(key: &str) -> Option<Self> {
+        if key.len() > Self::MAX_LEN {
+            return None;
+        }
+        let mut bytes = [0u8; Self::MAX_LEN];
+        bytes[..key.len()].copy_from_slice(key.as_bytes());
+        Some(Self { len: key.len() as u8, bytes })
+    }
+
+    pub fn as_str(&self) -> &str {
+        // Built from a &str in `new`, so always valid UTF-8.
+        std::str::from_utf8(&self.bytes[..self.len as usize]).unwrap_or_default()
+    }
+}
+
+impl fmt::Debug for ParamId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ParamId({:?})", self.as_str())
+    }
+}
+
+impl fmt::Display for ParamId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A control-thread request applied by the `Chain` at the start of a block.
+/// Nothing here frees memory on the audio thread: inserted slots are moved
+/// into the chain and removed ones are handed back through `ParamQueue::retired`.
+pub enum ParamEvent {
+    /// `Effect::set_param_db(key, db)` on the effect at `index`.
+    SetParamDb { index: usize, key: ParamId, db: f32 },
+    /// `Effect::reset_param_to_default(key)` on the effect at `index`.
+    ResetParam { index: usize, key: ParamId },
+    BypassAll(bool),
+    /// Output silence (effects are not run) while set.
+    Pause(bool),
+    /// Insert an already-prepared slot at `index` (clamped to the chain length).
+    InsertEffect { index: usize, fx: Slot },
+    RemoveEffect { index: usize },
+    /// Remove every slot (see `ParamQueue::remove_all`).
+    RemoveAll,
+    /// Like `RemoveEffect`, but the slot rings out its tail and is then sent on `reply`.
+    PopEffect { index: usize, reply: SyncSender<Slot> },
+    /// Replace the slot at `index` with an already-prepared `fx`, keeping the
+    /// old slot's wet/dry. The old slot is sent on `reply`.
+    SwapEffect { index: usize, fx: Slot, reply: SyncSender<Slot> },
+}
+
+/// Multi-producer queue of `ParamEvent`s drained by the audio thread.
+/// The audio side only ever `try_lock`s, so a busy control thread delays
+/// events by a block instead of blocking the callback.
+#[derive(Clone)]
+pub struct ParamQueue {
+    events: Arc<Mutex<Vec<ParamEvent>>>,
+    retired: Arc<Mutex<Vec<Slot>>>,
+}
+
+impl Default for ParamQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ParamQueue {
+    pub fn new() -> Self {
+        Self {
+            events: Arc::new(Mutex::new(Vec::with_capacity(64))),
+            retired: Arc::new(Mutex::new(Vec::with_capacity(16))),
+        }
+    }
+
+    /// Control thread: enqueue an event (and free effects the chain gave back).
+    pub fn send(&self, ev: ParamEvent) {
+        self.events.lock().push(ev);
+        self.retired.lock().clear();
+    }
+
+    /// Control thread: remove the effect at `index` from the running chain.
+    /// The receiver yields its slot once the effect's `tail_samples()` have
+    /// played out (`slot.inner` is the effect); it disconnects if `index` was
+    /// out of range.
+    pub fn pop_effect(&self, index: usize) -> Receiver<Slot> {
+        let (reply, rx) = std::sync::mpsc::sync_channel(1);
+        self.send(ParamEvent::PopEffect { index, reply });
+        rx
+    }
+
+    /// Control thread: replace the effect at `index` in the running chain with
+    /// `fx` (see `graph::prepared_slot`) at the start of the next block. The
+    /// receiver yields the old slot, to be dropped off the audio thread; it
+    /// disconnects if `index` was out of range.
+    pub fn swap_effect(&self, index: usize, fx: Slot) -> Receiver<Slot> {
+        let (reply, rx) = std::sync::mpsc::sync_channel(1);
+        self.send(ParamEvent::SwapEffect { index, fx, reply });
+        rx
+    }
+
+    /// Control thread: empty the running chain at the start of the next block,
+    /// rather than one `RemoveEffect` per slot that may land in different blocks.
+    pub fn remove_all(&self) {
+        self.send(ParamEvent::RemoveAll);
+    }
+
+    /// Control thread: replace the running chain with already-prepared slots
+    /// (see `graph::prepared_slot`). The removal and the inserts are queued
+    /// together, so the chain switches over between two blocks and never
+    /// plays one with only some of them. Old slots are retired as with
+    /// `RemoveEffect`; slots past `graph::MAX_EFFECTS` are retired unused.
+    pub fn replace_all(&self, effects: Vec<Slot>) {
+        let mut events = self.events.lock();
+        events.push(ParamEvent::RemoveAll);
+        events.extend(effects.into_iter().enumerate().map(|(index, fx)| ParamEvent::InsertEffect { index, fx }));
+        drop(events);
+        self.retired.lock().clear();
+    }
+
+    /// Audio thread: hand every pending event to `f`; no-op if contended.
+    pub fn drain(&self, mut f: impl FnMut(ParamEvent)) {
+        if let Some(mut events) = self.events.try_lock() {
+            for ev in events.drain(..) {
+                f(ev);
+            }
+        }
+    }
+
+    /// Audio thread: pass a removed slot back for the control thread to drop.
+    /// Gives it back if the lock is contended or there is no spare capacity.
+    pub fn retire(&self, fx: Slot) -> Result<(), Slot> {
+        match self.retired.try_lock() {
+            Some(mut r) if r.len() < r.capacity() => {
+                r.push(fx);
+                Ok(())
+            }
+            _ => Err(fx),
+        }
+    }
+}
@@ -0,0 +1,555 @@
+//! CLAP plugins as chain effects.
+//!
+//! The host side of the CLAP ABI that bord needs (entry, plugin factory,
+//! plugin, process, the `clap.params` and `clap.audio-ports` extensions) is
+//! declared here rather than pulled in from `clap-sys`. Parameters are
+//! addressed by their CLAP name through `set_param_db` / `set_param`.
+//! Input port channel `i` reads chain channel `i % channels`; output port
+//! channel `c` replaces chain channel `c`, and chain channels past the output
+//! port's pass through dry.
+
+use crate::dsp::effect::Effect;
+use anyhow::{anyhow, Context, Result};
+use libloading::Library;
+use std::ffi::{c_char, c_void, CStr, CString};
+use std::path::Path;
+use std::ptr;
+
+/// Largest block handed to `clap_plugin::process`; longer blocks are split.
+const MAX_BLOCK: usize = 4096;
+/// Parameter changes held for the next `process` call; more are dropped.
+const MAX_PENDING_PARAMS: usize = 64;
+
+const CLAP_VERSION: ClapVersion = ClapVersion { major: 1, minor: 2, revision: 0 };
+const CLAP_PLUGIN_FACTORY_ID: &CStr = c"clap.plugin-factory";
+const CLAP_EXT_PARAMS: &CStr = c"clap.params";
+const CLAP_EXT_AUDIO_PORTS: &CStr = c"clap.audio-ports";
+const CLAP_CORE_EVENT_SPACE_ID: u16 = 0;
+const CLAP_EVENT_PARAM_VALUE: u16 = 5;
+const CLAP_PROCESS_ERROR: i32 = 0;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct ClapVersion {
+    major: u32,
+    minor: u32,
+    revision: u32,
+}
+
+#[repr(C)]
+struct ClapPluginEntry {
+    clap_version: ClapVersion,
+    init: unsafe extern "C" fn(plugin_path: *const c_char) -> bool,
+    deinit: unsafe extern "C" fn(),
+    get_factory: unsafe extern "C" fn(factory_id: *const c_char) -> *const c_void,
+}
+
+#[repr(C)]
+struct ClapPluginFactory {
+    get_plugin_count: unsafe extern "C" fn(*const ClapPluginFactory) -> u32,
+    get_plugin_descriptor: unsafe extern "C" fn(*const ClapPluginFactory, u32) -> *const ClapPluginDescriptor,
+    create_plugin: unsafe extern "C" fn(*const ClapPluginFactory, *const ClapHost, *const c_char) -> *const ClapPlugin,
+}
+
+#[repr(C)]
+struct ClapPluginDescriptor {
+    clap_version: ClapVersion,
+    id: *const c_char,
+    name: *const c_char,
+    vendor: *const c_char,
+    url: *const c_char,
+    manual_url: *const c_char,
+    support_url: *const c_char,
+    version: *const c_char,
+    description: *const c_char,
+    features: *const *const c_char,
+}
+
+#[repr(C)]
+struct ClapHost {
+    clap_version: ClapVersion,
+    host_data: *mut c_void,
+    name: *const c_char,
+    vendor: *const c_char,
+    url: *const c_char,
+    version: *const c_char,
+    get_extension: unsafe extern "C" fn(*const ClapHost, *const c_char) -> *const c_void,
+    request_restart: unsafe extern "C" fn(*const ClapHost),
+    request_process: unsafe extern "C" fn(*const ClapHost),
+    request_callback: unsafe extern "C" fn(*const ClapHost),
+}
+
+#[repr(C)]
+struct ClapPlugin {
+    desc: *const ClapPluginDescriptor,
+    plugin_data: *mut c_void,
+    init: unsafe extern "C" fn(*const ClapPlugin) -> bool,
+    destroy: unsafe extern "C" fn(*const ClapPlugin),
+    activate: unsafe extern "C" fn(*const ClapPlugin, f64, u32, u32) -> bool,
+    deactivate: unsafe extern "C" fn(*const ClapPlugin),
+    start_processing: unsafe extern "C" fn(*const ClapPlugin) -> bool,
+    stop_processing: unsafe extern "C" fn(*const ClapPlugin),
+    reset: unsafe extern "C" fn(*const ClapPlugin),
+    process: unsafe extern "C" fn(*const ClapPlugin, *const ClapProcess) -> i32,
+    get_extension: unsafe extern "C" fn(*const ClapPlugin, *const c_char) -> *const c_void,
+    on_main_thread: unsafe extern "C" fn(*const ClapPlugin),
+}
+
+#[repr(C)]
+struct ClapAudioBuffer {
+    data32: *mut *mut f32,
+    data64: *mut *mut f64,
+    channel_count: u32,
+    latency: u32,
+    constant_mask: u64,
+}
+
+#[repr(C)]
+struct ClapProcess {
+    steady_time: i64,
+    frames_count: u32,
+    transport: *const c_void,
+    audio_inputs: *const ClapAudioBuffer,
+    audio_outputs: *mut ClapAudioBuffer,
+    audio_inputs_count: u32,
+    audio_outputs_count: u32,
+    in_events: *const ClapInputEvents,
+    out_events: *const ClapOutputEvents,
+}
+
+#[repr(C)]
+struct ClapEventHeader {
+    size: u32,
+    time: u32,
+    space_id: u16,
+    type_: u16,
+    flags: u32,
+}
+
+#[repr(C)]
+struct ClapEventParamValue {
+    header: ClapEventHeader,
+    param_id: u32,
+    cookie: *mut c_void,
+    note_id: i32,
+    port_index: i16,
+    channel: i16,
+    key: i16,
+    value: f64,
+}
+
+#[repr(C)]
+struct ClapInputEvents {
+    ctx: *mut c_void,
+    size: unsafe extern "C" fn(*const ClapInputEvents) -> u32,
+    get: unsafe extern "C" fn(*const ClapInputEvents, u32) -> *const ClapEventHeader,
+}
+
+#[repr(C)]
+struct ClapOutputEvents {
+    ctx: *mut c_void,
+    try_push: unsafe extern "C" fn(*const ClapOutputEvents, *const ClapEventHeader) -> bool,
+}
+
+#[repr(C)]
+struct ClapParamInfo {
+    id: u32,
+    flags: u32,
+    cookie: *mut c_void,
+    name: [c_char; 256],
+    module: [c_char; 1024],
+    min_value: f64,
+    max_value: f64,
+    default_value: f64,
+}
+
+#[repr(C)]
+struct ClapPluginParams {
+    count: unsafe extern "C" fn(*const ClapPlugin) -> u32,
+    get_info: unsafe extern "C" fn(*const ClapPlugin, u32, *mut ClapParamInfo) -> bool,
+    get_value: unsafe extern "C" fn(*const ClapPlugin, u32, *mut f64) -> bool,
+    value_to_text: unsafe extern "C" fn(*const ClapPlugin, u32, f64, *mut c_char, u32) -> bool,
+    text_to_value: unsafe extern "C" fn(*const ClapPlugin, u32, *const c_char, *mut f64) -> bool,
+    flush: unsafe extern "C" fn(*const ClapPlugin, *const ClapInputEvents, *const ClapOutputEvents),
+}
+
+#[repr(C)]
+struct ClapAudioPortInfo {
+    id: u32,
+    name: [c_char; 256],
+    flags: u32,
+    channel_count: u32,
+    port_type: *const c_char,
+    in_place_pair: u32,
+}
+
+#[repr(C)]
+struct ClapPluginAudioPorts {
+    count: unsafe extern "C" fn(*const ClapPlugin, bool) -> u32,
+    get: unsafe extern "C" fn(*const ClapPlugin, u32, bool, *mut ClapAudioPortInfo) -> bool,
+}
+
+// bord offers the plugin no host extensions and ignores its requests: it
+// isn't restarted or called back on the main thread, only processed.
+unsafe extern "C" fn host_get_extension(_: *const ClapHost, _: *const c_char) -> *const c_void {
+    ptr::null()
+}
+unsafe extern "C" fn host_request(_: *const ClapHost) {}
+
+/// The `ClapEffect::pending` a list from `event_lists` points at.
+unsafe fn pending_events<'a>(list: *const ClapInputEvents) -> &'a [ClapEventParamValue] {
+    &*((*list).ctx as *const Vec<ClapEventParamValue>)
+}
+unsafe extern "C" fn events_size(list: *const ClapInputEvents) -> u32 {
+    pending_events(list).len() as u32
+}
+unsafe extern "C" fn events_get(list: *const ClapInputEvents, index: u32) -> *const ClapEventHeader {
+    match pending_events(list).get(index as usize) {
+        Some(ev) => &ev.header,
+        None => ptr::null(),
+    }
+}
+// Parameter changes the plugin makes itself aren't tracked.
+unsafe extern "C" fn events_try_push(_: *const ClapOutputEvents, _: *const ClapEventHeader) -> bool {
+    true
+}
+
+/// Where the plugin is in CLAP's lifecycle. `Drop` and `prepare()` walk it
+/// back down in order: stop processing, deactivate, destroy.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum ClapState {
+    Initialized,
+    Active,
+    Processing,
+}
+
+struct ClapParam {
+    id: u32,
+    name: String,
+}
+
+pub struct ClapEffect {
+    plugin: *const ClapPlugin,
+    params_ext: *const ClapPluginParams,
+    state: ClapState,
+    name: String,
+    params: Vec<ClapParam>,
+    pending: Vec<ClapEventParamValue>, // param changes for the next process call
+    audio_in: usize,
+    audio_out: usize,
+    channels: usize,
+    failed: bool, // activate or process failed; pass through from then on
+    steady_time: i64,
+    inputs: Vec<Vec<f32>>,
+    outputs: Vec<Vec<f32>>,
+    input_ptrs: Vec<*mut f32>,
+    output_ptrs: Vec<*mut f32>,
+    _host: Box<ClapHost>, // the plugin keeps a pointer to it
+    entry: *const ClapPluginEntry,
+    _lib: Library, // dropped after `Drop::drop` has deinitialised `entry`
+}
+
+// Safety: the plugin is only called from whichever thread currently owns the
+// effect, and the raw pointers all point into it or into `_lib`.
+unsafe impl Send for ClapEffect {}
+
+impl ClapEffect {
+    /// Load the CLAP bundle at `path` and create its plugin with `plugin_id`
+    /// (e.g. "com.u-he.diva"). The plugin is activated in `prepare()`.
+    pub fn load(path: &Path, plugin_id: &str) -> Result<Box<dyn Effect>> {
+        // Safety: loading runs the library's initialisers; plugins are trusted code.
+        let lib = unsafe { Library::new(path) }.with_context(|| format!("Failed to load CLAP plugin {}", path.display()))?;
+        let path_c = CString::new(path.to_string_lossy().as_bytes())?;
+        let id_c = CString::new(plugin_id)?;
+        // Safety: `clap_entry` is a `clap_plugin_entry_t` per the CLAP ABI, and
+        // every pointer below comes from the plugin and is checked for null.
+        unsafe {
+            let entry: *const ClapPluginEntry =
+                *lib.get::<*const ClapPluginEntry>(b"clap_entry").context("Not a CLAP plugin: no clap_entry")?;
+            if entry.is_null() || (*entry).clap_version.major < 1 {
+                return Err(anyhow!("{}: unsupported CLAP version", path.display()));
+            }
+            if !((*entry).init)(path_c.as_ptr()) {
+                return Err(anyhow!("{}: clap_entry.init failed", path.display()));
+            }
+            let host = Box::new(ClapHost {
+                clap_version: CLAP_VERSION,
+                host_data: ptr::null_mut(),
+                name: c"bord".as_ptr(),
+                vendor: c"bord".as_ptr(),
+                url: c"".as_ptr(),
+                version: c"0.1.0".as_ptr(),
+                get_extension: host_get_extension,
+                request_restart: host_request,
+                request_process: host_request,
+                request_callback: host_request,
+            });
+            let plugin = match create_plugin(entry, &host, &id_c) {
+                Ok(plugin) => plugin,
+                Err(e) => {
+                    ((*entry).deinit)();
+                    return Err(e.context(format!("{}", path.display())));
+                }
+            };
+            let mut fx = ClapEffect {
+                plugin,
+                params_ext: ptr::null(),
+                state: ClapState::Initialized,
+                name: cstr_or(plugin_desc_name(plugin), plugin_id),
+                params: Vec::new(),
+                pending: Vec::with_capacity(MAX_PENDING_PARAMS),
+                audio_in: 0,
+                audio_out: 0,
+                channels: 1,
+                failed: false,
+                steady_time: 0,
+                inputs: Vec::new(),
+                outputs: Vec::new(),
+                input_ptrs: Vec::new(),
+                output_ptrs: Vec::new(),
+                _host: host,
+                entry,
+                _lib: lib,
+            };
+            fx.read_extensions();
+            if fx.audio_out == 0 {
+                return Err(anyhow!("CLAP plugin {plugin_id} has no audio outputs"));
+            }
+            Ok(Box::new(fx))
+        }
+    }
+
+    /// Set the parameter called `name` (its CLAP display name) to `value`,
+    /// in the parameter's own units. Returns false if there is no such
+    /// parameter or too many changes are already waiting for `process`.
+    pub fn set_param(&mut self, name: &str, value: f64) -> bool {
+        let Some(p) = self.params.iter().find(|p| p.name == name) else {
+            return false;
+        };
+        if self.pending.len() == self.pending.capacity() {
+            return false;
+        }
+        self.pending.push(ClapEventParamValue {
+            header: ClapEventHeader {
+                size: size_of::<ClapEventParamValue>() as u32,
+                time: 0,
+                space_id: CLAP_CORE_EVENT_SPACE_ID,
+                type_: CLAP_EVENT_PARAM_VALUE,
+                flags: 0,
+            },
+            param_id: p.id,
+            cookie: ptr::null_mut(),
+            // -1: every note, port, channel and key.
+            note_id: -1,
+            port_index: -1,
+            channel: -1,
+            key: -1,
+            value,
+        });
+        if self.state != ClapState::Processing {
+            self.flush_params();
+        }
+        true
+    }
+
+    /// Parameter names, as `set_param` takes them.
+    pub fn param_names(&self) -> impl Iterator<Item = &str> {
+        self.params.iter().map(|p| p.name.as_str())
+    }
+
+    /// Read the parameter list and main audio port widths. Only valid once
+    /// the plugin is initialised.
+    unsafe fn read_extensions(&mut self) {
+        let plugin = self.plugin;
+        let params = ((*plugin).get_extension)(plugin, CLAP_EXT_PARAMS.as_ptr()) as *const ClapPluginParams;
+        if !params.is_null() {
+            self.params_ext = params;
+            for i in 0..((*params).count)(plugin) {
+                let mut info: ClapParamInfo = std::mem::zeroed();
+                if ((*params).get_info)(plugin, i, &mut info) {
+                    let name = CStr::from_ptr(info.name.as_ptr()).to_string_lossy().into_owned();
+                    self.params.push(ClapParam { id: info.id, name });
+                }
+            }
+        }
+        let ports = ((*plugin).get_extension)(plugin, CLAP_EXT_AUDIO_PORTS.as_ptr()) as *const ClapPluginAudioPorts;
+        if !ports.is_null() {
+            let main = |is_input| {
+                let mut info: ClapAudioPortInfo = std::mem::zeroed();
+                let found = ((*ports).count)(plugin, is_input) > 0 && ((*ports).get)(plugin, 0, is_input, &mut info);
+                if found { info.channel_count as usize } else { 0 }
+            };
+            self.audio_in = main(true);
+            self.audio_out = main(false);
+        }
+    }
+
+    /// Hand pending parameter changes to the plugin outside `process`.
+    fn flush_params(&mut self) {
+        if self.params_ext.is_null() || self.pending.is_empty() {
+            return;
+        }
+        let (input, output) = self.event_lists();
+        // Safety: the plugin isn't processing, which is when CLAP allows flush.
+        unsafe { ((*self.params_ext).flush)(self.plugin, &input, &output) };
+        self.pending.clear();
+    }
+
+    fn event_lists(&mut self) -> (ClapInputEvents, ClapOutputEvents) {
+        let input =
+            ClapInputEvents { ctx: &mut self.pending as *mut Vec<_> as *mut c_void, size: events_size, get: events_get };
+        (input, ClapOutputEvents { ctx: ptr::null_mut(), try_push: events_try_push })
+    }
+
+    /// Walk the plugin back down to `target` (Initialized or Active).
+    fn leave_state(&mut self, target: ClapState) {
+        // Safety: each call is the one CLAP allows from the current state.
+        unsafe {
+            if self.state == ClapState::Processing && target != ClapState::Processing {
+                ((*self.plugin).stop_processing)(self.plugin);
+                self.state = ClapState::Active;
+            }
+            if self.state == ClapState::Active && target == ClapState::Initialized {
+                ((*self.plugin).deactivate)(self.plugin);
+                self.state = ClapState::Initialized;
+            }
+        }
+    }
+}
+
+/// Find `plugin_id` in the entry's plugin factory, create and init it.
+unsafe fn create_plugin(entry: *const ClapPluginEntry, host: &ClapHost, plugin_id: &CStr) -> Result<*const ClapPlugin> {
+    let factory = ((*entry).get_factory)(CLAP_PLUGIN_FACTORY_ID.as_ptr()) as *const ClapPluginFactory;
+    if factory.is_null() {
+        return Err(anyhow!("no CLAP plugin factory"));
+    }
+    let known = (0..((*factory).get_plugin_count)(factory))
+        .map(|i| ((*factory).get_plugin_descriptor)(factory, i))
+        .filter(|d| !d.is_null() && !(**d).id.is_null())
+        .any(|d| CStr::from_ptr((*d).id) == plugin_id);
+    if !known {
+        return Err(anyhow!("no CLAP plugin with id {plugin_id:?}"));
+    }
+    let plugin = ((*factory).create_plugin)(factory, host, plugin_id.as_ptr());
+    if plugin.is_null() {
+        return Err(anyhow!("creating CLAP plugin {plugin_id:?} failed"));
+    }
+    if !((*plugin).init)(plugin) {
+        ((*plugin).destroy)(plugin);
+        return Err(anyhow!("CLAP plugin {plugin_id:?} failed to initialise"));
+    }
+    Ok(plugin)
+}
+
+unsafe fn plugin_desc_name(plugin: *const ClapPlugin) -> *const c_char {
+    let desc = (*plugin).desc;
+    if desc.is_null() { ptr::null() } else { (*desc).name }
+}
+
+unsafe fn cstr_or(s: *const c_char, fallback: &str) -> String {
+    if s.is_null() { fallback.to_string() } else { CStr::from_ptr(s).to_string_lossy().into_owned() }
+}
+
+impl Drop for ClapEffect {
+    fn drop(&mut self) {
+        self.leave_state(ClapState::Initialized);
+        // Safety: the plugin is deactivated; destroy it, then release the entry.
+        unsafe {
+            ((*self.plugin).destroy)(self.plugin);
+            ((*self.entry).deinit)();
+        }
+    }
+}
+
+impl Effect for ClapEffect {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn prepare(&mut self, sr: u32, channels: u16) {
+        self.leave_state(ClapState::Initialized);
+        self.channels = channels.max(1) as usize;
+        self.inputs = vec![vec![0.0; MAX_BLOCK]; self.audio_in];
+        self.outputs = vec![vec![0.0; MAX_BLOCK]; self.audio_out];
+        self.input_ptrs = self.inputs.iter_mut().map(|b| b.as_mut_ptr()).collect();
+        self.output_ptrs = self.outputs.iter_mut().map(|b| b.as_mut_ptr()).collect();
+        self.steady_time = 0;
+        // Safety: the plugin is initialised and inactive.
+        self.failed = !unsafe { ((*self.plugin).activate)(self.plugin, sr as f64, 1, MAX_BLOCK as u32) };
+        if self.failed {
+            crate::bord_log!("{}: CLAP activate failed", self.name);
+        } else {
+            self.state = ClapState::Active;
+        }
+    }
+
+    fn set_param_db(&mut self, key: &str, db: f32) {
+        self.set_param(key, db as f64);
+    }
+
+    fn process(&mut self, block: &mut [f32]) {
+        if self.failed || self.state == ClapState::Initialized {
+            return; // not prepared, or the plugin failed: pass through
+        }
+        if self.state == ClapState::Active {
+            // Safety: active and not yet processing; this is the audio thread.
+            if !unsafe { ((*self.plugin).start_processing)(self.plugin) } {
+                self.failed = true;
+                return;
+            }
+            self.state = ClapState::Processing;
+        }
+        let channels = self.channels;
+        for chunk in block.chunks_mut(MAX_BLOCK * channels) {
+            let frames = chunk.len() / channels;
+            for (i, input) in self.inputs.iter_mut().enumerate() {
+                for (f, s) in input[..frames].iter_mut().enumerate() {
+                    *s = chunk[f * channels + i % channels];
+                }
+            }
+            let audio_in = ClapAudioBuffer {
+                data32: self.input_ptrs.as_mut_ptr(),
+                data64: ptr::null_mut(),
+                channel_count: self.audio_in as u32,
+                latency: 0,
+                constant_mask: 0,
+            };
+            let mut audio_out = ClapAudioBuffer {
+                data32: self.output_ptrs.as_mut_ptr(),
+                data64: ptr::null_mut(),
+                channel_count: self.audio_out as u32,
+                latency: 0,
+                constant_mask: 0,
+            };
+            let (in_events, out_events) = self.event_lists();
+            let process = ClapProcess {
+                steady_time: self.steady_time,
+                frames_count: frames as u32,
+                transport: ptr::null(),
+                audio_inputs: &audio_in,
+                audio_outputs: &mut audio_out,
+                audio_inputs_count: u32::from(self.audio_in > 0),
+                audio_outputs_count: 1,
+                in_events: &in_events,
+                out_events: &out_events,
+            };
+            // Safety: buffers hold `frames` samples per port channel read from the plugin.
+            let status = unsafe { ((*self.plugin).process)(self.plugin, &process) };
+            self.pending.clear();
+            if status == CLAP_PROCESS_ERROR {
+                self.failed = true;
+                return;
+            }
+            self.steady_time += frames as i64;
+            for (c, output) in self.outputs.iter().enumerate().take(channels) {
+                for (f, &s) in output[..frames].iter().enumerate() {
+                    chunk[f * channels + c] = s;
+                }
+            }
+        }
+    }
+
+    fn memory_usage_bytes(&self) -> usize {
+        (self.inputs.len() + self.outputs.len()) * MAX_BLOCK * size_of::<f32>()
+    }
+}
@@ -0,0 +1,159 @@
+//! LV2 plugins as chain effects, hosted with `livi` (requires lilv on the system).
+//!
+//! Control input ports are addressed by their LV2 symbol through
+//! `set_param_db` / `set_param`. Plugins with one audio input and one output
+//! run one instance per channel; otherwise audio port `i` maps to channel
+//! `i % channels` and channels without an output port pass through dry.
+
+use crate::dsp::effect::Effect;
+use anyhow::{anyhow, Result};
+use livi::{EmptyPortConnections, FeaturesBuilder, PortIndex, PortType};
+
+/// Largest block handed to `Instance::run`; longer blocks are split.
+const MAX_BLOCK: usize = 4096;
+
+struct Control {
+    symbol: String,
+    index: PortIndex,
+    value: f32,
+}
+
+pub struct Lv2Effect {
+    world: livi::World, // owns the plugin descriptions `plugin` refers to
+    plugin: livi::Plugin,
+    name: String,
+    controls: Vec<Control>,
+    audio_in: usize,
+    audio_out: usize,
+    channels: usize,
+    instances: Vec<livi::Instance>,
+    failed: bool, // a run() error; pass through from then on
+    inputs: Vec<Vec<f32>>,
+    outputs: Vec<Vec<f32>>,
+}
+
+// Safety: lilv data is only touched by whichever thread currently owns the effect.
+unsafe impl Send for Lv2Effect {}
+
+impl Lv2Effect {
+    /// Find the installed plugin with `uri`; it is instantiated in `prepare()`.
+    pub fn load(uri: &str) -> Result<Box<dyn Effect>> {
+        let world = livi::World::new();
+        let plugin = world.plugin_by_uri(uri).ok_or_else(|| anyhow!("No LV2 plugin with URI {uri}"))?;
+        let mut controls = Vec::new();
+        let (mut audio_in, mut audio_out) = (0, 0);
+        for port in plugin.ports() {
+            match port.port_type {
+                PortType::ControlInput => {
+                    controls.push(Control { symbol: port.symbol, index: port.index, value: port.default_value })
+                }
+                PortType::AudioInput => audio_in += 1,
+                PortType::AudioOutput => audio_out += 1,
+                _ => {}
+            }
+        }
+        if audio_out == 0 {
+            return Err(anyhow!("LV2 plugin {uri} has no audio outputs"));
+        }
+        let name = plugin.name();
+        Ok(Box::new(Lv2Effect {
+            world,
+            plugin,
+            name,
+            controls,
+            audio_in,
+            audio_out,
+            channels: 1,
+            instances: Vec::new(),
+            failed: false,
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+        }))
+    }
+
+    /// Write `value` to the control input with LV2 symbol `symbol`.
+    /// Returns false if the plugin has no such port.
+    pub fn set_param(&mut self, symbol: &str, value: f32) -> bool {
+        let Some(c) = self.controls.iter_mut().find(|c| c.symbol == symbol) else {
+            return false;
+        };
+        c.value = value;
+        for inst in &mut self.instances {
+            inst.set_control_input(c.index, value);
+        }
+        true
+    }
+
+    fn per_channel(&self) -> bool {
+        self.audio_in <= 1 && self.audio_out == 1
+    }
+}
+
+impl Effect for Lv2Effect {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn prepare(&mut self, sr: u32, channels: u16) {
+        self.channels = channels.max(1) as usize;
+        let count = if self.per_channel() { self.channels } else { 1 };
+        let features = self.world.build_features(FeaturesBuilder { min_block_length: 1, max_block_length: MAX_BLOCK });
+        self.instances.clear();
+        self.failed = false;
+        for _ in 0..count {
+            // Safety: running plugin code; LV2 plugins are trusted like native ones.
+            match unsafe { self.plugin.instantiate(features.clone(), sr as f64) } {
+                Ok(mut inst) => {
+                    for c in &self.controls {
+                        inst.set_control_input(c.index, c.value);
+                    }
+                    self.instances.push(inst);
+                }
+                Err(e) => {
+                    crate::bord_log!("{}: LV2 instantiate failed: {e}", self.name);
+                    self.instances.clear();
+                    break;
+                }
+            }
+        }
+        self.inputs = vec![vec![0.0; MAX_BLOCK]; self.audio_in];
+        self.outputs = vec![vec![0.0; MAX_BLOCK]; self.audio_out];
+    }
+
+    fn set_param_db(&mut self, key: &str, db: f32) {
+        self.set_param(key, db);
+    }
+
+    fn process(&mut self, block: &mut [f32]) {
+        if self.instances.is_empty() || self.failed {
+            return; // not prepared, or the plugin failed: pass through
+        }
+        let channels = self.channels;
+        let per_channel = self.per_channel();
+        for chunk in block.chunks_mut(MAX_BLOCK * channels) {
+            let frames = chunk.len() / channels;
+            for (n, inst) in self.instances.iter_mut().enumerate() {
+                for (i, input) in self.inputs.iter_mut().enumerate() {
+                    let ch = if per_channel { n } else { i % channels };
+                    for (f, s) in input[..frames].iter_mut().enumerate() {
+                        *s = chunk[f * channels + ch];
+                    }
+                }
+                let ports = EmptyPortConnections::new()
+                    .with_audio_inputs(self.inputs.iter().map(|b| &b[..frames]))
+                    .with_audio_outputs(self.outputs.iter_mut().map(|b| &mut b[..frames]));
+                // Safety: buffers match the port counts read from the plugin.
+                if unsafe { inst.run(frames, ports) }.is_err() {
+                    self.failed = true;
+                    return;
+                }
+                for (o, output) in self.outputs.iter().enumerate().take(channels) {
+                    let ch = if per_channel { n } else { o };
+                    for (f, &s) in output[..frames].iter().enumerate() {
+                        chunk[f * channels + ch] = s;
+                    }
+                }
+            }
+        }
+    }
+}
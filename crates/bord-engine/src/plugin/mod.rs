@@ -0,0 +1,115 @@
+//! Effects loaded from shared libraries at runtime.
+//!
+//! A plugin exports three `extern "C"` symbols (use `bord_plugin_sdk::export_effect!`):
+//! - `bord_plugin_abi_version() -> u32`, which must equal `BORD_PLUGIN_ABI_VERSION`
+//! - `bord_create_effect() -> *mut Box<dyn Effect>`
+//! - `bord_destroy_effect(*mut Box<dyn Effect>)`
+//!
+//! `dyn Effect` is a fat pointer, so it crosses the boundary boxed once more.
+//! Trait-object layout is only stable for the same compiler and bord version,
+//! which is what the ABI version guards.
+
+#[cfg(feature = "clap-plugins")]
+pub mod clap;
+#[cfg(feature = "lv2")]
+pub mod lv2;
+
+use crate::dsp::effect::{Effect, ProcessContext};
+use crate::BORD_PLUGIN_ABI_VERSION;
+use anyhow::{anyhow, Context, Result};
+use libloading::Library;
+use std::path::Path;
+
+type CreateFn = unsafe extern "C" fn() -> *mut Box<dyn Effect>;
+type DestroyFn = unsafe extern "C" fn(*mut Box<dyn Effect>);
+type AbiFn = unsafe extern "C" fn() -> u32;
+
+pub struct PluginHost;
+
+impl PluginHost {
+    /// Load the library at `path` and instantiate its effect.
+    pub fn load(path: &Path) -> Result<Box<dyn Effect>> {
+        // Safety: loading runs the library's initialisers; plugins are trusted code.
+        let lib = unsafe { Library::new(path) }
+            .with_context(|| format!("Failed to load plugin {}", path.display()))?;
+        // Safety: symbol types match the contract documented above.
+        unsafe {
+            let abi: AbiFn = *lib
+                .get::<AbiFn>(b"bord_plugin_abi_version")
+                .context("Plugin has no bord_plugin_abi_version")?;
+            let version = abi();
+            if version != BORD_PLUGIN_ABI_VERSION {
+                return Err(anyhow!(
+                    "{}: plugin ABI {version}, host expects {BORD_PLUGIN_ABI_VERSION}",
+                    path.display()
+                ));
+            }
+            let create: CreateFn = *lib.get::<CreateFn>(b"bord_create_effect").context("Plugin has no bord_create_effect")?;
+            let destroy: DestroyFn = *lib.get::<DestroyFn>(b"bord_destroy_effect").context("Plugin has no bord_destroy_effect")?;
+            let fx = create();
+            if fx.is_null() {
+                return Err(anyhow!("{}: bord_create_effect returned null", path.display()));
+            }
+            Ok(Box::new(PluginEffect { fx, destroy, _lib: lib }))
+        }
+    }
+}
+
+/// Owns a plugin-allocated effect; frees it through the plugin, then unloads.
+struct PluginEffect {
+    fx: *mut Box<dyn Effect>,
+    destroy: DestroyFn,
+    _lib: Library, // dropped after `Drop::drop`, i.e. after `destroy` ran
+}
+
+// Safety: the pointee is a `dyn Effect`, which is `Send`; we hold the only pointer.
+unsafe impl Send for PluginEffect {}
+
+impl PluginEffect {
+    fn inner(&self) -> &dyn Effect {
+        // Safety: non-null, created by the plugin and alive until drop.
+        unsafe { &**self.fx }
+    }
+    fn inner_mut(&mut self) -> &mut dyn Effect {
+        unsafe { &mut **self.fx }
+    }
+}
+
+impl Drop for PluginEffect {
+    fn drop(&mut self) {
+        unsafe { (self.destroy)(self.fx) }
+    }
+}
+
+impl Effect for PluginEffect {
+    fn name(&self) -> &str { self.inner().name() }
+    fn version(&self) -> (u32, u32, u32) { self.inner().version() }
+    fn prepare(&mut self, sr: u32, channels: u16) { self.inner_mut().prepare(sr, channels) }
+    fn prepare_hot(&mut self, sr: u32, channels: u16) -> bool { self.inner_mut().prepare_hot(sr, channels) }
+    fn set_param_db(&mut self, key: &str, db: f32) { self.inner_mut().set_param_db(key, db) }
+    fn param_descriptors(&self) -> &[crate::dsp::effect::ParamDescriptor] { self.inner().param_descriptors() }
+    fn reset_param_to_default(&mut self, key: &str) { self.inner_mut().reset_param_to_default(key) }
+    fn process(&mut self, block: &mut [f32]) { self.inner_mut().process(block) }
+    fn process_with_context(&mut self, block: &mut [f32], ctx: &ProcessContext) { self.inner_mut().process_with_context(block, ctx) }
+    fn in_place_safe(&self) -> bool { self.inner().in_place_safe() }
+    fn process_into(&mut self, input: &[f32], output: &mut [f32]) { self.inner_mut().process_into(input, output) }
+    fn process_stereo(&mut self, left: &mut [f32], right: &mut [f32]) { self.inner_mut().process_stereo(left, right) }
+    fn process_frames(&mut self, frames: &mut [crate::dsp::effect::Frame<2>]) { self.inner_mut().process_frames(frames) }
+    fn process_stereo_with_context(&mut self, left: &mut [f32], right: &mut [f32], ctx: &ProcessContext) {
+        self.inner_mut().process_stereo_with_context(left, right, ctx)
+    }
+    fn process_frames_with_context(&mut self, frames: &mut [crate::dsp::effect::Frame<2>], ctx: &ProcessContext) {
+        self.inner_mut().process_frames_with_context(frames, ctx)
+    }
+    fn supports_stereo_split(&self) -> bool { self.inner().supports_stereo_split() }
+    fn output_channel_count(&self, input_channels: u16) -> u16 { self.inner().output_channel_count(input_channels) }
+    fn latency_samples(&self) -> u32 { self.inner().latency_samples() }
+    fn memory_usage_bytes(&self) -> usize { self.inner().memory_usage_bytes() }
+    fn tail_samples(&self) -> u32 { self.inner().tail_samples() }
+    fn supported_block_sizes(&self) -> Option<(u32, u32)> { self.inner().supported_block_sizes() }
+    fn branches(&self) -> &[Vec<Box<dyn Effect>>] { self.inner().branches() }
+    // A copy made by the plugin wouldn't keep its library loaded.
+    fn clone_fresh(&self) -> Option<Box<dyn Effect>> { None }
+    #[cfg(feature = "async")]
+    fn as_async(&mut self) -> Option<&mut dyn crate::dsp::effect::AsyncEffect> { self.inner_mut().as_async() }
+}
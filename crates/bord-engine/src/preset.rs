@@ -0,0 +1,105 @@
+//! Named effect presets: a chain layout plus the parameters set on each effect,
+//! stored as TOML in `presets_dir()` (`~/.config/bord/presets/<name>.toml`).
+//!
+//! Effects are rebuilt with `dsp::effect_by_name` and their `state` replayed
+//! through `Effect::set_param_db`. Only parameters set while the engine ran
+//! (over IPC or by an earlier preset) are known, so only those are saved.
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Preset {
+    pub name: String,
+    /// Effects in chain order.
+    #[serde(default)]
+    pub effects: Vec<EffectPreset>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EffectPreset {
+    /// Name accepted by `dsp::effect_by_name`, e.g. `"Gain"`.
+    pub type_name: String,
+    /// Parameter key -> value, as passed to `set_param_db`.
+    #[serde(default)]
+    pub state: BTreeMap<String, f32>,
+}
+
+impl Preset {
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        toml::from_str(&text).with_context(|| format!("{} is not a bord preset", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, toml::to_string(self)?).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Fail on the first effect `dsp::effect_by_name` cannot build.
+    pub fn check_buildable(&self) -> Result<()> {
+        match self.effects.iter().find(|fx| crate::dsp::effect_by_name(&fx.type_name).is_none()) {
+            Some(fx) => Err(anyhow!("Preset {:?}: effect {:?} cannot be built by name", self.name, fx.type_name)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// `$XDG_CONFIG_HOME/bord/presets`, falling back to `~/.config/bord/presets`
+/// (`%APPDATA%\bord\presets` on Windows).
+pub fn presets_dir() -> Result<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".config")))
+        .or_else(|| std::env::var_os("APPDATA").map(PathBuf::from))
+        .ok_or_else(|| anyhow!("Cannot locate a config directory (HOME is not set)"))?;
+    Ok(base.join("bord").join("presets"))
+}
+
+/// File a preset called `name` lives in. Names are plain file stems.
+pub fn preset_path(name: &str) -> Result<PathBuf> {
+    if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+        return Err(anyhow!("Invalid preset name {name:?}"));
+    }
+    Ok(presets_dir()?.join(format!("{name}.toml")))
+}
+
+/// Names of the saved presets, sorted. A missing directory means none.
+pub fn list() -> Result<Vec<String>> {
+    let dir = presets_dir()?;
+    let entries = match std::fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", dir.display())),
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|e| e == "toml"))
+        .filter_map(|p| p.file_stem()?.to_str().map(String::from))
+        .collect();
+    names.sort();
+    Ok(names)
+}
+
+pub fn load_named(name: &str) -> Result<Preset> {
+    Preset::load(&preset_path(name)?)
+}
+
+/// Save under `preset.name`, creating the presets directory if needed.
+pub fn save_named(preset: &Preset) -> Result<PathBuf> {
+    let path = preset_path(&preset.name)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    preset.save(&path)?;
+    Ok(path)
+}
+
+pub fn delete(name: &str) -> Result<()> {
+    let path = preset_path(name)?;
+    std::fs::remove_file(&path).with_context(|| format!("Failed to delete {}", path.display()))
+}
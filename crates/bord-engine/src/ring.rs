@@ -0,0 +1,117 @@
+use crate::dsp::aligned_vec::AlignedVec;
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+pub(crate) fn next_pow2(mut x: usize) -> usize {
+    if x <= 1 { return 1; }
+    x -= 1;
+    x |= x >> 1;
+    x |= x >> 2;
+    x |= x >> 4;
+    x |= x >> 8;
+    x |= x >> 16;
+    #[cfg(target_pointer_width = "64")]
+    { x |= x >> 32; }
+    x + 1
+}
+
+/// Lock-free single-producer/single-consumer ring of `Copy` values.
+/// Capacity is rounded up to a power of two; one slot is kept free.
+/// Callers must keep to one thread calling `push_slice` and one calling `pop_into`.
+pub struct SpscRing<T> {
+    // Interior mutability: single producer writes, single consumer reads.
+    buf: UnsafeCell<AlignedVec<T>>,
+    mask: usize,
+    write: AtomicUsize,
+    read: AtomicUsize,
+}
+
+// Safety: we uphold SPSC discipline externally; only one writer and one reader exist.
+// The writer only mutates indices [read..write) advancing write; the reader only
+// reads and advances read. No aliasing writes occur.
+unsafe impl<T: Send> Send for SpscRing<T> {}
+unsafe impl<T: Send> Sync for SpscRing<T> {}
+
+/// The audio sample ring the engine uses between its callbacks.
+pub type SpscRingF32 = SpscRing<f32>;
+
+impl<T: Copy + Default> SpscRing<T> {
+    pub fn with_capacity(cap: usize) -> Self {
+        let cap_pow2 = next_pow2(cap);
+        Self {
+            buf: UnsafeCell::new(AlignedVec::from_elem(T::default(), cap_pow2)),
+            mask: cap_pow2 - 1,
+            write: AtomicUsize::new(0),
+            read: AtomicUsize::new(0),
+        }
+    }
+
+    /// Slots in the buffer (the power of two `with_capacity` rounded up to).
+    pub fn capacity(&self) -> usize {
+        self.mask + 1
+    }
+
+    #[inline]
+    fn len(&self, w: usize, r: usize) -> usize {
+        w.wrapping_sub(r) & self.mask
+    }
+
+    /// Producer: push entire slice; returns false if not enough space.
+    pub fn push_slice(&self, data: &[T]) -> bool {
+        let r = self.read.load(Ordering::Acquire);
+        let w = self.write.load(Ordering::Relaxed);
+        let cap = unsafe { (&*self.buf.get()).len() };
+        let free = cap - self.len(w, r) - 1;
+        if free < data.len() { return false; }
+
+        // Safe because: single producer thread writes, and we never write indices
+        // that the consumer is reading (bounded by free-space check).
+        let buf = unsafe { &mut *self.buf.get() };
+        let mut wi = w;
+        for &v in data {
+            buf[wi & self.mask] = v;
+            wi = wi.wrapping_add(1);
+        }
+        self.write.store(wi, Ordering::Release);
+        true
+    }
+
+    /// Producer: push as much of the slice as fits; returns the samples written.
+    /// Callers pushing interleaved audio should pass whole frames' worth of
+    /// free space or accept that the stream loses channel alignment.
+    pub fn push_slice_partial(&self, data: &[T]) -> usize {
+        let r = self.read.load(Ordering::Acquire);
+        let w = self.write.load(Ordering::Relaxed);
+        let cap = unsafe { (&*self.buf.get()).len() };
+        let free = cap - self.len(w, r) - 1;
+        let n = free.min(data.len());
+
+        // Safe for the same reasons as `push_slice`: at most `free` slots are written.
+        let buf = unsafe { &mut *self.buf.get() };
+        let mut wi = w;
+        for &v in &data[..n] {
+            buf[wi & self.mask] = v;
+            wi = wi.wrapping_add(1);
+        }
+        self.write.store(wi, Ordering::Release);
+        n
+    }
+
+    /// Consumer: pop exactly out.len() samples into out; false if not enough data.
+    pub fn pop_into(&self, out: &mut [T]) -> bool {
+        let w = self.write.load(Ordering::Acquire);
+        let r = self.read.load(Ordering::Relaxed);
+        let avail = self.len(w, r);
+        if avail < out.len() { return false; }
+
+        // Safe because: single consumer thread reads; producer only writes beyond `w`.
+        let buf = unsafe { &*self.buf.get() };
+        let mut ri = r;
+        for o in out.iter_mut() {
+            *o = buf[ri & self.mask];
+            ri = ri.wrapping_add(1);
+        }
+        self.read.store(ri, Ordering::Release);
+        true
+    }
+}
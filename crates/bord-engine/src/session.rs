@@ -0,0 +1,68 @@
+//! Saved sessions: the engine config, the chain layout and MIDI bindings, so a
+//! later `bord run --session` can rebuild what an earlier run had set up.
+//!
+//! Effects are recorded by name and rebuilt with `dsp::effect_by_name`; their
+//! parameters beyond what the config covers (e.g. `gain_db`) are not saved.
+
+use crate::config::{self, ConfigFile};
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Session {
+    pub config: ConfigFile,
+    /// Effect names in chain order, including the ones `config` implies.
+    #[serde(default)]
+    pub chain: Vec<String>,
+    #[serde(default)]
+    pub midi: Vec<SessionMidiBinding>,
+}
+
+/// File form of a `midi::MidiBinding` plus the input it was made on.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SessionMidiBinding {
+    /// Full port name; matched as a substring when the session is loaded.
+    pub device: String,
+    pub channel: u8,
+    pub cc: u8,
+    pub effect_index: usize,
+    pub param: String,
+    pub min: f32,
+    pub max: f32,
+}
+
+impl Session {
+    /// Read a session saved by `save` (TOML for `.toml`, JSON otherwise).
+    pub fn load(path: &Path) -> Result<Self> {
+        serde_json::from_value(config::load_value(path)?)
+            .with_context(|| format!("{} is not a bord session", path.display()))
+    }
+
+    /// Write as TOML for a `.toml` path, pretty JSON otherwise.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let is_toml = path.extension().is_some_and(|e| e.eq_ignore_ascii_case("toml"));
+        let text = if is_toml { toml::to_string(self)? } else { serde_json::to_string_pretty(self)? };
+        std::fs::write(path, text).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Split `chain` around the run of `builtin` effects (which `Engine::start`
+    /// recreates from the config) into the ones inserted before and after it.
+    pub(crate) fn split_chain(&self, builtin: &[&str]) -> Result<(Vec<&str>, Vec<&str>)> {
+        if self.chain.is_empty() {
+            return Ok((Vec::new(), Vec::new()));
+        }
+        let names: Vec<&str> = self.chain.iter().map(String::as_str).collect();
+        let at = names
+            .windows(builtin.len().max(1))
+            .position(|w| w == builtin)
+            .ok_or_else(|| anyhow!("Session chain {names:?} lacks the effects its config implies ({builtin:?})"))?;
+        let (front, back) = (&names[..at], &names[at + builtin.len()..]);
+        if let Some(name) = front.iter().chain(back).find(|n| crate::dsp::effect_by_name(n).is_none()) {
+            return Err(anyhow!("Session effect {name:?} cannot be rebuilt by name"));
+        }
+        Ok((front.to_vec(), back.to_vec()))
+    }
+}
@@ -0,0 +1,175 @@
+//! Helpers for testing effects and chains without audio hardware, behind the
+//! `test-utils` feature (the crate's own tests enable it).
+
+use crate::dsp::effect::Effect;
+use crate::graph::Chain;
+
+/// Stands in for a duplex device: feeds the chain block by block the way the
+/// output callback does, with silence (or a given signal) as the input, so a
+/// chain runs without cpal opening anything.
+#[derive(Debug, Clone, Copy)]
+pub struct SilentDevice {
+    pub sample_rate: u32,
+    pub channels: u16,
+    /// Frames per callback.
+    pub block_frames: usize,
+}
+
+impl SilentDevice {
+    pub fn new(sample_rate: u32, channels: u16, block_frames: usize) -> Self {
+        Self { sample_rate, channels, block_frames: block_frames.max(1) }
+    }
+
+    /// An empty chain in this device's format.
+    pub fn chain(&self) -> Chain {
+        Chain::new(self.sample_rate, self.channels)
+    }
+
+    /// Run `blocks` callbacks of silent input; the output, interleaved.
+    pub fn run(&self, chain: &mut Chain, blocks: usize) -> Vec<f32> {
+        self.run_input(chain, &vec![0.0; blocks * self.block_frames * self.channels as usize])
+    }
+
+    /// Run callbacks with `input` (interleaved) as the device input; the last
+    /// block is padded with silence. The output, interleaved.
+    pub fn run_input(&self, chain: &mut Chain, input: &[f32]) -> Vec<f32> {
+        let block_len = self.block_frames * self.channels as usize;
+        let mut out = Vec::with_capacity(input.len().div_ceil(block_len) * block_len);
+        let mut block = vec![0.0; block_len];
+        for chunk in input.chunks(block_len) {
+            block[..chunk.len()].copy_from_slice(chunk);
+            block[chunk.len()..].fill(0.0);
+            chain.process(&mut block);
+            out.extend_from_slice(&block);
+        }
+        out
+    }
+}
+
+/// `prepare()` `effect` for (`sr`, `channels`) and `process()` a copy of
+/// `input` as one block. The result has `output_channel_count(channels)`
+/// channels, as a chain would pass on.
+pub fn process_effect(effect: &mut dyn Effect, input: &[f32], sr: u32, channels: u16) -> Vec<f32> {
+    effect.prepare(sr, channels);
+    let frames = input.len() / channels.max(1) as usize;
+    let out = effect.output_channel_count(channels) as usize;
+    let mut block = input.to_vec();
+    if out > channels as usize {
+        block.resize(frames * out, 0.0);
+    }
+    effect.process(&mut block);
+    block.truncate(frames * out);
+    block
+}
+
+/// Panic unless the RMS of `actual` is `expected_db` from that of `reference`,
+/// within `tolerance_db`.
+#[track_caller]
+pub fn assert_gain_db(expected_db: f32, actual: &[f32], reference: &[f32], tolerance_db: f32) {
+    let rms = |x: &[f32]| (x.iter().map(|s| s * s).sum::<f32>() / x.len().max(1) as f32).sqrt();
+    let (a, r) = (rms(actual), rms(reference));
+    assert!(r > 0.0, "reference signal is silent");
+    let db = 20.0 * (a / r).log10();
+    assert!(
+        (db - expected_db).abs() <= tolerance_db,
+        "gain is {db:.3} dB, expected {expected_db:.3} dB ± {tolerance_db} dB"
+    );
+}
+
+/// Run the checks every effect should pass, panicking with the effect's name
+/// and what went wrong. At each of `block_sizes` (frames; sizes outside
+/// `supported_block_sizes()` are skipped):
+/// - finite input gives finite output;
+/// - `latency_samples()` stays the same from block to block;
+/// - `prepare()` again after processing resets it: the same input then gives
+///   the same output as after the first `prepare()`, and as a `clone_fresh`
+///   copy if the effect has one;
+/// - `prepare()` twice in a row gives the same output as once.
+#[track_caller]
+pub fn effect_invariants_test(effect: &mut dyn Effect, sr: u32, channels: u16, block_sizes: &[usize]) {
+    let name = effect.name().to_string();
+    let input = invariants_signal(sr, channels);
+    // Different audio to leave state behind before a reset.
+    let other: Vec<f32> = input.iter().rev().map(|s| -s).collect();
+    for &block in block_sizes {
+        let block = block.max(1);
+        if effect.supported_block_sizes().is_some_and(|(lo, hi)| !(lo as usize..=hi as usize).contains(&block)) {
+            continue;
+        }
+        let what = |check: &str| format!("{name} ({channels} ch, {block}-frame blocks): {check}");
+
+        effect.prepare(sr, channels);
+        let first = run_checked(effect, &input, channels, block, &what);
+        run_checked(effect, &other, channels, block, &what);
+        effect.prepare(sr, channels);
+        let reset = run_checked(effect, &input, channels, block, &what);
+        assert_same_output(&first, &reset, &what("output after prepare() again differs from the first run"));
+
+        effect.prepare(sr, channels);
+        effect.prepare(sr, channels);
+        let twice = run_checked(effect, &input, channels, block, &what);
+        assert_same_output(&first, &twice, &what("output after prepare() twice differs from after once"));
+
+        if let Some(mut fresh) = effect.clone_fresh() {
+            fresh.prepare(sr, channels);
+            let fresh = run_checked(&mut *fresh, &input, channels, block, &what);
+            assert_same_output(&first, &fresh, &what("a clone_fresh() copy gives different output"));
+        }
+    }
+}
+
+/// 100 ms of interleaved test audio: an impulse, two sines a little apart
+/// per channel, then full-scale square bursts.
+fn invariants_signal(sr: u32, channels: u16) -> Vec<f32> {
+    let frames = (sr / 10).max(64) as usize;
+    let tau = core::f32::consts::TAU;
+    (0..frames)
+        .flat_map(|f| {
+            (0..channels).map(move |c| {
+                let t = f as f32 / sr as f32;
+                match f {
+                    0 => 1.0,
+                    f if f >= frames * 3 / 4 => if (f / 32) % 2 == 0 { 1.0 } else { -1.0 },
+                    _ => 0.4 * (tau * 440.0 * t + c as f32).sin() + 0.3 * (tau * 5_000.0 * t).sin(),
+                }
+            })
+        })
+        .collect()
+}
+
+/// Process `input` in blocks of `block` frames, panicking on non-finite
+/// output or a latency change. The output, with the effect's output channels.
+fn run_checked(
+    effect: &mut dyn Effect,
+    input: &[f32],
+    channels: u16,
+    block: usize,
+    what: &dyn Fn(&str) -> String,
+) -> Vec<f32> {
+    let (inc, outc) = (channels as usize, effect.output_channel_count(channels) as usize);
+    let latency = effect.latency_samples();
+    let mut out = Vec::with_capacity(input.len() / inc * outc);
+    let mut buf = Vec::with_capacity(block * inc.max(outc));
+    for (n, chunk) in input.chunks(block * inc).enumerate() {
+        let frames = chunk.len() / inc;
+        buf.clear();
+        buf.extend_from_slice(chunk);
+        buf.resize(frames * inc.max(outc), 0.0);
+        effect.process(&mut buf);
+        buf.truncate(frames * outc);
+        if let Some(i) = buf.iter().position(|s| !s.is_finite()) {
+            panic!("{}", what(&format!("non-finite output {} in block {n}, frame {}", buf[i], i / outc)));
+        }
+        assert_eq!(effect.latency_samples(), latency, "{}", what(&format!("latency changed after block {n}")));
+        out.extend_from_slice(&buf);
+    }
+    out
+}
+
+#[track_caller]
+fn assert_same_output(expected: &[f32], actual: &[f32], what: &str) {
+    assert_eq!(expected.len(), actual.len(), "{what} (length)");
+    if let Some(i) = expected.iter().zip(actual).position(|(a, b)| (a - b).abs() > 1e-5) {
+        panic!("{what}: sample {i} is {} instead of {}", actual[i], expected[i]);
+    }
+}
@@ -0,0 +1,123 @@
+//! Per-effect processing time for `Chain::enable_timing`.
+//!
+//! Each effect gets a ring of its last `HISTORY` block times, allocated when
+//! timing is enabled; recording and the periodic callback don't allocate.
+
+use crate::graph::{Slot, MAX_EFFECTS};
+use crate::dsp::effect::Effect;
+use std::time::Instant;
+
+/// Blocks of history kept per effect.
+const HISTORY: usize = 1024;
+
+/// Called from the audio thread with `(name, mean_ns, p99_ns)` per effect, in
+/// chain order. It must be real-time safe: store into atomics, don't lock or allocate.
+pub type TimingCallback = Box<dyn FnMut(&[(&str, u64, u64)]) + Send>;
+
+/// One effect's processing time per block over the recorded history.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EffectTiming {
+    pub name: String,
+    pub mean_ns: u64,
+    pub p99_ns: u64,
+}
+
+pub(crate) struct Timing {
+    nanos: Vec<u64>, // MAX_EFFECTS rows of HISTORY
+    pos: usize,      // next column to write
+    blocks: usize,   // columns filled, up to HISTORY
+    scratch: Vec<u64>,
+    callback: Option<(usize, TimingCallback)>, // every n blocks
+    since_callback: usize,
+}
+
+impl Timing {
+    pub(crate) fn new() -> Self {
+        Self {
+            nanos: vec![0; MAX_EFFECTS * HISTORY],
+            pos: 0,
+            blocks: 0,
+            scratch: Vec::with_capacity(HISTORY),
+            callback: None,
+            since_callback: 0,
+        }
+    }
+
+    pub(crate) fn set_callback(&mut self, every_blocks: usize, cb: TimingCallback) {
+        self.callback = Some((every_blocks.max(1), cb));
+        self.since_callback = 0;
+    }
+
+    /// Forget the history, e.g. because effects moved.
+    pub(crate) fn reset(&mut self) {
+        self.pos = 0;
+        self.blocks = 0;
+        self.since_callback = 0;
+    }
+
+    /// Run `f` as effect `index`'s share of the current block.
+    #[inline]
+    pub(crate) fn time<R>(&mut self, index: usize, f: impl FnOnce() -> R) -> R {
+        let start = Instant::now();
+        let r = f();
+        if index < MAX_EFFECTS {
+            self.nanos[index * HISTORY + self.pos] = start.elapsed().as_nanos() as u64;
+        }
+        r
+    }
+
+    /// Close the current block; fires the callback every `every_blocks`.
+    pub(crate) fn end_block(&mut self, effects: &[Slot]) {
+        self.pos = (self.pos + 1) % HISTORY;
+        self.blocks = (self.blocks + 1).min(HISTORY);
+        let Some((every, _)) = &self.callback else { return };
+        self.since_callback += 1;
+        if self.since_callback < *every {
+            return;
+        }
+        self.since_callback = 0;
+        let mut rows = [("", 0u64, 0u64); MAX_EFFECTS];
+        let n = effects.len().min(MAX_EFFECTS);
+        for (i, (row, fx)) in rows.iter_mut().zip(effects).enumerate() {
+            let recorded = &self.nanos[i * HISTORY..][..self.blocks];
+            let (mean, p99) = stats(recorded, &mut self.scratch);
+            *row = (fx.name(), mean, p99);
+        }
+        if let Some((_, cb)) = self.callback.as_mut() {
+            cb(&rows[..n]);
+        }
+    }
+
+    /// Effect `index`'s recorded block times. Until the ring wraps, the filled
+    /// columns are the first `blocks`.
+    fn row(&self, index: usize) -> &[u64] {
+        &self.nanos[index * HISTORY..][..self.blocks]
+    }
+
+    pub(crate) fn histogram(&self, effects: &[Slot]) -> Vec<EffectTiming> {
+        let mut scratch = Vec::with_capacity(HISTORY);
+        effects
+            .iter()
+            .take(MAX_EFFECTS)
+            .enumerate()
+            .map(|(i, fx)| {
+                let (mean_ns, p99_ns) = stats(self.row(i), &mut scratch);
+                EffectTiming { name: fx.name().to_string(), mean_ns, p99_ns }
+            })
+            .collect()
+    }
+}
+
+/// Mean and 99th percentile of `row`, using `scratch` (no allocation if it
+/// has room for `row`).
+fn stats(row: &[u64], scratch: &mut Vec<u64>) -> (u64, u64) {
+    if row.is_empty() {
+        return (0, 0);
+    }
+    scratch.clear();
+    scratch.extend_from_slice(row);
+    let mean = scratch.iter().sum::<u64>() / row.len() as u64;
+    let at = (row.len() * 99 / 100).min(row.len() - 1);
+    let (_, &mut p99, _) = scratch.select_nth_unstable(at);
+    (mean, p99)
+}
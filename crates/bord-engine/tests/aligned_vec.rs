@@ -0,0 +1,61 @@
+use bord_engine::dsp::aligned_vec::{AlignedVec, ALIGN};
+use bord_engine::ring::SpscRingF32;
+
+#[test]
+fn buffers_start_on_a_cache_line() {
+    for len in [1, 3, 64, 4097] {
+        let v = AlignedVec::from_elem(0.5f32, len);
+        assert_eq!(v.as_ptr() as usize % ALIGN, 0, "len {len}");
+        assert_eq!(v.len(), len);
+        assert!(v.iter().all(|&s| s == 0.5));
+        assert_eq!(v.memory_bytes(), len * 4);
+    }
+    assert!(AlignedVec::<f32>::new().is_empty());
+    assert_eq!(AlignedVec::<f32>::new().memory_bytes(), 0);
+}
+
+#[test]
+fn clones_and_drops_their_elements() {
+    let mut v = AlignedVec::from_elem(String::from("a"), 3);
+    v[1].push('b');
+    let c = v.clone();
+    drop(v);
+    assert_eq!(&*c, ["a", "ab", "a"]);
+}
+
+#[test]
+fn ring_still_round_trips() {
+    let ring = SpscRingF32::with_capacity(8);
+    assert!(ring.push_slice(&[1.0, 2.0, 3.0]));
+    let mut out = [0.0; 3];
+    assert!(ring.pop_into(&mut out));
+    assert_eq!(out, [1.0, 2.0, 3.0]);
+}
+
+#[test]
+fn zero_sized_elements_are_dropped_once_each() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    static CLONES: AtomicUsize = AtomicUsize::new(0);
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    struct Token;
+    impl Clone for Token {
+        fn clone(&self) -> Self {
+            CLONES.fetch_add(1, Ordering::Relaxed);
+            Token
+        }
+    }
+    impl Drop for Token {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    let v = AlignedVec::from_elem(Token, 5);
+    assert_eq!(v.len(), 5);
+    assert_eq!(v.memory_bytes(), 0);
+    drop(v);
+    // The clones plus the value passed in.
+    assert_eq!(CLONES.load(Ordering::Relaxed), 5);
+    assert_eq!(DROPS.load(Ordering::Relaxed), 6);
+}
@@ -0,0 +1,68 @@
+#![cfg(feature = "async")]
+
+use bord_engine::dsp::channel_converter::ChannelConverter;
+use bord_engine::dsp::effect::{AsyncEffect, Effect};
+use bord_engine::dsp::gain::Gain;
+use bord_engine::graph::Chain;
+use futures::future::BoxFuture;
+
+/// Wraps an effect so it is only ever prepared through `prepare_async`.
+struct Slow<E>(E);
+
+impl<E: Effect> Effect for Slow<E> {
+    fn name(&self) -> &str {
+        self.0.name()
+    }
+    fn prepare(&mut self, _sr: u32, _channels: u16) {
+        panic!("prepared synchronously");
+    }
+    fn output_channel_count(&self, input_channels: u16) -> u16 {
+        self.0.output_channel_count(input_channels)
+    }
+    fn process(&mut self, block: &mut [f32]) {
+        self.0.process(block);
+    }
+    fn memory_usage_bytes(&self) -> usize {
+        self.0.memory_usage_bytes()
+    }
+    fn as_async(&mut self) -> Option<&mut dyn AsyncEffect> {
+        Some(self)
+    }
+}
+
+impl<E: Effect> AsyncEffect for Slow<E> {
+    fn prepare_async(&mut self, sr: u32, channels: u16) -> BoxFuture<'_, anyhow::Result<()>> {
+        Box::pin(async move {
+            self.0.prepare(sr, channels);
+            Ok(())
+        })
+    }
+}
+
+#[test]
+fn async_slot_at_partial_mix_blends_with_its_dry_signal() {
+    let mut chain = Chain::new(48_000, 2);
+    chain.push_deferred(Box::new(Slow(Gain::new(-6.0))));
+    chain.set_effect_mix(0, 0.5);
+    chain.prepare_all_blocking(48_000, 2).unwrap();
+    // The slot's dry buffer was sized along with the async prepare.
+    assert!(chain.total_memory_bytes() >= 4096 * 2 * 4);
+
+    let mut block = [0.5f32; 512];
+    chain.process(&mut block);
+    let want = 0.5 * (0.5 + 0.5 * 10f32.powf(-6.0 / 20.0));
+    assert!(block.iter().all(|&s| (s - want).abs() < 1e-5), "{}", block[0]);
+}
+
+#[test]
+fn async_slot_that_reshapes_runs_fully_wet_at_partial_mix() {
+    let mut chain = Chain::new(48_000, 1);
+    chain.push_deferred(Box::new(Slow(ChannelConverter::new(2))));
+    chain.set_effect_mix(0, 0.5);
+    chain.prepare_all_blocking(48_000, 1).unwrap();
+
+    let input = [0.5f32; 256];
+    let mut out = [0.0f32; 512];
+    chain.process_into(&input, &mut out);
+    assert!(out.iter().all(|&s| s == 0.5), "{:?}", &out[..4]);
+}
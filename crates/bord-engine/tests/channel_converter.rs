@@ -0,0 +1,28 @@
+use bord_engine::dsp::channel_converter::ChannelConverter;
+use bord_engine::test_utils::{effect_invariants_test, process_effect};
+
+#[test]
+fn widens_by_repeating_the_input_channels() {
+    let out = process_effect(&mut ChannelConverter::new(2), &[0.1, 0.2, 0.3], 48_000, 1);
+    assert_eq!(out, [0.1, 0.1, 0.2, 0.2, 0.3, 0.3]);
+
+    let out = process_effect(&mut ChannelConverter::new(4), &[0.1, -0.1, 0.2, -0.2], 48_000, 2);
+    assert_eq!(out, [0.1, -0.1, 0.1, -0.1, 0.2, -0.2, 0.2, -0.2]);
+}
+
+#[test]
+fn narrows_by_averaging() {
+    let out = process_effect(&mut ChannelConverter::new(1), &[0.5, 0.25, -0.5, 0.5], 48_000, 2);
+    assert_eq!(out, [0.375, 0.0]);
+
+    // Channel 0 averages inputs 0 and 2, channel 1 is input 1 alone.
+    let out = process_effect(&mut ChannelConverter::new(2), &[0.5, 0.25, 0.25], 48_000, 3);
+    assert_eq!(out, [0.375, 0.25]);
+}
+
+#[test]
+fn holds_the_effect_invariants() {
+    for (from, to) in [(1, 2), (2, 1), (3, 2)] {
+        effect_invariants_test(&mut ChannelConverter::new(to), 48_000, from, &[1, 64, 512]);
+    }
+}
@@ -0,0 +1,9 @@
+use bord_engine::dsp::channel_insert::ChannelInsert;
+use bord_engine::dsp::hpf::HighPassFilter;
+use bord_engine::test_utils::effect_invariants_test;
+
+#[test]
+fn holds_the_effect_invariants() {
+    let mut fx = ChannelInsert::new(0, Box::new(HighPassFilter::new(200.0, 2)));
+    effect_invariants_test(&mut fx, 48_000, 2, &[1, 64, 512]);
+}
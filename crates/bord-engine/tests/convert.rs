@@ -0,0 +1,48 @@
+use bord_engine::dsp::convert::*;
+
+#[test]
+fn i16_edges() {
+    assert_eq!(i16_to_f32(0), 0.0);
+    assert_eq!(i16_to_f32(i16::MIN), -1.0);
+    assert!((i16_to_f32(i16::MAX) - 1.0).abs() < 1e-4);
+    assert_eq!(f32_to_i16(0.0), 0);
+    assert_eq!(f32_to_i16(1.0), i16::MAX);
+    assert_eq!(f32_to_i16(-1.0), i16::MIN);
+    assert_eq!(f32_to_i16(2.0), i16::MAX);
+    assert_eq!(f32_to_i16(-2.0), i16::MIN);
+}
+
+#[test]
+fn u16_edges() {
+    assert_eq!(u16_to_f32(32768), 0.0);
+    assert_eq!(u16_to_f32(0), -1.0);
+    assert!((u16_to_f32(u16::MAX) - 1.0).abs() < 1e-4);
+    assert_eq!(f32_to_u16(0.0), 32768);
+    assert_eq!(f32_to_u16(1.0), u16::MAX);
+    assert_eq!(f32_to_u16(-1.0), 0);
+    assert_eq!(f32_to_u16(5.0), u16::MAX);
+}
+
+#[test]
+fn i32_edges() {
+    assert_eq!(i32_to_f32(0), 0.0);
+    assert_eq!(i32_to_f32(i32::MIN), -1.0);
+    assert!((i32_to_f32(i32::MAX) - 1.0).abs() < 1e-6);
+    assert_eq!(f32_to_i32(0.0), 0);
+    assert_eq!(f32_to_i32(1.0), i32::MAX);
+    assert_eq!(f32_to_i32(-1.0), i32::MIN);
+    assert_eq!(f32_to_i32(-3.0), i32::MIN);
+}
+
+#[test]
+fn slice_roundtrip_is_lossless() {
+    let src: [i16; 4] = [i16::MIN, -1, 0, i16::MAX];
+    let mut f = Vec::with_capacity(16);
+    convert_slice_to_f32(&src, &mut f);
+    assert_eq!(f.len(), 4);
+    assert_eq!(f.capacity(), 16);
+    let mut back = [0i16; 4];
+    convert_slice_from_f32(&f, &mut back);
+    assert_eq!(back, src);
+    assert_eq!(<u16 as SampleConvert>::SILENCE.to_f32(), 0.0);
+}
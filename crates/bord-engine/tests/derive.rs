@@ -0,0 +1,70 @@
+use bord_engine::dsp::effect::{Effect, EffectParams, ParamUnit};
+use bord_engine::effect_params_methods;
+
+#[derive(EffectParams)]
+struct Trim {
+    #[param(id = 1, name = "gain_db", min = -120.0, max = 40.0, default = 0.0, unit = Decibels)]
+    gain_db: f32,
+    #[param(id = 2, min = 0, max = 100, default = 100, unit = Percent)]
+    mix: u8,
+    scale: f32,
+}
+
+impl Effect for Trim {
+    fn name(&self) -> &str {
+        "Trim"
+    }
+    effect_params_methods!();
+    fn process(&mut self, block: &mut [f32]) {
+        self.scale = 10f32.powf(self.gain_db / 20.0) * self.mix as f32 / 100.0;
+        block.iter_mut().for_each(|s| *s *= self.scale);
+    }
+}
+
+fn trim() -> Trim {
+    Trim { gain_db: 0.0, mix: 100, scale: 1.0 }
+}
+
+#[test]
+fn set_and_get_by_id_clamp_to_the_range() {
+    let mut fx = trim();
+    assert!(fx.set_param(1, -6.0));
+    assert_eq!(fx.get_param(1), Some(-6.0));
+    fx.set_param(1, 100.0);
+    assert_eq!(fx.get_param(1), Some(40.0));
+    fx.set_param(2, 250.0);
+    assert_eq!(fx.mix, 100);
+    assert!(!fx.set_param(3, 1.0));
+    assert_eq!(fx.get_param(3), None);
+}
+
+#[test]
+fn describes_its_params_to_the_effect_trait() {
+    let mut fx = trim();
+    let keys: Vec<_> = fx.param_descriptors().iter().map(|d| (d.key, d.default_value)).collect();
+    assert_eq!(keys, [("gain_db", 0.0), ("mix", 100.0)]);
+    assert_eq!(Trim::PARAM_INFO[0].unit, ParamUnit::Decibels);
+    assert_eq!(Trim::PARAM_INFO[1].unit, ParamUnit::Percent);
+
+    fx.set_param_db("gain_db", -20.0);
+    fx.set_param_db("mix", 50.0);
+    let mut block = [1.0f32; 4];
+    fx.process(&mut block);
+    assert!((block[0] - 0.05).abs() < 1e-6);
+    fx.reset_param_to_default("gain_db");
+    assert_eq!(fx.gain_db, 0.0);
+}
+
+#[test]
+fn state_round_trips() {
+    let mut fx = trim();
+    fx.set_param(1, -3.5);
+    fx.set_param(2, 25.0);
+    let state = fx.save_state();
+    assert_eq!(state.len(), 2);
+
+    let mut restored = trim();
+    restored.load_state(&state);
+    assert_eq!(restored.get_param(1), Some(-3.5));
+    assert_eq!(restored.get_param(2), Some(25.0));
+}
@@ -0,0 +1,12 @@
+use bord_engine::dsp::{effect_by_name, EFFECT_NAMES};
+use bord_engine::test_utils::effect_invariants_test;
+
+#[test]
+fn every_built_in_effect_holds_the_invariants() {
+    for name in EFFECT_NAMES {
+        for channels in [1, 2] {
+            let mut fx = effect_by_name(name).unwrap();
+            effect_invariants_test(&mut *fx, 48_000, channels, &[1, 64, 256, 1000]);
+        }
+    }
+}
@@ -0,0 +1,33 @@
+use bord_engine::dsp::gain::Gain;
+use bord_engine::test_utils::{assert_gain_db, effect_invariants_test, process_effect, SilentDevice};
+
+fn sine(frames: usize) -> Vec<f32> {
+    (0..frames).map(|i| (i as f32 * 0.05).sin() * 0.5).collect()
+}
+
+#[test]
+fn applies_its_db() {
+    let input = sine(4800);
+    for db in [-12.0, -6.0, 0.0, 3.0] {
+        let out = process_effect(&mut Gain::new(db), &input, 48_000, 1);
+        assert_gain_db(db, &out, &input, 0.01);
+    }
+}
+
+#[test]
+fn runs_in_a_chain_without_a_device() {
+    let device = SilentDevice::new(48_000, 1, 256);
+    let mut chain = device.chain();
+    chain.push(Box::new(Gain::new(-6.0))).unwrap();
+    assert!(device.run(&mut chain, 4).iter().all(|&s| s == 0.0));
+
+    let input = sine(1000);
+    let out = device.run_input(&mut chain, &input);
+    assert_eq!(out.len(), 1024);
+    assert_gain_db(-6.0, &out[..1000], &input, 0.01);
+}
+
+#[test]
+fn holds_the_effect_invariants() {
+    effect_invariants_test(&mut Gain::new(-6.0), 48_000, 2, &[1, 64, 512]);
+}
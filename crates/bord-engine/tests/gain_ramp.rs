@@ -0,0 +1,33 @@
+use bord_engine::dsp::gain_ramp::{GainRamp, RampShape};
+
+#[test]
+fn lands_on_the_target_in_exactly_n_samples() {
+    let mut ramp = GainRamp::new(0.0);
+    ramp.ramp_to(1.0, 4);
+    let values: Vec<f32> = (0..6).map(|_| ramp.next()).collect();
+    assert_eq!(values, [0.25, 0.5, 0.75, 1.0, 1.0, 1.0]);
+    assert!(ramp.is_done());
+
+    // A long ramp doesn't drift off its line.
+    ramp.ramp_to(0.0, 480_000);
+    let last = (0..479_999).map(|_| ramp.next()).last().unwrap();
+    assert!((last - 1.0 / 480_000.0).abs() < 1e-6, "{last}");
+    assert_eq!(ramp.next(), 0.0);
+}
+
+#[test]
+fn apply_to_block_ramps_then_holds() {
+    let mut ramp = GainRamp::new(1.0);
+    ramp.shape = RampShape::Exponential;
+    ramp.ramp_to(0.25, 2);
+    let mut block = [1.0; 4];
+    ramp.apply_to_block(&mut block);
+    assert_eq!(block, [0.5, 0.25, 0.25, 0.25]);
+
+    // Restarting mid-ramp starts from where it got to.
+    ramp.shape = RampShape::Linear;
+    ramp.ramp_to(1.0, 4);
+    assert_eq!(ramp.next(), 0.4375);
+    ramp.ramp_to(0.0, 2);
+    assert_eq!(ramp.next(), 0.21875);
+}
@@ -0,0 +1,66 @@
+use bord_engine::dsp::effect::Effect;
+use bord_engine::dsp::gain_rider::GainRider;
+
+const SR: u32 = 48_000;
+
+/// Two seconds of stereo 1 kHz sine with an RMS of `rms_db`, run through a
+/// rider aiming at -20 dBFS; returns the rider and the RMS of the last 100 ms.
+fn ride(rms_db: f32) -> (GainRider, f32) {
+    let mut rider = GainRider::new(-20.0, 12.0, -12.0, 200.0);
+    rider.prepare(SR, 2);
+    let amp = 10f32.powf(rms_db / 20.0) * std::f32::consts::SQRT_2;
+    let mut out = Vec::new();
+    for block in 0..(2 * SR as usize / 480) {
+        let mut buf: Vec<f32> = (0..480)
+            .flat_map(|i| {
+                let t = (block * 480 + i) as f32 / SR as f32;
+                let s = amp * (std::f32::consts::TAU * 1000.0 * t).sin();
+                [s, s]
+            })
+            .collect();
+        rider.process(&mut buf);
+        out.extend(buf);
+    }
+    let tail = &out[out.len() - SR as usize / 10 * 2..];
+    let rms = (tail.iter().map(|s| s * s).sum::<f32>() / tail.len() as f32).sqrt();
+    (rider, 20.0 * rms.log10())
+}
+
+#[test]
+fn quiet_input_is_boosted_to_the_target() {
+    let (rider, out_db) = ride(-30.0);
+    assert!((rider.gain_db() - 10.0).abs() < 0.2, "gain {} dB", rider.gain_db());
+    assert!((out_db + 20.0).abs() < 0.5, "output {out_db} dBFS");
+}
+
+#[test]
+fn loud_input_is_cut_no_further_than_max_cut() {
+    let (rider, out_db) = ride(0.0);
+    assert!((rider.gain_db() + 12.0).abs() < 0.2, "gain {} dB", rider.gain_db());
+    assert!((out_db + 12.0).abs() < 0.5, "output {out_db} dBFS");
+}
+
+#[test]
+fn silence_is_not_boosted() {
+    let mut rider = GainRider::new(-20.0, 12.0, -12.0, 200.0);
+    rider.prepare(SR, 1);
+    let mut block = vec![0.0f32; SR as usize];
+    rider.process(&mut block);
+    assert_eq!(rider.gain_db(), 0.0);
+}
+
+#[test]
+fn gain_db_is_the_gain_applied_while_ramping() {
+    let mut rider = GainRider::new(-20.0, 12.0, -12.0, 200.0);
+    rider.prepare(SR, 1);
+    let level = 0.01;
+    let mut ramping = false;
+    for _ in 0..50 {
+        let mut block = vec![level; 960];
+        rider.process(&mut block);
+        let applied = 20.0 * (block[block.len() - 1] / level).log10();
+        assert!((rider.gain_db() - applied).abs() < 1e-3, "reported {} dB, applied {applied} dB", rider.gain_db());
+        ramping |= applied > 0.1 && applied < 11.9;
+    }
+    assert!(ramping, "the gain never passed through the ramp");
+}
@@ -0,0 +1,14 @@
+use bord_engine::dsp::comb::{CombFilter, CombMode};
+use bord_engine::log::set_log_handler;
+use std::sync::Mutex;
+
+static MESSAGES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+#[test]
+fn warnings_go_to_the_installed_handler() {
+    set_log_handler(|args| MESSAGES.lock().unwrap().push(args.to_string()));
+    CombFilter::new(100, 1.5, CombMode::Iir);
+    let messages = MESSAGES.lock().unwrap();
+    assert_eq!(messages.len(), 1);
+    assert!(messages[0].starts_with("CombFilter: IIR gain 1.5 is unstable"), "{}", messages[0]);
+}
@@ -0,0 +1,9 @@
+use bord_engine::dsp::lookahead::Lookahead;
+use bord_engine::test_utils::effect_invariants_test;
+
+#[test]
+fn holds_the_effect_invariants() {
+    for delay in [1, 480] {
+        effect_invariants_test(&mut Lookahead::new(delay), 48_000, 2, &[1, 64, 512]);
+    }
+}
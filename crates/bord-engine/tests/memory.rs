@@ -0,0 +1,21 @@
+use bord_engine::dsp::gain::Gain;
+use bord_engine::dsp::pingpong::PingpongDelay;
+use bord_engine::graph::PushError;
+use bord_engine::test_utils::SilentDevice;
+
+#[test]
+fn push_refuses_effects_past_the_limit() {
+    let mut chain = SilentDevice::new(48_000, 2, 256).chain();
+    chain.push(Box::new(PingpongDelay::new(375.0, 0.5, 0.35))).unwrap();
+    let used = chain.total_memory_bytes();
+    assert!(used >= 18_000 * 2 * 4, "a 375 ms stereo delay line holds {used} bytes");
+
+    // Room for a Gain slot (its dry buffer), not for a second delay.
+    chain.set_max_memory_bytes(Some(2 * used - 1));
+    chain.push(Box::new(Gain::new(0.0))).unwrap();
+    let used = chain.total_memory_bytes();
+    let err = chain.push(Box::new(PingpongDelay::new(375.0, 0.5, 0.35))).unwrap_err();
+    assert!(matches!(err, PushError::MemoryLimit { .. }), "{err}");
+    assert_eq!(chain.effect_memory_bytes().len(), 2);
+    assert_eq!(chain.total_memory_bytes(), used);
+}
@@ -0,0 +1,37 @@
+use bord_engine::dsp::mono_mix::MonoMix;
+use bord_engine::test_utils::{effect_invariants_test, process_effect};
+
+fn stereo(l: impl Fn(usize) -> f32, r: impl Fn(usize) -> f32) -> Vec<f32> {
+    (0..256).flat_map(|i| [l(i), r(i)]).collect()
+}
+
+#[test]
+fn opposite_polarity_cancels() {
+    let mut fx = MonoMix::new(true);
+    let sine = |i: usize| (i as f32 * 0.1).sin() * 0.8;
+    let out = process_effect(&mut fx, &stereo(sine, |i| -sine(i)), 48_000, 2);
+    assert!(out.iter().all(|&s| s.abs() < 1e-6));
+}
+
+#[test]
+fn writes_the_average_to_every_channel() {
+    let mut fx = MonoMix::new(true);
+    let sine = |i: usize| (i as f32 * 0.1).sin() * 0.5;
+    let same = stereo(sine, sine);
+    assert_eq!(process_effect(&mut fx, &same, 48_000, 2), same);
+
+    let out = process_effect(&mut fx, &stereo(|_| 0.75, |_| 0.25), 48_000, 2);
+    assert!(out.iter().all(|&s| (s - 0.5).abs() < 1e-6));
+
+    // Disabled it passes through.
+    fx.set_enabled(false);
+    let block = stereo(|_| 0.75, |_| 0.25);
+    assert_eq!(process_effect(&mut fx, &block, 48_000, 2), block);
+}
+
+#[test]
+fn holds_the_effect_invariants() {
+    for enabled in [true, false] {
+        effect_invariants_test(&mut MonoMix::new(enabled), 48_000, 2, &[1, 64, 512]);
+    }
+}
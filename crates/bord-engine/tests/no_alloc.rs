@@ -0,0 +1,131 @@
+use bord_engine::dsp::convert::pop_converted;
+use bord_engine::ring::SpscRingF32;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+/// Counts allocations made on threads that opted in via `COUNTING`.
+struct CountingAlloc;
+
+thread_local! {
+    static COUNTING: Cell<bool> = const { Cell::new(false) };
+    static ALLOCS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if COUNTING.with(Cell::get) {
+            ALLOCS.with(|n| n.set(n.get() + 1));
+        }
+        System.alloc(layout)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+/// The integer output callback path, run 1000 times with the ring alternately
+/// full and short, must not touch the allocator once its scratch is sized.
+#[test]
+fn output_callback_does_not_allocate() {
+    const BLOCK: usize = 512;
+    let ring = SpscRingF32::with_capacity(BLOCK * 8);
+    let mut scratch = Vec::with_capacity(BLOCK * 8);
+    let (mut out_i16, mut out_u16) = ([0i16; BLOCK], [0u16; BLOCK]);
+    let input = [0.25f32; BLOCK];
+
+    COUNTING.with(|c| c.set(true));
+    let mut underruns = 0;
+    for i in 0..1000 {
+        if i % 3 != 0 {
+            ring.push_slice(&input);
+        }
+        let ok = if i % 2 == 0 {
+            pop_converted(&ring, &mut scratch, &mut out_i16)
+        } else {
+            pop_converted(&ring, &mut scratch, &mut out_u16)
+        };
+        underruns += usize::from(!ok);
+    }
+    COUNTING.with(|c| c.set(false));
+
+    assert_eq!(ALLOCS.with(Cell::get), 0);
+    assert!(underruns > 0, "the silence path should have been exercised too");
+}
+
+/// Filter parameter changes go through `prepare_hot` on the audio thread and
+/// must not reallocate the filters' state.
+#[test]
+fn filter_param_changes_do_not_allocate() {
+    use bord_engine::dsp::{lpf::LowPassFilter, notch::Notch};
+    use bord_engine::graph::Chain;
+    use bord_engine::params::{ParamEvent, ParamId};
+
+    let mut chain = Chain::new(48_000, 2);
+    chain.push(Box::new(LowPassFilter::new(2_000.0, 4))).unwrap();
+    chain.push(Box::new(Notch::new(1_000.0, 2.0))).unwrap();
+    let (cutoff, center) = (ParamId::new("cutoff_hz").unwrap(), ParamId::new("center").unwrap());
+    let mut block = [0.1f32; 512];
+
+    COUNTING.with(|c| c.set(true));
+    for i in 0..1000 {
+        let f = 500.0 + i as f32;
+        chain.apply(ParamEvent::SetParamDb { index: 0, key: cutoff, db: f });
+        chain.apply(ParamEvent::SetParamDb { index: 1, key: center, db: f });
+        chain.process(&mut block);
+    }
+    COUNTING.with(|c| c.set(false));
+
+    assert_eq!(ALLOCS.with(Cell::get), 0);
+}
+
+/// A partial wet/dry mix must not grow its dry copy on the audio thread:
+/// a block past the default size is blended in pieces, and one within the
+/// chain's block size in one go.
+#[test]
+fn partial_mix_does_not_allocate() {
+    use bord_engine::dsp::effect::Effect;
+    use bord_engine::dsp::gain::Gain;
+    use bord_engine::dsp::mix::Mix;
+    use bord_engine::graph::Chain;
+
+    let want = 0.5 * (0.5 + 0.5 * 10f32.powf(-6.0 / 20.0));
+    let mut mix = Mix::new(Gain::new(-6.0), 0.5);
+    mix.prepare(48_000, 2);
+    let mut chain = Chain::new(48_000, 2);
+    chain.set_block_size(8192);
+    chain.push_with_mix(Box::new(Gain::new(-6.0)), 0.5).unwrap();
+    let (mut a, mut b) = (vec![0.5f32; 8192 * 2], vec![0.5f32; 8192 * 2]);
+
+    COUNTING.with(|c| c.set(true));
+    mix.process(&mut a);
+    chain.process(&mut b);
+    COUNTING.with(|c| c.set(false));
+
+    assert_eq!(ALLOCS.with(Cell::get), 0);
+    assert!(a.iter().chain(&b).all(|&s| (s - want).abs() < 1e-5));
+}
+
+/// Ringing out a popped slot's tail must not grow the chain's tail scratch,
+/// for blocks past its default size too.
+#[test]
+fn ringing_out_a_tail_does_not_allocate() {
+    use bord_engine::dsp::comb::{CombFilter, CombMode};
+    use bord_engine::graph::Chain;
+    use bord_engine::params::ParamEvent;
+
+    let mut chain = Chain::new(48_000, 2);
+    chain.push(Box::new(CombFilter::new(48_000, 0.5, CombMode::Fir))).unwrap();
+    let (reply, popped) = std::sync::mpsc::sync_channel(1);
+    chain.apply(ParamEvent::PopEffect { index: 0, reply });
+    let mut block = vec![0.1f32; 8192 * 2];
+
+    COUNTING.with(|c| c.set(true));
+    chain.process(&mut block);
+    COUNTING.with(|c| c.set(false));
+
+    assert_eq!(ALLOCS.with(Cell::get), 0);
+    assert!(popped.try_recv().is_err(), "the tail is still ringing out");
+}
@@ -0,0 +1,8 @@
+use bord_engine::dsp::parametric_eq::{EqBand, ParametricEq};
+use bord_engine::test_utils::effect_invariants_test;
+
+#[test]
+fn holds_the_effect_invariants() {
+    let bands = vec![EqBand { freq_hz: 120.0, gain_db: 4.0, q: 0.7 }, EqBand { freq_hz: 3_000.0, gain_db: -6.0, q: 2.0 }];
+    effect_invariants_test(&mut ParametricEq::new(bands), 48_000, 2, &[1, 64, 512]);
+}
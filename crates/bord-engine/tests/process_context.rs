@@ -0,0 +1,94 @@
+use bord_engine::dsp::effect::{Effect, Frame, ProcessContext};
+use bord_engine::dsp::gain_automation::{AutomationPoint, GainAutomation};
+use bord_engine::graph::Chain;
+use bord_engine::test_utils::effect_invariants_test;
+
+type Seen = std::sync::Arc<std::sync::Mutex<Vec<ProcessContext>>>;
+
+/// Records the context of every block it sees; with `split` it takes the
+/// chain's planar stereo path.
+struct Recorder {
+    seen: Seen,
+    split: bool,
+}
+
+impl Effect for Recorder {
+    fn name(&self) -> &str {
+        "Recorder"
+    }
+    fn process(&mut self, _block: &mut [f32]) {}
+    fn process_with_context(&mut self, _block: &mut [f32], ctx: &ProcessContext) {
+        self.seen.lock().unwrap().push(*ctx);
+    }
+    fn process_stereo(&mut self, _left: &mut [f32], _right: &mut [f32]) {}
+    fn process_stereo_with_context(&mut self, _left: &mut [f32], _right: &mut [f32], ctx: &ProcessContext) {
+        self.seen.lock().unwrap().push(*ctx);
+    }
+    fn process_frames_with_context(&mut self, _frames: &mut [Frame<2>], ctx: &ProcessContext) {
+        self.seen.lock().unwrap().push(*ctx);
+    }
+    fn supports_stereo_split(&self) -> bool {
+        self.split
+    }
+}
+
+#[test]
+fn chain_counts_frames_into_the_context() {
+    for split in [false, true] {
+        let seen = Seen::default();
+        let mut chain = Chain::new(48_000, 2);
+        chain.push(Box::new(Recorder { seen: seen.clone(), split })).unwrap();
+
+        chain.process(&mut [0.0; 128]);
+        chain.process_with_context(&mut [0.0; 32], &ProcessContext { sample_pos: 999, sample_rate: 48_000, is_realtime: true });
+        chain.set_sample_pos(10_000);
+        chain.process(&mut [0.0; 2]);
+        chain.process_frames_stereo(&mut [Frame::default(); 3]);
+
+        let seen = seen.lock().unwrap();
+        let positions: Vec<_> = seen.iter().map(|c| (c.sample_pos, c.is_realtime)).collect();
+        assert_eq!(positions, [(0, false), (64, true), (10_000, false), (10_001, false)], "split {split}");
+        assert_eq!(chain.sample_pos(), 10_004);
+    }
+}
+
+#[test]
+fn automation_follows_the_chain_position() {
+    let points = vec![AutomationPoint { frame: 0, gain_db: 0.0 }, AutomationPoint { frame: 100, gain_db: f32::NEG_INFINITY }];
+    let mut chain = Chain::new(48_000, 1);
+    chain.push(Box::new(GainAutomation::new(points))).unwrap();
+
+    let mut block = [1.0f32; 4];
+    chain.process(&mut block);
+    assert_eq!(block, [1.0, 0.99, 0.98, 0.97]);
+
+    // A seek jumps straight to the envelope there.
+    chain.set_sample_pos(50);
+    let mut block = [1.0f32; 2];
+    chain.process(&mut block);
+    assert_eq!(block, [0.5, 0.49]);
+
+    chain.set_sample_pos(200);
+    let mut block = [1.0f32; 2];
+    chain.process(&mut block);
+    assert_eq!(block, [0.0, 0.0]);
+}
+
+#[test]
+fn automation_follows_the_position_on_the_frames_path() {
+    let points = vec![AutomationPoint { frame: 0, gain_db: 0.0 }, AutomationPoint { frame: 100, gain_db: f32::NEG_INFINITY }];
+    let mut chain = Chain::new(48_000, 2);
+    chain.push(Box::new(GainAutomation::new(points))).unwrap();
+
+    chain.set_sample_pos(50);
+    let mut frames = [Frame([1.0f32; 2]); 2];
+    chain.process_frames_stereo(&mut frames);
+    assert_eq!(frames, [Frame([0.5; 2]), Frame([0.49; 2])]);
+    assert_eq!(chain.sample_pos(), 52);
+}
+
+#[test]
+fn automation_holds_the_effect_invariants() {
+    let points = vec![AutomationPoint { frame: 0, gain_db: 0.0 }, AutomationPoint { frame: 1_000, gain_db: -12.0 }];
+    effect_invariants_test(&mut GainAutomation::new(points), 48_000, 2, &[1, 64, 512]);
+}
@@ -0,0 +1,71 @@
+use bord_engine::dsp::gain::Gain;
+use bord_engine::dsp::lpf::LowPassFilter;
+use bord_engine::graph::{prepared_slot, Chain};
+use bord_engine::params::ParamQueue;
+
+const BLOCK: usize = 256;
+
+/// Exactly two cycles per block, so every block has the same RMS.
+fn tone(blocks: usize) -> Vec<f32> {
+    (0..blocks * BLOCK).map(|i| (std::f32::consts::TAU * 375.0 * i as f32 / 48_000.0).sin() * 0.5).collect()
+}
+
+fn rms(block: &[f32]) -> f32 {
+    (block.iter().map(|s| s * s).sum::<f32>() / block.len() as f32).sqrt()
+}
+
+#[test]
+fn replace_all_switches_between_two_blocks() {
+    let mut chain = Chain::new(48_000, 1);
+    let queue = ParamQueue::new();
+    chain.set_param_queue(queue.clone());
+    for db in [-3.0, -3.0] {
+        chain.push(Box::new(Gain::new(db))).unwrap();
+    }
+    let mut out = tone(40);
+    for (i, block) in out.chunks_mut(BLOCK).enumerate() {
+        if i == 20 {
+            // Same overall gain from one effect instead of two.
+            queue.replace_all(vec![prepared_slot(Box::new(Gain::new(-6.0)), 1.0, 48_000, 1)]);
+        }
+        chain.process(block);
+    }
+    assert_eq!(chain.iter_effects().count(), 1);
+
+    let reference = rms(&tone(40)) * 10f32.powf(-6.0 / 20.0);
+    for block in out.chunks(BLOCK) {
+        assert!((rms(block) - reference).abs() < reference * 0.01, "block RMS {} vs {reference}", rms(block));
+    }
+    // No step anywhere in the waveform either.
+    let max_step = out.windows(2).map(|w| (w[1] - w[0]).abs()).fold(0.0, f32::max);
+    assert!(max_step < 0.03, "max step {max_step}");
+}
+
+#[test]
+fn remove_all_leaves_a_passthrough_chain() {
+    let mut chain = Chain::new(48_000, 1);
+    let queue = ParamQueue::new();
+    chain.set_param_queue(queue.clone());
+    chain.push(Box::new(Gain::new(-12.0))).unwrap();
+    chain.push(Box::new(LowPassFilter::new(1000.0, 2))).unwrap();
+    queue.remove_all();
+    let input = tone(2);
+    let mut out = input.clone();
+    chain.process(&mut out);
+    assert_eq!(chain.iter_effects().count(), 0);
+    assert_eq!(out, input);
+}
+
+#[test]
+fn chain_replace_all_returns_the_old_effects() {
+    let mut chain = Chain::new(48_000, 2);
+    chain.push(Box::new(Gain::new(-6.0))).unwrap();
+    chain.push(Box::new(LowPassFilter::new(1000.0, 2))).unwrap();
+    let old = chain.replace_all(vec![Box::new(Gain::new(0.0))]).unwrap();
+    let names: Vec<_> = old.iter().map(|fx| fx.name()).collect();
+    assert_eq!(names, ["Gain", "LowPassFilter"]);
+    assert_eq!(chain.iter_effects().map(|fx| fx.name()).collect::<Vec<_>>(), ["Gain"]);
+
+    assert_eq!(chain.remove_all().len(), 1);
+    assert_eq!(chain.iter_effects().count(), 0);
+}
@@ -0,0 +1,129 @@
+use bord_engine::ring::{SpscRing, SpscRingF32};
+use proptest::prelude::*;
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+#[derive(Debug, Clone)]
+enum Op {
+    Push(usize),
+    PushPartial(usize),
+    Pop(usize),
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        (0usize..80).prop_map(Op::Push),
+        (0usize..80).prop_map(Op::PushPartial),
+        (0usize..80).prop_map(Op::Pop),
+    ]
+}
+
+proptest! {
+    /// Single-threaded model check against a VecDeque: every push/pop succeeds
+    /// exactly when the model says it fits, and popped data is the model's front.
+    #[test]
+    fn matches_fifo_model(cap in 2usize..300, ops in prop::collection::vec(op(), 1..200)) {
+        let ring = SpscRingF32::with_capacity(cap);
+        let usable = cap.next_power_of_two() - 1;
+        let mut model = VecDeque::new();
+        let mut next = 0.0f32;
+        let mut popped = Vec::new();
+
+        for op in ops {
+            match op {
+                Op::Push(n) => {
+                    let data: Vec<f32> = (0..n).map(|i| next + i as f32).collect();
+                    let fits = model.len() + n <= usable;
+                    prop_assert_eq!(ring.push_slice(&data), fits);
+                    if fits {
+                        model.extend(data.iter().copied());
+                        next += n as f32;
+                    }
+                }
+                Op::PushPartial(n) => {
+                    let data: Vec<f32> = (0..n).map(|i| next + i as f32).collect();
+                    let fits = n.min(usable - model.len());
+                    prop_assert_eq!(ring.push_slice_partial(&data), fits);
+                    model.extend(data[..fits].iter().copied());
+                    next += fits as f32;
+                }
+                Op::Pop(n) => {
+                    let mut out = vec![f32::NAN; n];
+                    let enough = model.len() >= n;
+                    prop_assert_eq!(ring.pop_into(&mut out), enough);
+                    if enough {
+                        let expect: Vec<f32> = model.drain(..n).collect();
+                        prop_assert_eq!(&out, &expect);
+                        popped.extend(out);
+                    }
+                }
+            }
+        }
+
+        // Output is an in-order prefix of the input: no loss, no duplicates.
+        for (i, v) in popped.iter().enumerate() {
+            prop_assert_eq!(*v, i as f32);
+        }
+    }
+}
+
+/// Deterministic xorshift so failures reproduce.
+fn xorshift(state: &mut u32) -> u32 {
+    *state ^= *state << 13;
+    *state ^= *state >> 17;
+    *state ^= *state << 5;
+    *state
+}
+
+#[test]
+fn concurrent_producer_consumer_preserves_order() {
+    const TOTAL: usize = 200_000;
+    let ring = Arc::new(SpscRingF32::with_capacity(1024));
+
+    let producer = {
+        let ring = ring.clone();
+        std::thread::spawn(move || {
+            let mut rng = 0x1234_5678u32;
+            let mut sent = 0usize;
+            let mut chunk = Vec::with_capacity(64);
+            while sent < TOTAL {
+                let n = (1 + xorshift(&mut rng) as usize % 64).min(TOTAL - sent);
+                chunk.clear();
+                chunk.extend((sent..sent + n).map(|v| v as f32));
+                while !ring.push_slice(&chunk) {
+                    std::thread::yield_now();
+                }
+                sent += n;
+                if xorshift(&mut rng).is_multiple_of(8) {
+                    std::thread::yield_now();
+                }
+            }
+        })
+    };
+
+    let mut rng = 0x9abc_def0u32;
+    let mut expected = 0usize;
+    let mut out = [0.0f32; 64];
+    while expected < TOTAL {
+        let n = (1 + xorshift(&mut rng) as usize % 64).min(TOTAL - expected);
+        if ring.pop_into(&mut out[..n]) {
+            for &v in &out[..n] {
+                assert_eq!(v, expected as f32, "sample lost, duplicated or reordered");
+                expected += 1;
+            }
+        } else {
+            std::thread::yield_now();
+        }
+    }
+    producer.join().unwrap();
+    assert!(!ring.pop_into(&mut out[..1]), "ring should be drained");
+}
+
+#[test]
+fn generic_ring_carries_other_copy_types() {
+    let ring = SpscRing::<(u32, bool)>::with_capacity(4);
+    assert!(ring.push_slice(&[(1, true), (2, false)]));
+    let mut out = [(0, false); 2];
+    assert!(ring.pop_into(&mut out));
+    assert_eq!(out, [(1, true), (2, false)]);
+}
@@ -0,0 +1,81 @@
+use bord_engine::dsp::effect::Effect;
+use bord_engine::dsp::silence_gap::SilenceGap;
+use bord_engine::graph::Chain;
+use bord_engine::test_utils::effect_invariants_test;
+use std::sync::{Arc, Mutex};
+
+const SR: u32 = 48_000;
+
+/// Run 10 ms blocks of `(seconds, loud)` sections through a 2 s gap finder;
+/// returns the gap and resume times it reported.
+fn run(sections: &[(f32, bool)]) -> Vec<(&'static str, f64)> {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let (gaps, resumes) = (events.clone(), events.clone());
+    let mut fx = SilenceGap::new(-50.0, 2000.0, Arc::new(move |t| gaps.lock().unwrap().push(("gap", t))))
+        .with_on_resume(Arc::new(move |t| resumes.lock().unwrap().push(("resume", t))));
+    fx.prepare(SR, 2);
+    for &(seconds, loud) in sections {
+        for _ in 0..(seconds * 100.0) as usize {
+            let mut block = vec![if loud { 0.5 } else { 1e-4 }; 480 * 2];
+            let input = block.clone();
+            fx.process(&mut block);
+            assert_eq!(block, input);
+        }
+    }
+    let events = events.lock().unwrap().clone();
+    events
+}
+
+#[test]
+fn reports_a_long_pause_and_its_end() {
+    let events = run(&[(1.0, true), (3.0, false), (0.5, true)]);
+    assert_eq!(events, [("gap", 1.0), ("resume", 4.0)]);
+}
+
+#[test]
+fn ignores_pauses_shorter_than_the_minimum() {
+    let events = run(&[(1.0, true), (1.5, false), (1.0, true), (1.9, false), (0.1, true)]);
+    assert!(events.is_empty(), "{events:?}");
+}
+
+#[test]
+fn a_seek_ends_a_reported_gap_without_resuming() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let (gaps, resumes) = (events.clone(), events.clone());
+    let mut chain = Chain::new(SR, 1);
+    let fx = SilenceGap::new(-50.0, 1000.0, Arc::new(move |t| gaps.lock().unwrap().push(("gap", t))))
+        .with_on_resume(Arc::new(move |t| resumes.lock().unwrap().push(("resume", t))));
+    chain.push(Box::new(fx)).unwrap();
+
+    let mut silence = vec![0.0f32; SR as usize / 100];
+    for _ in 0..150 {
+        chain.process(&mut silence);
+    }
+    // Silent again at the new position: a fresh gap, reported from there.
+    chain.set_sample_pos(10 * SR as u64);
+    for _ in 0..150 {
+        chain.process(&mut silence);
+    }
+    chain.process(&mut vec![0.5f32; SR as usize / 100]);
+    assert_eq!(*events.lock().unwrap(), [("gap", 0.0), ("gap", 10.0), ("resume", 11.5)]);
+}
+
+#[test]
+fn threshold_field_applies_without_a_prepare() {
+    let fired = Arc::new(Mutex::new(0));
+    let count = fired.clone();
+    let mut fx = SilenceGap::new(-50.0, 100.0, Arc::new(move |_| *count.lock().unwrap() += 1));
+    fx.prepare(SR, 1);
+    // -80 dBFS: silent at -50, signal at -120.
+    fx.threshold_db = -120.0;
+    for _ in 0..50 {
+        fx.process(&mut [1e-4; 480]);
+    }
+    assert_eq!(*fired.lock().unwrap(), 0);
+}
+
+#[test]
+fn holds_the_effect_invariants() {
+    let mut fx = SilenceGap::new(-50.0, 100.0, Arc::new(|_| {})).with_on_resume(Arc::new(|_| {}));
+    effect_invariants_test(&mut fx, 48_000, 2, &[1, 64, 512]);
+}
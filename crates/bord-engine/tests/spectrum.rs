@@ -0,0 +1,35 @@
+#![cfg(feature = "spectrum")]
+
+use bord_engine::dsp::effect::Effect;
+use bord_engine::dsp::spectrum::{log_bands, SpectrumAnalyzer};
+use bord_engine::test_utils::effect_invariants_test;
+
+#[test]
+fn full_scale_sine_peaks_near_0_dbfs_in_its_bin() {
+    let (mut tap, mut reader) = SpectrumAnalyzer::new(1024);
+    tap.prepare(48_000, 1);
+    // Bin 64 of 1024 at 48 kHz: 3 kHz.
+    let mut block: Vec<f32> = (0..1024).map(|i| (std::f32::consts::TAU * 64.0 * i as f32 / 1024.0).sin()).collect();
+    tap.process(&mut block);
+
+    let spectrum = reader.spectrum();
+    assert_eq!(spectrum.len(), 513);
+    let peak = (0..spectrum.len()).max_by(|&a, &b| spectrum[a].total_cmp(&spectrum[b])).unwrap();
+    assert_eq!(peak, 64);
+    assert!(spectrum[64].abs() < 0.1, "peak {} dBFS", spectrum[64]);
+    assert!(spectrum[200] < -100.0);
+}
+
+#[test]
+fn log_bands_take_the_loudest_bin_in_each_band() {
+    // 9 bins of 3 kHz at 48 kHz; one band per octave from 3 to 24 kHz.
+    let spectrum = [-120.0, -60.0, -6.0, -50.0, -40.0, -30.0, -20.0, -10.0, -3.0];
+    let bands = log_bands(&spectrum, 48_000, 3, 3_000.0, 24_000.0);
+    assert_eq!(bands, [-60.0, -6.0, -10.0]);
+}
+
+#[test]
+fn holds_the_effect_invariants() {
+    let (mut tap, _reader) = SpectrumAnalyzer::new(256);
+    effect_invariants_test(&mut tap, 48_000, 2, &[1, 64, 512]);
+}
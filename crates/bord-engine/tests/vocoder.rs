@@ -0,0 +1,12 @@
+use bord_engine::dsp::vocoder::Vocoder;
+use bord_engine::ring::SpscRingF32;
+use bord_engine::test_utils::effect_invariants_test;
+use std::sync::Arc;
+
+#[test]
+fn holds_the_effect_invariants() {
+    // An empty carrier ring: the same (silent) carrier whatever the block size.
+    let carrier = Arc::new(SpscRingF32::with_capacity(4096));
+    let mut fx = Vocoder::new(carrier, 16).unwrap();
+    effect_invariants_test(&mut fx, 48_000, 2, &[1, 64, 512]);
+}
@@ -0,0 +1,139 @@
+//! `#[derive(EffectParams)]` for bord effects, re-exported by bord-engine's
+//! `derive` feature. Each field marked `#[param(...)]` becomes a parameter:
+//!
+//! ```ignore
+//! #[derive(EffectParams)]
+//! struct Tremolo {
+//!     #[param(id = 1, name = "depth", min = 0.0, max = 1.0, default = 0.5)]
+//!     depth: f32,
+//!     #[param(id = 2, name = "rate_hz", min = 0.1, max = 20.0, default = 4.0, unit = Hertz)]
+//!     rate_hz: f32,
+//!     phase: f32,
+//! }
+//! ```
+//!
+//! `id` is required and must be unique; `name` (the `set_param_db` key)
+//! defaults to the field name, `min`/`max` to unbounded, `default` to 0 and
+//! `unit` to `Linear`. Fields must be numeric: values are cast to and from f32.
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Error, Expr, Fields, Ident, LitInt, LitStr, Result};
+
+struct Param {
+    field: Ident,
+    id: u32,
+    key: String,
+    min: Option<Expr>,
+    max: Option<Expr>,
+    default: Option<Expr>,
+    unit: Ident,
+}
+
+#[proc_macro_derive(EffectParams, attributes(param))]
+pub fn derive_effect_params(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input).unwrap_or_else(Error::into_compile_error).into()
+}
+
+fn expand(input: &DeriveInput) -> Result<proc_macro2::TokenStream> {
+    let params = parse_params(input)?;
+    let krate = quote!(::bord_engine::dsp::effect);
+    let descriptors = params.iter().map(|p| {
+        let (key, default) = (&p.key, default_expr(p));
+        quote!(#krate::ParamDescriptor::new(#key, #default))
+    });
+    let infos = params.iter().map(|p| {
+        let (id, key, unit, default) = (p.id, &p.key, &p.unit, default_expr(p));
+        let min = p.min.as_ref().map_or_else(|| quote!(f32::NEG_INFINITY), |e| quote!((#e) as f32));
+        let max = p.max.as_ref().map_or_else(|| quote!(f32::INFINITY), |e| quote!((#e) as f32));
+        quote!(#krate::ParamInfo { id: #id, key: #key, min: #min, max: #max, default_value: #default, unit: #krate::ParamUnit::#unit })
+    });
+    let setters = params.iter().enumerate().map(|(i, p)| {
+        let (id, field) = (p.id, &p.field);
+        quote! {
+            #id => {
+                let info = &<Self as #krate::EffectParams>::PARAM_INFO[#i];
+                self.#field = value.max(info.min).min(info.max) as _;
+                true
+            }
+        }
+    });
+    let getters = params.iter().map(|p| {
+        let (id, field) = (p.id, &p.field);
+        quote!(#id => ::core::option::Option::Some(self.#field as f32))
+    });
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #krate::EffectParams for #name #ty_generics #where_clause {
+            const PARAMS: &'static [#krate::ParamDescriptor] = &[#(#descriptors),*];
+            const PARAM_INFO: &'static [#krate::ParamInfo] = &[#(#infos),*];
+
+            #[allow(clippy::unnecessary_cast)]
+            fn set_param(&mut self, id: u32, value: f32) -> bool {
+                match id {
+                    #(#setters)*
+                    _ => false,
+                }
+            }
+
+            #[allow(clippy::unnecessary_cast)]
+            fn get_param(&self, id: u32) -> ::core::option::Option<f32> {
+                match id {
+                    #(#getters,)*
+                    _ => ::core::option::Option::None,
+                }
+            }
+        }
+    })
+}
+
+fn default_expr(p: &Param) -> proc_macro2::TokenStream {
+    p.default.as_ref().map_or_else(|| quote!(0.0), |e| quote!((#e) as f32))
+}
+
+fn parse_params(input: &DeriveInput) -> Result<Vec<Param>> {
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new(Span::call_site(), "EffectParams can only be derived for structs"));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(Error::new(Span::call_site(), "EffectParams needs a struct with named fields"));
+    };
+    let mut params: Vec<Param> = Vec::new();
+    for field in &fields.named {
+        let Some(attr) = field.attrs.iter().find(|a| a.path().is_ident("param")) else { continue };
+        let ident = field.ident.clone().expect("named field");
+        let mut p = Param {
+            key: ident.to_string(),
+            field: ident,
+            id: 0,
+            min: None,
+            max: None,
+            default: None,
+            unit: Ident::new("Linear", Span::call_site()),
+        };
+        let mut id = None;
+        attr.parse_nested_meta(|meta| {
+            let key = meta.path.get_ident().map(|i| i.to_string()).unwrap_or_default();
+            match key.as_str() {
+                "id" => id = Some(meta.value()?.parse::<LitInt>()?.base10_parse()?),
+                "name" => p.key = meta.value()?.parse::<LitStr>()?.value(),
+                "min" => p.min = Some(meta.value()?.parse()?),
+                "max" => p.max = Some(meta.value()?.parse()?),
+                "default" => p.default = Some(meta.value()?.parse()?),
+                "unit" => p.unit = meta.value()?.parse()?,
+                _ => return Err(meta.error("expected id, name, min, max, default or unit")),
+            }
+            Ok(())
+        })?;
+        p.id = id.ok_or_else(|| Error::new_spanned(attr, "#[param] needs an `id`"))?;
+        if let Some(other) = params.iter().find(|o| o.id == p.id || o.key == p.key) {
+            let what = if other.id == p.id { format!("id {}", p.id) } else { format!("name {:?}", p.key) };
+            return Err(Error::new_spanned(attr, format!("{what} is already used by `{}`", other.field)));
+        }
+        params.push(p);
+    }
+    Ok(params)
+}
@@ -0,0 +1,45 @@
+//! Everything a bord plugin needs: the `Effect` trait, the ABI version the
+//! host checks, and `export_effect!` to emit the entry points.
+//!
+//! ```ignore
+//! // Cargo.toml: [lib] crate-type = ["cdylib"]
+//! struct Invert;
+//! impl bord_plugin_sdk::Effect for Invert {
+//!     fn name(&self) -> &str { "Invert" }
+//!     fn process(&mut self, block: &mut [f32]) { for s in block { *s = -*s; } }
+//! }
+//! bord_plugin_sdk::export_effect!(Invert);
+//! ```
+//!
+//! Build plugins with the same compiler and bord version as the host:
+//! trait objects have no stable layout.
+
+pub use bord_engine::dsp::effect::Effect;
+pub use bord_engine::BORD_PLUGIN_ABI_VERSION;
+
+/// Emit `bord_plugin_abi_version`, `bord_create_effect` and
+/// `bord_destroy_effect` for an expression that builds the effect.
+#[macro_export]
+macro_rules! export_effect {
+    ($ctor:expr) => {
+        #[no_mangle]
+        pub extern "C" fn bord_plugin_abi_version() -> u32 {
+            $crate::BORD_PLUGIN_ABI_VERSION
+        }
+
+        #[no_mangle]
+        pub extern "C" fn bord_create_effect() -> *mut Box<dyn $crate::Effect> {
+            let fx: Box<dyn $crate::Effect> = Box::new($ctor);
+            Box::into_raw(Box::new(fx))
+        }
+
+        /// # Safety
+        /// `fx` must come from `bord_create_effect` and not be used afterwards.
+        #[no_mangle]
+        pub unsafe extern "C" fn bord_destroy_effect(fx: *mut Box<dyn $crate::Effect>) {
+            if !fx.is_null() {
+                drop(Box::from_raw(fx));
+            }
+        }
+    };
+}
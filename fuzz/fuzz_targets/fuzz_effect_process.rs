@@ -0,0 +1,33 @@
+//! Run every `effect_by_name` effect over arbitrary samples, NaN/Inf included.
+//! `cargo +nightly fuzz run fuzz_effect_process`
+#![no_main]
+
+use bord_engine::dsp::{effect_by_name, EFFECT_NAMES};
+use libfuzzer_sys::fuzz_target;
+
+/// Inputs inside this range must come out finite; anything wilder only has to not panic.
+const SANE: f32 = 1.0e3;
+
+fuzz_target!(|data: &[u8]| {
+    let [which, channels, rate, rest @ ..] = data else { return };
+    let name = EFFECT_NAMES[*which as usize % EFFECT_NAMES.len()];
+    let channels = u16::from(*channels % 8) + 1;
+    let sample_rate = [8_000, 22_050, 44_100, 48_000, 96_000, 192_000][*rate as usize % 6];
+    let input: Vec<f32> = rest.chunks_exact(4).map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])).collect();
+
+    let mut fx = effect_by_name(name).expect("EFFECT_NAMES entries construct");
+    fx.prepare(sample_rate, channels);
+    let out_channels = fx.output_channel_count(channels);
+
+    // Reshaping effects get a block sized for their output, input interleaved up front.
+    let frames = input.len() / channels as usize;
+    let mut block = input.clone();
+    block.resize(block.len().max(frames * out_channels as usize), 0.0);
+    let len = block.len();
+    fx.process(&mut block);
+    assert_eq!(block.len(), len, "{name} changed the block length");
+
+    if input.iter().all(|x| x.is_finite() && x.abs() <= SANE) {
+        assert!(block.iter().all(|x| x.is_finite()), "{name} produced NaN/Inf from finite input");
+    }
+});
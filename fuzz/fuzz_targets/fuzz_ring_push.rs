@@ -0,0 +1,38 @@
+//! Push/pop arbitrary lengths through `SpscRingF32` against a `VecDeque` model.
+//! `cargo +nightly fuzz run fuzz_ring_push`
+#![no_main]
+
+use bord_engine::ring::SpscRingF32;
+use libfuzzer_sys::fuzz_target;
+use std::collections::VecDeque;
+
+fuzz_target!(|data: &[u8]| {
+    let [cap, ops @ ..] = data else { return };
+    let cap = *cap as usize + 1;
+    let ring = SpscRingF32::with_capacity(cap);
+    let usable = cap.next_power_of_two() - 1;
+    let mut model = VecDeque::new();
+    let mut next = 0.0f32;
+
+    for op in ops {
+        // High bit picks push/pop; the rest is a length that may overshoot capacity.
+        let n = (*op & 0x7f) as usize * 2;
+        if op & 0x80 == 0 {
+            let chunk: Vec<f32> = (0..n).map(|i| next + i as f32).collect();
+            let fits = model.len() + n <= usable;
+            assert_eq!(ring.push_slice(&chunk), fits);
+            if fits {
+                model.extend(chunk);
+                next += n as f32;
+            }
+        } else {
+            let mut out = vec![f32::NAN; n];
+            let enough = model.len() >= n;
+            assert_eq!(ring.pop_into(&mut out), enough);
+            if enough {
+                let expected: Vec<f32> = model.drain(..n).collect();
+                assert_eq!(out, expected);
+            }
+        }
+    }
+});